@@ -0,0 +1,120 @@
+#[cfg(feature = "instax_framer")]
+use std::path::PathBuf;
+
+pub(crate) const USAGE: &str =
+    "Usage: iced_counter [--open <image|project>] [--theme <name>] [--new-instance]";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct CliArgs {
+    #[cfg(feature = "instax_framer")]
+    pub open: Option<PathBuf>,
+    /// A built-in or user theme, looked up once the user themes are read
+    #[cfg(feature = "omni_themes")]
+    pub theme: Option<String>,
+    pub new_instance: bool,
+}
+
+impl CliArgs {
+    pub(crate) fn parse(args: impl IntoIterator<Item = String>) -> Result<CliArgs, String> {
+        let mut cli_args = CliArgs::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                #[cfg(feature = "instax_framer")]
+                "--open" => {
+                    let path = PathBuf::from(args.next().ok_or("Missing value for --open")?);
+
+                    // NOTE: resolve relative paths here, a forwarded path is read from another cwd
+                    cli_args.open = Some(std::path::absolute(&path).unwrap_or(path));
+                }
+                #[cfg(feature = "omni_themes")]
+                "--theme" => {
+                    cli_args.theme = Some(args.next().ok_or("Missing value for --theme")?);
+                }
+                "--new-instance" => cli_args.new_instance = true,
+                unknown => return Err(format!("Unknown argument: {unknown}")),
+            }
+        }
+
+        Ok(cli_args)
+    }
+
+    /// Turns the arguments back into a command line, used to forward them to a running instance
+    #[allow(unused)]
+    pub(crate) fn to_args(&self) -> Vec<String> {
+        #[allow(unused_mut)]
+        let mut args = vec![];
+
+        #[cfg(feature = "instax_framer")]
+        if let Some(path) = &self.open {
+            args.push("--open".to_owned());
+            args.push(path.to_string_lossy().into_owned());
+        }
+
+        #[cfg(feature = "omni_themes")]
+        if let Some(theme) = &self.theme {
+            args.push("--theme".to_owned());
+            args.push(theme.clone());
+        }
+
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| (*arg).to_owned()).collect()
+    }
+
+    #[test]
+    fn parses_what_to_args_writes() {
+        let cli_args = CliArgs {
+            #[cfg(feature = "instax_framer")]
+            open: Some(PathBuf::from("/photos/beach.jpg")),
+            #[cfg(feature = "omni_themes")]
+            theme: Some("My Theme".to_owned()),
+            new_instance: false,
+        };
+
+        assert_eq!(CliArgs::parse(cli_args.to_args()), Ok(cli_args));
+    }
+
+    #[test]
+    fn parses_new_instance() {
+        assert!(
+            CliArgs::parse(args(&["--new-instance"]))
+                .unwrap()
+                .new_instance
+        );
+        assert_eq!(CliArgs::parse(vec![]), Ok(CliArgs::default()));
+    }
+
+    #[cfg(feature = "omni_themes")]
+    #[test]
+    fn parses_theme_names() {
+        let cli_args = CliArgs::parse(args(&["--theme", "Tokyo Night Light"])).unwrap();
+
+        assert_eq!(cli_args.theme.as_deref(), Some("Tokyo Night Light"));
+    }
+
+    #[test]
+    fn rejects_a_missing_value() {
+        #[cfg(feature = "instax_framer")]
+        assert!(CliArgs::parse(args(&["--open"])).is_err());
+        #[cfg(feature = "omni_themes")]
+        assert!(CliArgs::parse(args(&["--new-instance", "--theme"])).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_arguments() {
+        assert_eq!(
+            CliArgs::parse(args(&["--verbose"])),
+            Err("Unknown argument: --verbose".to_owned())
+        );
+        assert!(CliArgs::parse(args(&["photo.jpg"])).is_err());
+    }
+}
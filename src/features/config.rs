@@ -56,6 +56,24 @@ impl Hash for OmniAppConfig {
         #[cfg(feature = "instax_framer")]
         {
             instax_framer.selected_file.hash(state);
+            instax_framer.crops.hash(state);
+            instax_framer.adjustments.hash(state);
+            instax_framer.film.hash(state);
+            instax_framer.template.hash(state);
+            instax_framer.export_settings.hash(state);
+            instax_framer.captions.hash(state);
+            instax_framer.caption_style.hash(state);
+            instax_framer.date_stamp.hash(state);
+            instax_framer.batch_name_pattern.hash(state);
+            instax_framer.sheet_files.hash(state);
+            instax_framer.sheet_settings.hash(state);
+            instax_framer.collage.hash(state);
+            instax_framer.show_collage.hash(state);
+            instax_framer.recent_files.hash(state);
+            instax_framer.max_pixels.hash(state);
+            instax_framer.embed_in_project.hash(state);
+            #[cfg(feature = "camera")]
+            instax_framer.photobooth.hash(state);
         }
     }
 }
@@ -0,0 +1,484 @@
+use std::{
+    hash::{Hash, Hasher},
+    ops::RangeInclusive,
+};
+
+use iced::{
+    Alignment, Element, Task,
+    widget::{button, column, pick_list, row, slider, text},
+};
+use image::RgbaImage;
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+
+use super::{InstaxFramer, InstaxFramerMessage};
+
+/// Size of a grain speck on the print, in millimeters
+const GRAIN_SIZE: f32 = 0.08;
+
+/// A base look imitating an Instax film stock, applied before the manual adjustments
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FilmPreset {
+    #[default]
+    Natural,
+    Color,
+    Monochrome,
+    Sepia,
+    Faded,
+}
+
+pub static FILM_PRESETS: &[FilmPreset] = &[
+    FilmPreset::Natural,
+    FilmPreset::Color,
+    FilmPreset::Monochrome,
+    FilmPreset::Sepia,
+    FilmPreset::Faded,
+];
+
+impl std::fmt::Display for FilmPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilmPreset::Natural => f.write_str("Natural"),
+            FilmPreset::Color => f.write_str("Instax Color"),
+            FilmPreset::Monochrome => f.write_str("Instax Monochrome"),
+            FilmPreset::Sepia => f.write_str("Instax Sepia"),
+            FilmPreset::Faded => f.write_str("Faded print"),
+        }
+    }
+}
+
+impl FilmPreset {
+    fn apply(self, [red, green, blue]: [f32; 3]) -> [f32; 3] {
+        let luma = luma([red, green, blue]);
+
+        match self {
+            FilmPreset::Natural => [red, green, blue],
+            // NOTE: warm highlights, slightly cyan shadows and blacks that never get fully black
+            FilmPreset::Color => {
+                let shadow = (1.0 - luma).powi(2);
+                let [red, green, blue] = saturate([red, green, blue], 1.08);
+
+                [
+                    lift(red * 1.03 - shadow * 0.02, 0.04),
+                    lift(green + shadow * 0.01, 0.04),
+                    lift(blue * 0.96 + shadow * 0.03, 0.05),
+                ]
+            }
+            FilmPreset::Monochrome => {
+                let luma = contrast(luma, 0.12);
+
+                [luma, luma, luma]
+            }
+            FilmPreset::Sepia => {
+                let luma = lift(luma, 0.05);
+
+                [luma * 1.07, luma * 0.94, luma * 0.76]
+            }
+            FilmPreset::Faded => {
+                let [red, green, blue] = saturate([red, green, blue], 0.7);
+
+                [
+                    lift(red, 0.1) * 0.98,
+                    lift(green, 0.09) * 0.96,
+                    lift(blue, 0.12) * 0.93,
+                ]
+            }
+        }
+    }
+}
+
+/// One of the manual sliders of [`Adjustments`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjustment {
+    Exposure,
+    Contrast,
+    Saturation,
+    Warmth,
+    Vignette,
+    Grain,
+}
+
+pub static ADJUSTMENTS: &[Adjustment] = &[
+    Adjustment::Exposure,
+    Adjustment::Contrast,
+    Adjustment::Saturation,
+    Adjustment::Warmth,
+    Adjustment::Vignette,
+    Adjustment::Grain,
+];
+
+impl std::fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Adjustment::Exposure => f.write_str("Exposure"),
+            Adjustment::Contrast => f.write_str("Contrast"),
+            Adjustment::Saturation => f.write_str("Saturation"),
+            Adjustment::Warmth => f.write_str("Warmth"),
+            Adjustment::Vignette => f.write_str("Vignette"),
+            Adjustment::Grain => f.write_str("Grain"),
+        }
+    }
+}
+
+impl Adjustment {
+    pub fn range(self) -> RangeInclusive<f32> {
+        match self {
+            Adjustment::Exposure => -2.0..=2.0,
+            Adjustment::Contrast | Adjustment::Saturation | Adjustment::Warmth => -1.0..=1.0,
+            Adjustment::Vignette | Adjustment::Grain => 0.0..=1.0,
+        }
+    }
+
+    pub fn format_value(self, value: f32) -> String {
+        match self {
+            Adjustment::Exposure => format!("{value:+.1} EV"),
+            Adjustment::Vignette | Adjustment::Grain => format!("{:.0}%", value * 100.0),
+            _ => format!("{:+.0}%", value * 100.0),
+        }
+    }
+}
+
+/// Non-destructive corrections, applied to the framed picture and never to the source file
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Adjustments {
+    pub preset: FilmPreset,
+    /// In stops
+    pub exposure: f32,
+    pub contrast: f32,
+    pub saturation: f32,
+    /// Positive towards orange, negative towards blue
+    pub warmth: f32,
+    pub vignette: f32,
+    pub grain: f32,
+}
+
+impl Hash for Adjustments {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.preset.hash(state);
+        self.exposure.to_bits().hash(state);
+        self.contrast.to_bits().hash(state);
+        self.saturation.to_bits().hash(state);
+        self.warmth.to_bits().hash(state);
+        self.vignette.to_bits().hash(state);
+        self.grain.to_bits().hash(state);
+    }
+}
+
+impl Adjustments {
+    pub fn get(&self, adjustment: Adjustment) -> f32 {
+        match adjustment {
+            Adjustment::Exposure => self.exposure,
+            Adjustment::Contrast => self.contrast,
+            Adjustment::Saturation => self.saturation,
+            Adjustment::Warmth => self.warmth,
+            Adjustment::Vignette => self.vignette,
+            Adjustment::Grain => self.grain,
+        }
+    }
+
+    pub fn set(&mut self, adjustment: Adjustment, value: f32) {
+        let range = adjustment.range();
+        let value = value.clamp(*range.start(), *range.end());
+
+        match adjustment {
+            Adjustment::Exposure => self.exposure = value,
+            Adjustment::Contrast => self.contrast = value,
+            Adjustment::Saturation => self.saturation = value,
+            Adjustment::Warmth => self.warmth = value,
+            Adjustment::Vignette => self.vignette = value,
+            Adjustment::Grain => self.grain = value,
+        }
+    }
+}
+
+/// Applies the preset and adjustments to a rendered picture, sized in print millimeters so the
+/// vignette and grain look the same in the preview and in the export
+pub fn apply(picture: &mut RgbaImage, adjustments: &Adjustments, pixels_per_mm: f32) {
+    if *adjustments == Adjustments::default() {
+        return;
+    }
+
+    let Adjustments {
+        preset,
+        exposure,
+        contrast: contrast_amount,
+        saturation,
+        warmth,
+        vignette,
+        grain,
+    } = *adjustments;
+
+    // NOTE: scaling linear light by 2^EV, approximated in gamma-encoded sRGB
+    let exposure_gain = exposure.exp2().powf(1.0 / 2.2);
+    let (half_width, half_height) = (picture.width() as f32 / 2.0, picture.height() as f32 / 2.0);
+    let grain_cell = (GRAIN_SIZE * pixels_per_mm).max(1.0);
+
+    for (x, y, pixel) in picture.enumerate_pixels_mut() {
+        let [red, green, blue, _] = pixel.0;
+        let color = [red, green, blue].map(|channel| f32::from(channel) / 255.0);
+
+        let color = preset.apply(color);
+        let color = color.map(|channel| contrast(channel * exposure_gain, contrast_amount * 0.5));
+        let [red, green, blue] = saturate(color, 1.0 + saturation);
+        let mut color = [
+            red + warmth * 0.08,
+            green + warmth * 0.02,
+            blue - warmth * 0.08,
+        ];
+
+        if vignette > 0.0 {
+            let dx = (x as f32 + 0.5 - half_width) / half_width;
+            let dy = (y as f32 + 0.5 - half_height) / half_height;
+            let distance = (dx * dx + dy * dy).sqrt() / std::f32::consts::SQRT_2;
+            let falloff = 1.0 - vignette * 0.75 * smoothstep(0.3, 1.0, distance);
+
+            color = color.map(|channel| channel * falloff);
+        }
+
+        if grain > 0.0 {
+            let noise = noise(
+                (x as f32 / grain_cell) as u32,
+                (y as f32 / grain_cell) as u32,
+            );
+            // NOTE: film grain shows most in the midtones
+            let midtones = 1.0 - (luma(color) * 2.0 - 1.0).powi(2);
+            let offset = noise * grain * 0.15 * (0.35 + 0.65 * midtones);
+
+            color = color.map(|channel| channel + offset);
+        }
+
+        for (channel, value) in pixel.0.iter_mut().zip(color) {
+            *channel = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+    }
+}
+
+fn luma([red, green, blue]: [f32; 3]) -> f32 {
+    0.2126 * red + 0.7152 * green + 0.0722 * blue
+}
+
+fn saturate(color: [f32; 3], factor: f32) -> [f32; 3] {
+    let luma = luma(color);
+
+    color.map(|channel| luma + (channel - luma) * factor)
+}
+
+fn contrast(channel: f32, amount: f32) -> f32 {
+    (channel - 0.5) * (1.0 + amount) + 0.5
+}
+
+/// Raises the black point, like the milky shadows of instant film
+fn lift(channel: f32, black: f32) -> f32 {
+    black + channel * (1.0 - black)
+}
+
+fn smoothstep(edge_start: f32, edge_end: f32, value: f32) -> f32 {
+    let t = ((value - edge_start) / (edge_end - edge_start)).clamp(0.0, 1.0);
+
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Stable noise from -1.0 to 1.0, so grain does not flicker between preview updates
+fn noise(x: u32, y: u32) -> f32 {
+    let mut hash = x.wrapping_mul(0x9E37_79B1) ^ y.wrapping_mul(0x85EB_CA77);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2C1B_3C6D);
+    hash ^= hash >> 12;
+
+    hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+impl InstaxFramer {
+    /// Handles the adjustment messages
+    pub(super) fn update_adjustments(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::AdjustmentsToggled => {
+                self.show_adjustments = !self.show_adjustments;
+            }
+            InstaxFramerMessage::FilmPresetSelected(preset) => {
+                if let Some(adjustments) = self.adjustments_mut() {
+                    adjustments.preset = preset;
+                }
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::AdjustmentChanged(adjustment, value) => {
+                if let Some(adjustments) = self.adjustments_mut() {
+                    adjustments.set(adjustment, value);
+                }
+
+                return self.compose_preview();
+            }
+            // NOTE: only there to save the config once the slider is let go
+            InstaxFramerMessage::AdjustmentReleased => {}
+            InstaxFramerMessage::AdjustmentsReset => {
+                if let Some(selected_file) = &self.selected_file {
+                    self.adjustments.remove(selected_file);
+                }
+
+                return self.compose_preview();
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    pub(super) fn adjustment_controls(&self) -> Option<Element<'_, InstaxFramerMessage>> {
+        if !self.show_adjustments {
+            return None;
+        }
+
+        let adjustments = self.adjustments();
+
+        let sliders = ADJUSTMENTS.iter().map(|&adjustment| {
+            let value = adjustments.get(adjustment);
+
+            row![
+                text(adjustment.to_string()).size(12).width(64),
+                slider(adjustment.range(), value, move |value| {
+                    InstaxFramerMessage::AdjustmentChanged(adjustment, value)
+                })
+                .step(0.05)
+                .on_release(InstaxFramerMessage::AdjustmentReleased)
+                .width(100),
+                text(adjustment.format_value(value)).size(12).width(48),
+            ]
+            .spacing(4)
+            .align_y(Alignment::Center)
+            .into()
+        });
+
+        Some(
+            column![
+                row![
+                    pick_list(
+                        FILM_PRESETS,
+                        Some(adjustments.preset),
+                        InstaxFramerMessage::FilmPresetSelected
+                    ),
+                    button(text("Reset"))
+                        .style(button::secondary)
+                        .on_press_maybe(
+                            (adjustments != Adjustments::default())
+                                .then_some(InstaxFramerMessage::AdjustmentsReset)
+                        ),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+                row(sliders).spacing(12).wrap(),
+            ]
+            .spacing(4)
+            .align_x(Alignment::Center)
+            .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    fn adjusted(color: Rgba<u8>, adjustments: Adjustments) -> RgbaImage {
+        let mut picture = RgbaImage::from_pixel(20, 20, color);
+        apply(&mut picture, &adjustments, 10.0);
+
+        picture
+    }
+
+    #[test]
+    fn default_adjustments_leave_the_picture_alone() {
+        let mut picture = RgbaImage::from_fn(8, 8, |x, y| Rgba([x as u8 * 30, y as u8 * 30, 7, 9]));
+        let original = picture.clone();
+
+        apply(&mut picture, &Adjustments::default(), 10.0);
+
+        assert_eq!(picture, original);
+    }
+
+    #[test]
+    fn values_are_clamped_to_their_slider() {
+        let mut adjustments = Adjustments::default();
+
+        for adjustment in ADJUSTMENTS.iter().copied() {
+            adjustments.set(adjustment, 10.0);
+            assert_eq!(adjustments.get(adjustment), *adjustment.range().end());
+
+            adjustments.set(adjustment, -10.0);
+            assert_eq!(adjustments.get(adjustment), *adjustment.range().start());
+        }
+    }
+
+    #[test]
+    fn monochrome_and_desaturation_remove_color_but_not_alpha() {
+        let color = Rgba([200, 60, 30, 128]);
+
+        for adjustments in [
+            Adjustments {
+                preset: FilmPreset::Monochrome,
+                ..Adjustments::default()
+            },
+            Adjustments {
+                saturation: -1.0,
+                ..Adjustments::default()
+            },
+        ] {
+            let Rgba([red, green, blue, alpha]) = *adjusted(color, adjustments).get_pixel(0, 0);
+
+            assert!(red.abs_diff(green) <= 1 && green.abs_diff(blue) <= 1);
+            assert_eq!(alpha, 128);
+        }
+    }
+
+    #[test]
+    fn exposure_brightens_and_darkens() {
+        let gray = Rgba([100, 100, 100, 255]);
+        let exposed = |exposure| {
+            adjusted(
+                gray,
+                Adjustments {
+                    exposure,
+                    ..Adjustments::default()
+                },
+            )
+            .get_pixel(0, 0)[0]
+        };
+
+        assert!(exposed(1.0) > 100);
+        assert!(exposed(-1.0) < 100);
+    }
+
+    #[test]
+    fn the_vignette_darkens_the_corners_only() {
+        let picture = adjusted(
+            Rgba([200, 200, 200, 255]),
+            Adjustments {
+                vignette: 1.0,
+                ..Adjustments::default()
+            },
+        );
+
+        assert_eq!(picture.get_pixel(10, 10)[0], 200);
+        assert!(picture.get_pixel(0, 0)[0] < 100);
+    }
+
+    #[test]
+    fn grain_is_the_same_on_every_render() {
+        let grainy = Adjustments {
+            grain: 1.0,
+            ..Adjustments::default()
+        };
+        let picture = adjusted(Rgba([128, 128, 128, 255]), grainy);
+
+        assert_eq!(picture, adjusted(Rgba([128, 128, 128, 255]), grainy));
+        assert!(picture.pixels().any(|pixel| pixel[0] != 128));
+        assert!((0..64).all(|x| (-1.0..=1.0).contains(&noise(x, x * 7))));
+    }
+}
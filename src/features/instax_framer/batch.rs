@@ -0,0 +1,534 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use iced::{
+    Alignment, Element, Length, Task,
+    futures::{Stream, StreamExt},
+    task,
+    widget::{button, column, progress_bar, row, scrollable, text, text_input},
+};
+use rfd::{FileDialog, MessageDialog, MessageLevel};
+
+use super::{
+    Crop, ExportSettings, Framing, IMAGE_EXTENSIONS, ImageMetadata, InstaxFilm, InstaxFramer,
+    InstaxFramerMessage, LoadedImage, browser, export, frame, workers,
+};
+
+pub const DEFAULT_NAME_PATTERN: &str = "{name}_instax";
+
+/// Everything a batch needs, captured when it starts so that later edits do not leak into it
+#[derive(Debug)]
+pub struct BatchJob {
+    pub files: Vec<PathBuf>,
+    pub output_dir: PathBuf,
+    pub name_pattern: String,
+    /// Applied to every file, except for the crop and date stamp which are per file
+    pub framing: Framing,
+    pub crops: BTreeMap<PathBuf, Crop>,
+    pub date_stamp: bool,
+    pub export_settings: ExportSettings,
+    pub max_pixels: u64,
+    pub cancelled: Arc<AtomicBool>,
+}
+
+impl BatchJob {
+    /// Some output would be named like its source in the source's own folder, differing by the
+    /// extension at most
+    pub fn names_outputs_like_sources(&self) -> bool {
+        let output_dir = self.output_dir.canonicalize().ok();
+
+        self.files.iter().enumerate().any(|(index, file)| {
+            let is_in_output_dir = output_dir.is_some()
+                && file.parent().and_then(|dir| dir.canonicalize().ok()) == output_dir;
+
+            is_in_output_dir
+                && file.file_stem().is_some_and(|file_stem| {
+                    file_name(&self.name_pattern, file, index, self.framing.film)
+                        == file_stem.to_string_lossy()
+                })
+        })
+    }
+}
+
+/// A framed file, with why its metadata was left out when it could not be kept
+#[derive(Debug, Clone)]
+pub struct FramedFile {
+    pub path: PathBuf,
+    pub metadata_error: Option<String>,
+}
+
+/// State of a running or finished batch, kept until its report is dismissed
+#[derive(Debug, Clone)]
+pub struct BatchRun {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failures: Vec<(PathBuf, String)>,
+    /// Files written, but not quite as asked
+    pub warnings: Vec<(PathBuf, String)>,
+    pub output_dir: PathBuf,
+    pub finished: bool,
+    cancelled: Arc<AtomicBool>,
+    handle: task::Handle,
+}
+
+impl BatchRun {
+    pub fn new(
+        total: usize,
+        output_dir: PathBuf,
+        cancelled: Arc<AtomicBool>,
+        handle: task::Handle,
+    ) -> BatchRun {
+        Self {
+            total,
+            succeeded: 0,
+            failures: vec![],
+            warnings: vec![],
+            output_dir,
+            finished: false,
+            cancelled,
+            handle,
+        }
+    }
+
+    pub fn done(&self) -> usize {
+        self.succeeded + self.failures.len()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Stops queued files right away, files being framed stop before they are written
+    pub fn cancel(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.handle.abort();
+        self.finished = true;
+    }
+}
+
+/// Frames every file of the job on a bounded pool of blocking workers, yielding each result
+pub fn run(job: BatchJob) -> impl Stream<Item = (PathBuf, Result<FramedFile, String>)> {
+    let files = job.files.iter().cloned().enumerate().collect();
+
+    workers::run_blocking(files, move |(index, file)| frame_file(&job, index, &file))
+        .map(|((_, file), result)| (file, result.and_then(|result| result)))
+}
+
+fn frame_file(job: &BatchJob, index: usize, file: &Path) -> Result<FramedFile, String> {
+    let check_cancelled = || {
+        if job.cancelled.load(Ordering::Relaxed) {
+            Err("Cancelled".to_owned())
+        } else {
+            Ok(())
+        }
+    };
+
+    check_cancelled()?;
+    let (image, exif) =
+        LoadedImage::decode(file, job.max_pixels).map_err(|e| format!("Failed to load: {e}"))?;
+
+    let mut framing = job.framing.clone();
+    framing.crop = job.crops.get(file).copied().unwrap_or_default();
+    if job.date_stamp {
+        let metadata = ImageMetadata::read(exif.as_deref(), image.width(), image.height());
+        framing.inscription.date_stamp = LoadedImage::taken_on(file, &metadata);
+    }
+
+    let framed_image = frame::compose(&image, &framing, job.export_settings.dpi.pixels_per_mm());
+    let encoded = export::encode(framed_image, &job.export_settings, exif.as_deref())
+        .map_err(|e| format!("Failed to encode: {e}"))?;
+
+    check_cancelled()?;
+    let (mut output, destination) = create_unique(
+        &job.output_dir,
+        &file_name(&job.name_pattern, file, index, framing.film),
+        job.export_settings.format.extension(),
+    )
+    .map_err(|e| format!("Failed to write: {e}"))?;
+    if let Err(e) = output.write_all(&encoded.bytes) {
+        let _ = std::fs::remove_file(&destination);
+
+        return Err(format!("Failed to write: {e}"));
+    }
+
+    Ok(FramedFile {
+        path: destination,
+        metadata_error: encoded.metadata_error,
+    })
+}
+
+/// Creates `<name>.<extension>` in the directory, or `<name>_2.<extension>` and so on when it is
+/// taken, so that files of the same name in a batch, or already there, are never replaced
+fn create_unique(dir: &Path, name: &str, extension: &str) -> io::Result<(File, PathBuf)> {
+    let mut number = 1;
+
+    loop {
+        let path = if number == 1 {
+            dir.join(format!("{name}.{extension}"))
+        } else {
+            dir.join(format!("{name}_{number}.{extension}"))
+        };
+
+        match File::options().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((file, path)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => number += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Expands `{name}`, `{index}` and `{film}` in the pattern, without the extension
+fn file_name(pattern: &str, file: &Path, index: usize, film: InstaxFilm) -> String {
+    let name = file
+        .file_stem()
+        .map_or("image".into(), |file_stem| file_stem.to_string_lossy());
+    let film = match film {
+        InstaxFilm::Mini => "mini",
+        InstaxFilm::Square => "square",
+        InstaxFilm::Wide => "wide",
+    };
+
+    // NOTE: without a per-file placeholder, every file would overwrite the previous one
+    let pattern = if pattern.contains("{name}") || pattern.contains("{index}") {
+        pattern.to_owned()
+    } else {
+        format!("{pattern}_{{index}}")
+    };
+
+    pattern
+        .replace("{name}", &name)
+        .replace("{index}", &format!("{:03}", index + 1))
+        .replace("{film}", film)
+        .replace(['/', '\\'], "_")
+}
+
+impl InstaxFramer {
+    /// Handles the batch messages
+    pub(super) fn update_batch(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::BatchNamePatternEdited(pattern) => {
+                self.batch_name_pattern = pattern;
+            }
+            InstaxFramerMessage::BatchFolderRequested => {
+                let Some(folder) = FileDialog::new().pick_folder() else {
+                    return Task::none();
+                };
+
+                let files = browser::list_images(&folder);
+
+                if files.is_empty() {
+                    let _ = MessageDialog::new()
+                        .set_title("Nothing to frame")
+                        .set_description(format!(
+                            "No {} images found in {}",
+                            IMAGE_EXTENSIONS.join("/"),
+                            folder.to_string_lossy()
+                        ))
+                        .set_level(MessageLevel::Info)
+                        .show();
+                } else {
+                    return self.start_batch(files);
+                }
+            }
+            InstaxFramerMessage::BatchFilesRequested => {
+                if let Some(files) = FileDialog::new()
+                    .add_filter("image", IMAGE_EXTENSIONS)
+                    .pick_files()
+                {
+                    return self.start_batch(files);
+                }
+            }
+            InstaxFramerMessage::BatchFileFinished(file, result) => {
+                if let Some(batch) = &mut self.batch {
+                    match result {
+                        Ok(framed_file) => {
+                            batch.succeeded += 1;
+                            if let Some(e) = framed_file.metadata_error {
+                                batch.warnings.push((
+                                    file,
+                                    format!(
+                                        "{} was written without metadata: {e}",
+                                        framed_file.path.to_string_lossy()
+                                    ),
+                                ));
+                            }
+                        }
+                        Err(e) => batch.failures.push((file, e)),
+                    }
+                }
+            }
+            InstaxFramerMessage::BatchFinished => {
+                if let Some(batch) = &mut self.batch {
+                    batch.finished = true;
+                }
+            }
+            InstaxFramerMessage::BatchCancelled => {
+                if let Some(batch) = &mut self.batch {
+                    batch.cancel();
+                }
+            }
+            InstaxFramerMessage::BatchReportDismissed => self.batch = None,
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    pub(super) fn batch_controls(&self) -> Element<'_, InstaxFramerMessage> {
+        let is_running = self.batch.as_ref().is_some_and(|batch| !batch.finished);
+
+        let controls = row![
+            text("Batch"),
+            text_input("File names, e.g. {name}_{film}", &self.batch_name_pattern)
+                .on_input(InstaxFramerMessage::BatchNamePatternEdited)
+                .width(180),
+            button(text("Frame folder..."))
+                .on_press_maybe((!is_running).then_some(InstaxFramerMessage::BatchFolderRequested)),
+            button(text("Frame files..."))
+                .on_press_maybe((!is_running).then_some(InstaxFramerMessage::BatchFilesRequested)),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .wrap();
+
+        let status: Option<Element<'_, InstaxFramerMessage>> = self.batch.as_ref().map(|batch| {
+            if !batch.finished {
+                return row![
+                    progress_bar(0.0..=batch.total as f32, batch.done() as f32)
+                        .length(160)
+                        .girth(8),
+                    text(format!("{} / {}", batch.done(), batch.total)),
+                    button(text("Cancel"))
+                        .style(button::danger)
+                        .on_press(InstaxFramerMessage::BatchCancelled),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .into();
+            }
+
+            let summary = format!(
+                "Framed {} of {} into {}{}",
+                batch.succeeded,
+                batch.total,
+                batch.output_dir.to_string_lossy(),
+                if batch.is_cancelled() {
+                    " (cancelled)"
+                } else {
+                    ""
+                }
+            );
+            let failures = batch
+                .failures
+                .iter()
+                .chain(&batch.warnings)
+                .map(|(file, error)| {
+                    text(format!(
+                        "{}: {error}",
+                        file.file_name()
+                            .map_or(file.to_string_lossy(), |file_name| file_name
+                                .to_string_lossy())
+                    ))
+                    .size(12)
+                    .into()
+                });
+
+            column![
+                row![
+                    text(summary),
+                    button(text("Dismiss"))
+                        .style(button::secondary)
+                        .on_press(InstaxFramerMessage::BatchReportDismissed),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+            ]
+            .push(
+                (!batch.failures.is_empty() || !batch.warnings.is_empty())
+                    .then(|| scrollable(column(failures).spacing(2)).height(Length::Shrink)),
+            )
+            .spacing(4)
+            .max_width(520)
+            .into()
+        });
+
+        column![controls]
+            .push(status)
+            .spacing(4)
+            .align_x(Alignment::Center)
+            .into()
+    }
+
+    /// Frames every file with the current film, template, look and caption
+    pub(super) fn start_batch(&mut self, files: Vec<PathBuf>) -> Task<InstaxFramerMessage> {
+        let Some(output_dir) = FileDialog::new()
+            .set_title("Choose the output folder")
+            .pick_folder()
+        else {
+            return Task::none();
+        };
+
+        let mut framing = self.framing();
+        framing.inscription.date_stamp = None;
+
+        let job = BatchJob {
+            files,
+            output_dir,
+            name_pattern: self.batch_name_pattern.clone(),
+            framing,
+            crops: self.crops.clone(),
+            date_stamp: self.date_stamp,
+            export_settings: self.export_settings.clone(),
+            max_pixels: self.max_pixels,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        };
+
+        if job.names_outputs_like_sources() {
+            let _ = MessageDialog::new()
+                .set_title("Batch not started")
+                .set_description(
+                    "The outputs would be named like the images they come from, in the same \
+                     folder. Pick another folder, or add to the name pattern.",
+                )
+                .set_level(MessageLevel::Warning)
+                .show();
+
+            return Task::none();
+        }
+
+        let files_count = job.files.len();
+        let cancelled = job.cancelled.clone();
+        let output_dir = job.output_dir.clone();
+
+        let (task, handle) = Task::stream(run(job))
+            .map(|(file, result)| InstaxFramerMessage::BatchFileFinished(file, result))
+            .chain(Task::done(InstaxFramerMessage::BatchFinished))
+            .abortable();
+
+        self.batch = Some(BatchRun::new(files_count, output_dir, cancelled, handle));
+
+        task
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage};
+
+    use super::*;
+    use crate::features::instax_framer::DEFAULT_MAX_PIXELS;
+
+    fn job(files: Vec<PathBuf>, output_dir: &Path, name_pattern: &str) -> BatchJob {
+        BatchJob {
+            files,
+            output_dir: output_dir.to_owned(),
+            name_pattern: name_pattern.to_owned(),
+            framing: Framing::default(),
+            crops: BTreeMap::new(),
+            date_stamp: false,
+            export_settings: ExportSettings::default(),
+            max_pixels: DEFAULT_MAX_PIXELS,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn write_image(path: &Path) {
+        RgbImage::from_pixel(4, 3, Rgb([200, 40, 40]))
+            .save(path)
+            .unwrap();
+    }
+
+    #[test]
+    fn file_name_expands_the_placeholders() {
+        let file = Path::new("/photos/beach.jpg");
+
+        assert_eq!(
+            file_name(DEFAULT_NAME_PATTERN, file, 0, InstaxFilm::Mini),
+            "beach_instax"
+        );
+        assert_eq!(
+            file_name("{index}-{film}-{name}", file, 11, InstaxFilm::Wide),
+            "012-wide-beach"
+        );
+    }
+
+    #[test]
+    fn file_name_numbers_patterns_without_a_per_file_placeholder() {
+        let file = Path::new("/photos/beach.jpg");
+
+        assert_eq!(
+            file_name("holiday", file, 1, InstaxFilm::Square),
+            "holiday_002"
+        );
+        assert_eq!(
+            file_name("{film}", file, 0, InstaxFilm::Square),
+            "square_001"
+        );
+    }
+
+    #[test]
+    fn file_name_keeps_outputs_in_the_folder() {
+        assert_eq!(
+            file_name("a/{name}\\b", Path::new("beach.jpg"), 0, InstaxFilm::Mini),
+            "a_beach_b"
+        );
+    }
+
+    #[test]
+    fn outputs_of_the_same_name_are_numbered() {
+        let sources = tempfile::tempdir().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let files = vec![sources.path().join("a.jpg"), sources.path().join("a.png")];
+        for file in &files {
+            write_image(file);
+        }
+        let job = job(files.clone(), output_dir.path(), "{name}");
+
+        let first = frame_file(&job, 0, &files[0]).unwrap();
+        let second = frame_file(&job, 1, &files[1]).unwrap();
+
+        assert_eq!(first.path, output_dir.path().join("a.png"));
+        assert_eq!(second.path, output_dir.path().join("a_2.png"));
+        assert!(image::open(&first.path).is_ok());
+        assert!(image::open(&second.path).is_ok());
+    }
+
+    #[test]
+    fn existing_files_are_never_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.png"), "keep me").unwrap();
+
+        let (_, path) = create_unique(dir.path(), "a", "png").unwrap();
+
+        assert_eq!(path, dir.path().join("a_2.png"));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.png")).unwrap(),
+            "keep me"
+        );
+    }
+
+    #[test]
+    fn outputs_named_like_their_sources_in_their_folder_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let other_dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.jpg");
+        write_image(&file);
+
+        assert!(job(vec![file.clone()], dir.path(), "{name}").names_outputs_like_sources());
+        assert!(
+            !job(vec![file.clone()], dir.path(), DEFAULT_NAME_PATTERN).names_outputs_like_sources()
+        );
+        assert!(!job(vec![file], other_dir.path(), "{name}").names_outputs_like_sources());
+    }
+}
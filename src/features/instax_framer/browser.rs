@@ -0,0 +1,377 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use iced::{
+    Alignment, Element, Length, Task,
+    futures::{Stream, StreamExt},
+    task,
+    widget::{button, column, container, image as iced_image, row, scrollable, text},
+};
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    InstaxFramer, InstaxFramerMessage, LoadedImage, is_supported_image, metadata, workers,
+};
+
+/// Unpinned entries beyond this are forgotten, pinned ones are always kept
+pub const MAX_RECENT_FILES: usize = 12;
+/// Size of the folder thumbnails on screen, decoded at twice that for high DPI displays
+pub const THUMBNAIL_SIZE: u32 = 64;
+/// Larger images only get a thumbnail when they embed one, so that a folder of huge scans
+/// cannot take all memory with every worker decoding one
+const THUMBNAIL_MAX_PIXELS: u64 = 50_000_000;
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    #[cfg_attr(feature = "config", serde(default))]
+    pub pinned: bool,
+}
+
+/// Moves the file to the front of the list, keeping its pin, and forgets the oldest unpinned ones
+pub fn remember(recent_files: &mut Vec<RecentFile>, path: &Path) {
+    let pinned = recent_files
+        .iter()
+        .position(|recent_file| recent_file.path == path)
+        .is_some_and(|index| recent_files.remove(index).pinned);
+
+    recent_files.insert(
+        0,
+        RecentFile {
+            path: path.to_owned(),
+            pinned,
+        },
+    );
+
+    let mut unpinned = 0;
+    recent_files.retain(|recent_file| {
+        unpinned += usize::from(!recent_file.pinned);
+
+        recent_file.pinned || unpinned <= MAX_RECENT_FILES
+    });
+}
+
+/// The images of the folder the current image is in, with thumbnails as they get decoded
+#[derive(Debug, Clone)]
+pub struct FolderBrowser {
+    pub dir: PathBuf,
+    pub files: Vec<PathBuf>,
+    pub thumbnails: BTreeMap<PathBuf, iced_image::Handle>,
+    handle: task::Handle,
+}
+
+impl FolderBrowser {
+    pub fn new(dir: PathBuf, files: Vec<PathBuf>, handle: task::Handle) -> FolderBrowser {
+        Self {
+            dir,
+            files,
+            thumbnails: BTreeMap::new(),
+            handle,
+        }
+    }
+
+    /// Stops decoding thumbnails, once another folder is shown
+    pub fn abort(&self) {
+        self.handle.abort();
+    }
+
+    /// The file before or after this one, wrapping around at both ends
+    pub fn step(&self, file: &Path, forward: bool) -> Option<&PathBuf> {
+        let index = self.files.iter().position(|other| other == file)?;
+        let count = self.files.len();
+
+        self.files.get(if forward {
+            (index + 1) % count
+        } else {
+            (index + count - 1) % count
+        })
+    }
+}
+
+/// Every supported image of the folder, sorted by name
+pub fn list_images(dir: &Path) -> Vec<PathBuf> {
+    let mut files = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| is_supported_image(path))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    files.sort();
+
+    files
+}
+
+/// Decodes the thumbnails on a few blocking workers, skipping files that fail to load
+pub fn load_thumbnails(
+    files: Vec<PathBuf>,
+    max_pixels: u64,
+) -> impl Stream<Item = (PathBuf, iced_image::Handle)> {
+    workers::run_blocking(files, move |file| thumbnail(&file, max_pixels))
+        .filter_map(async |(file, thumbnail)| Some((file, thumbnail.ok()??)))
+}
+
+/// The embedded preview when there is one, the downscaled image otherwise
+fn thumbnail(file: &Path, max_pixels: u64) -> Option<iced_image::Handle> {
+    let image = match metadata::embedded_thumbnail(file) {
+        Some(image) => image,
+        None => {
+            LoadedImage::decode(file, max_pixels.min(THUMBNAIL_MAX_PIXELS))
+                .ok()?
+                .0
+        }
+    };
+    let thumbnail = image
+        .thumbnail(THUMBNAIL_SIZE * 2, THUMBNAIL_SIZE * 2)
+        .to_rgba8();
+
+    Some(iced_image::Handle::from_rgba(
+        thumbnail.width(),
+        thumbnail.height(),
+        thumbnail.into_raw(),
+    ))
+}
+
+impl InstaxFramer {
+    /// Handles the recent files and folder messages
+    pub(super) fn update_browser(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::RecentFilesToggled => {
+                self.show_recent_files = !self.show_recent_files;
+            }
+            InstaxFramerMessage::RecentFilePinToggled(path) => {
+                if let Some(recent_file) = self
+                    .recent_files
+                    .iter_mut()
+                    .find(|recent_file| recent_file.path == path)
+                {
+                    recent_file.pinned = !recent_file.pinned;
+                }
+            }
+            InstaxFramerMessage::RecentFilesCleared => {
+                self.recent_files.retain(|recent_file| recent_file.pinned);
+            }
+            InstaxFramerMessage::FolderThumbnailLoaded(file, thumbnail) => {
+                if let Some(folder) = &mut self.folder
+                    && file.parent() == Some(folder.dir.as_path())
+                {
+                    folder.thumbnails.insert(file, thumbnail);
+                }
+            }
+            InstaxFramerMessage::AdjacentImageRequested { forward } => {
+                if let (Some(folder), Some(selected_file)) = (&self.folder, &self.selected_file)
+                    && let Some(file) = folder.step(selected_file, forward)
+                {
+                    return Task::done(InstaxFramerMessage::ImagePicked(file.clone()));
+                }
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    pub(super) fn recent_files_list(&self) -> Option<Element<'_, InstaxFramerMessage>> {
+        if self.recent_files.is_empty() {
+            return None;
+        }
+
+        // NOTE: pinned files first, each group from the most recently opened
+        let entries = self
+            .recent_files
+            .iter()
+            .filter(|recent_file| recent_file.pinned)
+            .chain(
+                self.recent_files
+                    .iter()
+                    .filter(|recent_file| !recent_file.pinned),
+            )
+            .map(|RecentFile { path, pinned }| {
+                row![
+                    button(text(if *pinned { "★" } else { "☆" }))
+                        .style(button::text)
+                        .on_press(InstaxFramerMessage::RecentFilePinToggled(path.clone())),
+                    button(text(
+                        path.file_name()
+                            .map_or(path.to_string_lossy(), |file_name| file_name
+                                .to_string_lossy())
+                    ))
+                    .style(button::text)
+                    .on_press(InstaxFramerMessage::ImagePicked(path.clone())),
+                    text(
+                        path.parent()
+                            .map(|dir| dir.to_string_lossy())
+                            .unwrap_or_default()
+                    )
+                    .size(12),
+                ]
+                .spacing(4)
+                .align_y(Alignment::Center)
+                .into()
+            });
+
+        Some(
+            column![
+                container(scrollable(column(entries))).max_height(200),
+                button(text("Clear history"))
+                    .style(button::secondary)
+                    .on_press_maybe(
+                        self.recent_files
+                            .iter()
+                            .any(|recent_file| !recent_file.pinned)
+                            .then_some(InstaxFramerMessage::RecentFilesCleared)
+                    ),
+            ]
+            .spacing(4)
+            .align_x(Alignment::Center)
+            .into(),
+        )
+    }
+
+    /// Thumbnails of the images next to the current one, ← and → step through them
+    pub(super) fn folder_strip(&self) -> Option<Element<'_, InstaxFramerMessage>> {
+        let folder = self
+            .folder
+            .as_ref()
+            .filter(|folder| folder.files.len() > 1)?;
+        let size = THUMBNAIL_SIZE as f32;
+
+        let thumbnails = folder.files.iter().map(|file| {
+            let thumbnail: Element<'_, InstaxFramerMessage> = match folder.thumbnails.get(file) {
+                Some(thumbnail) => iced_image(thumbnail.clone())
+                    .width(size)
+                    .height(size)
+                    .into(),
+                None => container(text("…")).center(size).into(),
+            };
+
+            button(thumbnail)
+                .padding(2)
+                .style(if self.selected_file.as_ref() == Some(file) {
+                    button::primary
+                } else {
+                    button::text
+                })
+                .on_press(InstaxFramerMessage::ImagePicked(file.clone()))
+                .into()
+        });
+
+        Some(
+            row![
+                button(text("◀"))
+                    .style(button::secondary)
+                    .on_press(InstaxFramerMessage::AdjacentImageRequested { forward: false }),
+                scrollable(row(thumbnails).spacing(4))
+                    .horizontal()
+                    .width(Length::Fill),
+                button(text("▶"))
+                    .style(button::secondary)
+                    .on_press(InstaxFramerMessage::AdjacentImageRequested { forward: true }),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into(),
+        )
+    }
+
+    /// Lists the folder of the image, unless it is already shown, and decodes its thumbnails
+    pub(super) fn browse_folder(&mut self, file: &Path) -> Task<InstaxFramerMessage> {
+        let Some(dir) = file.parent() else {
+            return Task::none();
+        };
+
+        if let Some(folder) = &self.folder {
+            if folder.dir == dir {
+                return Task::none();
+            }
+
+            folder.abort();
+        }
+
+        let files = list_images(dir);
+        let (task, handle) = Task::stream(load_thumbnails(files.clone(), self.max_pixels))
+            .map(|(file, thumbnail)| InstaxFramerMessage::FolderThumbnailLoaded(file, thumbnail))
+            .abortable();
+
+        self.folder = Some(FolderBrowser::new(dir.to_owned(), files, handle));
+
+        task
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::Task;
+
+    use super::*;
+
+    fn paths(recent_files: &[RecentFile]) -> Vec<&str> {
+        recent_files
+            .iter()
+            .map(|recent_file| recent_file.path.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reopened_files_move_to_the_front_with_their_pin() {
+        let mut recent_files = vec![];
+        remember(&mut recent_files, Path::new("a"));
+        remember(&mut recent_files, Path::new("b"));
+        recent_files[1].pinned = true;
+
+        remember(&mut recent_files, Path::new("a"));
+
+        assert_eq!(paths(&recent_files), ["a", "b"]);
+        assert!(recent_files[0].pinned);
+        assert!(!recent_files[1].pinned);
+    }
+
+    #[test]
+    fn the_oldest_unpinned_files_are_forgotten() {
+        let mut recent_files = vec![];
+        remember(&mut recent_files, Path::new("pinned"));
+        recent_files[0].pinned = true;
+        for index in 0..MAX_RECENT_FILES + 2 {
+            remember(&mut recent_files, Path::new(&index.to_string()));
+        }
+
+        assert_eq!(recent_files.len(), MAX_RECENT_FILES + 1);
+        assert_eq!(paths(&recent_files)[0], (MAX_RECENT_FILES + 1).to_string());
+        assert!(!paths(&recent_files).contains(&"0"));
+        assert!(!paths(&recent_files).contains(&"1"));
+        assert_eq!(paths(&recent_files).last(), Some(&"pinned"));
+    }
+
+    #[test]
+    fn stepping_wraps_around_the_folder() {
+        let (_, handle) = Task::<()>::none().abortable();
+        let files = ["a.jpg", "b.jpg", "c.jpg"].map(PathBuf::from).to_vec();
+        let browser = FolderBrowser::new(PathBuf::new(), files.clone(), handle);
+
+        assert_eq!(browser.step(&files[0], true), Some(&files[1]));
+        assert_eq!(browser.step(&files[2], true), Some(&files[0]));
+        assert_eq!(browser.step(&files[0], false), Some(&files[2]));
+        assert_eq!(browser.step(Path::new("d.jpg"), true), None);
+    }
+
+    #[test]
+    fn lists_only_images_sorted_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["b.png", "a.JPG", "notes.txt", "c.webp"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        assert_eq!(
+            list_images(dir.path()),
+            ["a.JPG", "b.png", "c.webp"].map(|name| dir.path().join(name))
+        );
+    }
+}
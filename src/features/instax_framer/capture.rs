@@ -0,0 +1,593 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use iced::{
+    Alignment, Border, Color, Element, Length, Task, Theme,
+    futures::{SinkExt, Stream, channel::mpsc},
+    stream,
+    widget::{button, column, container, image as iced_image, pick_list, row, stack, text},
+};
+#[cfg(feature = "webcam")]
+use image::DynamicImage;
+use image::{Rgba, RgbaImage};
+use rfd::{FileDialog, MessageDialog, MessageLevel};
+
+use super::{
+    DEFAULT_MAX_PIXELS, InstaxFramer, InstaxFramerMessage, LoadedImage, PhotoboothRun,
+    PhotoboothSettings, browser,
+};
+
+/// Frames are sent to the viewfinder at most this often, around 15 per second
+const FRAME_INTERVAL: Duration = Duration::from_millis(66);
+const TEST_PATTERN_SIZE: (u32, u32) = (640, 480);
+const TEST_PATTERN_BARS: [Rgba<u8>; 7] = [
+    Rgba([192, 192, 192, 255]),
+    Rgba([192, 192, 0, 255]),
+    Rgba([0, 192, 192, 255]),
+    Rgba([0, 192, 0, 255]),
+    Rgba([192, 0, 192, 255]),
+    Rgba([192, 0, 0, 255]),
+    Rgba([0, 0, 192, 255]),
+];
+/// Images of a directory source are downscaled to about what a webcam delivers
+const DIRECTORY_FRAME_SIZE: u32 = 1920;
+/// How long a directory source shows each image before moving on to the next one
+const DIRECTORY_SLIDE_DURATION: Duration = Duration::from_secs(2);
+
+/// Anything the viewfinder can show frames of, polled from a blocking thread
+pub trait FrameSource: Send {
+    /// Waits for and returns the next frame
+    fn frame(&mut self) -> Result<RgbaImage, String>;
+}
+
+/// Color bars with a block sweeping across, to try the viewfinder without a camera
+#[derive(Debug, Default)]
+pub struct TestPattern {
+    frame_index: u32,
+}
+
+impl FrameSource for TestPattern {
+    fn frame(&mut self) -> Result<RgbaImage, String> {
+        let (width, height) = TEST_PATTERN_SIZE;
+        let bars_height = height * 3 / 4;
+        let bar_width = width.div_ceil(TEST_PATTERN_BARS.len() as u32);
+        let block_size = height - bars_height;
+        // NOTE: the block moving shows that frames keep coming
+        let block_x = self.frame_index * 8 % (width - block_size);
+
+        self.frame_index = self.frame_index.wrapping_add(1);
+
+        Ok(RgbaImage::from_fn(width, height, |x, y| {
+            if y < bars_height {
+                TEST_PATTERN_BARS[(x / bar_width) as usize]
+            } else if (block_x..block_x + block_size).contains(&x) {
+                Rgba([255, 255, 255, 255])
+            } else {
+                Rgba([16, 16, 16, 255])
+            }
+        }))
+    }
+}
+
+/// The images of a directory as a slideshow, standing in for a camera pointed at them
+#[derive(Debug)]
+pub struct DirectorySource {
+    files: Vec<PathBuf>,
+    index: usize,
+    current: Option<RgbaImage>,
+    shown_since: Instant,
+}
+
+impl DirectorySource {
+    pub fn new(dir: &Path) -> Result<DirectorySource, String> {
+        let files = browser::list_images(dir);
+        if files.is_empty() {
+            return Err(format!("No images found in {}", dir.display()));
+        }
+
+        Ok(Self {
+            files,
+            index: 0,
+            current: None,
+            shown_since: Instant::now(),
+        })
+    }
+}
+
+impl FrameSource for DirectorySource {
+    fn frame(&mut self) -> Result<RgbaImage, String> {
+        if self.current.is_some() && self.shown_since.elapsed() >= DIRECTORY_SLIDE_DURATION {
+            self.index = (self.index + 1) % self.files.len();
+            self.current = None;
+        }
+
+        if self.current.is_none() {
+            let file = &self.files[self.index];
+            let (image, _) = LoadedImage::decode(file, DEFAULT_MAX_PIXELS)
+                .map_err(|e| format!("Failed to load {}: {e}", file.display()))?;
+
+            self.current = Some(
+                image
+                    .thumbnail(DIRECTORY_FRAME_SIZE, DIRECTORY_FRAME_SIZE)
+                    .to_rgba8(),
+            );
+            self.shown_since = Instant::now();
+        }
+
+        Ok(self.current.clone().unwrap_or_default())
+    }
+}
+
+#[cfg(feature = "webcam")]
+pub struct CameraSource(nokhwa::Camera);
+
+#[cfg(feature = "webcam")]
+impl CameraSource {
+    pub fn open(index: u32) -> Result<CameraSource, String> {
+        use nokhwa::{
+            pixel_format::RgbFormat,
+            utils::{CameraIndex, RequestedFormat, RequestedFormatType},
+        };
+
+        let mut camera = nokhwa::Camera::new(
+            CameraIndex::Index(index),
+            RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestResolution),
+        )
+        .map_err(|e| e.to_string())?;
+        camera.open_stream().map_err(|e| e.to_string())?;
+
+        Ok(Self(camera))
+    }
+}
+
+#[cfg(feature = "webcam")]
+impl FrameSource for CameraSource {
+    fn frame(&mut self) -> Result<RgbaImage, String> {
+        let frame = self
+            .0
+            .frame()
+            .and_then(|buffer| buffer.decode_image::<nokhwa::pixel_format::RgbFormat>())
+            .map_err(|e| e.to_string())?;
+
+        Ok(DynamicImage::ImageRgb8(frame).to_rgba8())
+    }
+}
+
+/// Which source the viewfinder shows, also identifying its subscription
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FrameSourceKind {
+    TestPattern,
+    Directory(PathBuf),
+    #[cfg(feature = "webcam")]
+    Camera {
+        index: u32,
+        name: String,
+    },
+}
+
+impl std::fmt::Display for FrameSourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameSourceKind::TestPattern => f.write_str("Test pattern"),
+            FrameSourceKind::Directory(dir) => write!(
+                f,
+                "Folder: {}",
+                dir.file_name()
+                    .map_or(dir.to_string_lossy(), |name| name.to_string_lossy())
+            ),
+            #[cfg(feature = "webcam")]
+            FrameSourceKind::Camera { name, .. } => f.write_str(name),
+        }
+    }
+}
+
+impl FrameSourceKind {
+    pub fn open(&self) -> Result<Box<dyn FrameSource>, String> {
+        Ok(match self {
+            FrameSourceKind::TestPattern => Box::new(TestPattern::default()),
+            FrameSourceKind::Directory(dir) => Box::new(DirectorySource::new(dir)?),
+            #[cfg(feature = "webcam")]
+            FrameSourceKind::Camera { index, .. } => Box::new(CameraSource::open(*index)?),
+        })
+    }
+}
+
+/// The connected cameras first, then the test pattern. Blocking, cameras are queried
+pub fn available_sources() -> Vec<FrameSourceKind> {
+    // NOTE: asks for the camera permission on macOS, cameras are listed once it is granted
+    #[cfg(all(feature = "webcam", target_os = "macos"))]
+    nokhwa::nokhwa_initialize(|_| {});
+    #[cfg(feature = "webcam")]
+    let cameras = nokhwa::query(nokhwa::utils::ApiBackend::Auto)
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to list cameras: {e}");
+            Vec::new()
+        })
+        .into_iter()
+        .filter_map(|info| {
+            Some(FrameSourceKind::Camera {
+                index: info.index().as_index().ok()?,
+                name: info.human_name(),
+            })
+        });
+    #[cfg(not(feature = "webcam"))]
+    let cameras = std::iter::empty();
+
+    cameras.chain([FrameSourceKind::TestPattern]).collect()
+}
+
+/// A frame as shown in the viewfinder, kept whole to save it when the shot is taken
+#[derive(Debug, Clone)]
+pub struct CapturedFrame {
+    pub image: Arc<RgbaImage>,
+    pub handle: iced_image::Handle,
+}
+
+impl CapturedFrame {
+    fn new(image: RgbaImage) -> CapturedFrame {
+        let handle =
+            iced_image::Handle::from_rgba(image.width(), image.height(), image.as_raw().clone());
+
+        Self {
+            image: Arc::new(image),
+            handle,
+        }
+    }
+}
+
+/// Opens the source and streams its frames until the stream is dropped, ending after an error
+pub fn frames(kind: &FrameSourceKind) -> impl Stream<Item = Result<CapturedFrame, String>> + use<> {
+    let kind = kind.clone();
+
+    stream::channel(
+        1,
+        async move |mut output: mpsc::Sender<Result<CapturedFrame, String>>| {
+            // NOTE: sources block while waiting for a frame, so they live on blocking threads
+            let mut source = match tokio::task::spawn_blocking(move || kind.open()).await {
+                Ok(Ok(source)) => source,
+                Ok(Err(e)) => {
+                    let _ = output.send(Err(e)).await;
+                    return;
+                }
+                Err(e) => {
+                    let _ = output.send(Err(e.to_string())).await;
+                    return;
+                }
+            };
+
+            loop {
+                let next = tokio::task::spawn_blocking(move || {
+                    let started = Instant::now();
+                    let frame = source.frame().map(CapturedFrame::new);
+                    std::thread::sleep(FRAME_INTERVAL.saturating_sub(started.elapsed()));
+
+                    (source, frame)
+                })
+                .await;
+
+                let frame = match next {
+                    Ok((next_source, frame)) => {
+                        source = next_source;
+                        frame
+                    }
+                    Err(e) => {
+                        let _ = output.send(Err(e.to_string())).await;
+                        return;
+                    }
+                };
+                let failed = frame.is_err();
+
+                // NOTE: the send fails once the viewfinder is closed, which drops the source
+                if output.send(frame).await.is_err() || failed {
+                    return;
+                }
+            }
+        },
+    )
+}
+
+/// Where shots are saved, the `captures` directory next to the config file
+pub fn captures_dir() -> PathBuf {
+    #[cfg(feature = "config")]
+    if let Some(dir) = confy::get_configuration_file_path(crate::constants::APP_NAME, None)
+        .ok()
+        .and_then(|config_file| Some(config_file.parent()?.join("captures")))
+    {
+        return dir;
+    }
+
+    std::env::temp_dir().join(crate::constants::APP_NAME)
+}
+
+/// Writes the frame to the directory as a PNG named after the time it was taken
+pub fn save_shot(frame: &RgbaImage, dir: &Path) -> Result<PathBuf, String> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis());
+    let path = dir.join(format!("shot-{millis}.png"));
+
+    std::fs::create_dir_all(dir)
+        .map_err(|e| e.to_string())
+        .and_then(|()| frame.save(&path).map_err(|e| e.to_string()))
+        .map_err(|e| format!("Failed to save the shot to {}: {e}", path.display()))?;
+
+    Ok(path)
+}
+
+/// State of the open viewfinder
+#[derive(Debug, Clone, Default)]
+pub struct Viewfinder {
+    /// Streamed while set, unset until the sources are listed and after a source fails
+    pub source: Option<FrameSourceKind>,
+    pub sources: Vec<FrameSourceKind>,
+    pub frame: Option<CapturedFrame>,
+    pub error: Option<String>,
+    /// A shot is being written, taking another one waits for it
+    pub saving: bool,
+    /// The running photobooth, kept once done to tell where the shots went
+    pub photobooth: Option<PhotoboothRun>,
+}
+
+impl InstaxFramer {
+    /// Handles the viewfinder messages
+    pub(super) fn update_viewfinder(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::ViewfinderOpened => {
+                self.viewfinder = Some(Viewfinder::default());
+
+                return Task::future(async {
+                    tokio::task::spawn_blocking(available_sources)
+                        .await
+                        .unwrap_or_else(|_| vec![FrameSourceKind::TestPattern])
+                })
+                .map(InstaxFramerMessage::ViewfinderSourcesListed);
+            }
+            InstaxFramerMessage::ViewfinderSourcesListed(sources) => {
+                if let Some(viewfinder) = &mut self.viewfinder {
+                    viewfinder.source = viewfinder.source.take().or(sources.first().cloned());
+                    viewfinder.sources = sources;
+                }
+            }
+            InstaxFramerMessage::ViewfinderSourceSelected(source) => {
+                if let Some(viewfinder) = &mut self.viewfinder {
+                    viewfinder.source = Some(source);
+                    viewfinder.frame = None;
+                    viewfinder.error = None;
+                }
+            }
+            InstaxFramerMessage::ViewfinderFolderRequested => {
+                let Some(viewfinder) = &mut self.viewfinder else {
+                    return Task::none();
+                };
+                let Some(folder) = FileDialog::new().pick_folder() else {
+                    return Task::none();
+                };
+
+                let source = FrameSourceKind::Directory(folder);
+                if !viewfinder.sources.contains(&source) {
+                    viewfinder.sources.push(source.clone());
+                }
+
+                return Task::done(InstaxFramerMessage::ViewfinderSourceSelected(source));
+            }
+            InstaxFramerMessage::ViewfinderFrame(frame) => {
+                if let Some(viewfinder) = &mut self.viewfinder {
+                    match frame {
+                        Ok(frame) => viewfinder.frame = Some(frame),
+                        // NOTE: the stream ended, unsetting the source lets it be picked again
+                        Err(e) => {
+                            viewfinder.source = None;
+                            viewfinder.frame = None;
+                            viewfinder.error = Some(e);
+                        }
+                    }
+                }
+            }
+            InstaxFramerMessage::ShotTaken => {
+                let Some(viewfinder) = self
+                    .viewfinder
+                    .as_mut()
+                    .filter(|viewfinder| !viewfinder.saving)
+                else {
+                    return Task::none();
+                };
+                let Some(frame) = viewfinder.frame.clone() else {
+                    return Task::none();
+                };
+
+                viewfinder.saving = true;
+
+                return Task::future(async move {
+                    let saved = tokio::task::spawn_blocking(move || {
+                        save_shot(&frame.image, &captures_dir())
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()));
+
+                    InstaxFramerMessage::ShotSaved(saved)
+                });
+            }
+            InstaxFramerMessage::ShotSaved(saved) => match saved {
+                Ok(shot) => {
+                    self.viewfinder = None;
+
+                    return self.update(InstaxFramerMessage::ImagePicked(shot));
+                }
+                Err(e) => {
+                    if let Some(viewfinder) = &mut self.viewfinder {
+                        viewfinder.saving = false;
+                    }
+
+                    let _ = MessageDialog::new()
+                        .set_title("Failed to take shot")
+                        .set_description(e)
+                        .set_level(MessageLevel::Error)
+                        .show();
+                }
+            },
+            InstaxFramerMessage::ViewfinderClosed => self.viewfinder = None,
+            _ => {}
+        }
+
+        Task::none()
+    }
+}
+
+/// Live frames of the selected source, with the source picker, the shot button and the
+/// photobooth
+pub(super) fn viewfinder_view(
+    viewfinder: &Viewfinder,
+    settings: PhotoboothSettings,
+) -> Element<'_, InstaxFramerMessage> {
+    let capturing = viewfinder
+        .photobooth
+        .as_ref()
+        .is_some_and(PhotoboothRun::is_capturing);
+
+    let frame: Element<'_, InstaxFramerMessage> = match (&viewfinder.frame, &viewfinder.error) {
+        (_, Some(error)) => text(error).into(),
+        (Some(frame), None) => iced_image(frame.handle.clone())
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into(),
+        (None, None) => text("Starting...").into(),
+    };
+    let countdown = viewfinder
+        .photobooth
+        .as_ref()
+        .and_then(|run| run.seconds_left(Instant::now()))
+        .map(|seconds| {
+            container(
+                container(text(seconds).size(120).color(Color::WHITE))
+                    .padding([0, 32])
+                    .style(|_theme: &Theme| container::Style {
+                        background: Some(Color::BLACK.scale_alpha(0.5).into()),
+                        border: Border {
+                            radius: 16.0.into(),
+                            ..Border::default()
+                        },
+                        ..container::Style::default()
+                    }),
+            )
+            .center(Length::Fill)
+        });
+
+    column![
+        row![
+            pick_list(
+                viewfinder.sources.as_slice(),
+                viewfinder.source.as_ref(),
+                InstaxFramerMessage::ViewfinderSourceSelected
+            )
+            .placeholder("Pick a source"),
+            button(text("Folder..."))
+                .style(button::secondary)
+                .on_press(InstaxFramerMessage::ViewfinderFolderRequested),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center),
+        stack![container(frame).center(Length::Fill)].push(countdown),
+        row![
+            button(text("📸 Take shot")).on_press_maybe(
+                (viewfinder.frame.is_some() && !viewfinder.saving && !capturing)
+                    .then_some(InstaxFramerMessage::ShotTaken)
+            ),
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(InstaxFramerMessage::ViewfinderClosed),
+        ]
+        .spacing(8),
+        super::photobooth::photobooth_controls(viewfinder, settings),
+    ]
+    .spacing(8)
+    .align_x(Alignment::Center)
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_image(path: &Path, color: Rgba<u8>) {
+        RgbaImage::from_pixel(4, 3, color).save(path).unwrap();
+    }
+
+    #[test]
+    fn test_pattern_frames_move_the_block() {
+        let mut pattern = TestPattern::default();
+
+        let first = pattern.frame().unwrap();
+        let second = pattern.frame().unwrap();
+
+        assert_eq!(first.dimensions(), TEST_PATTERN_SIZE);
+        assert_eq!(second.dimensions(), TEST_PATTERN_SIZE);
+        // NOTE: the bars stay, only the block below them moves
+        let bars_height = TEST_PATTERN_SIZE.1 * 3 / 4;
+        assert_eq!(first.get_pixel(0, 0), second.get_pixel(0, 0));
+        assert_ne!(
+            first.get_pixel(0, bars_height),
+            second.get_pixel(0, bars_height)
+        );
+    }
+
+    #[test]
+    fn directory_source_needs_images() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not an image").unwrap();
+
+        assert!(DirectorySource::new(dir.path()).is_err());
+    }
+
+    #[test]
+    fn directory_source_cycles_through_its_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+        write_image(&dir.path().join("a.png"), red);
+        write_image(&dir.path().join("b.png"), blue);
+
+        let mut source = DirectorySource::new(dir.path()).unwrap();
+        let next_slide = |source: &mut DirectorySource| {
+            let frame = source.frame().unwrap();
+            source.shown_since -= DIRECTORY_SLIDE_DURATION;
+
+            *frame.get_pixel(0, 0)
+        };
+
+        assert_eq!(next_slide(&mut source), red);
+        assert_eq!(next_slide(&mut source), blue);
+        assert_eq!(next_slide(&mut source), red);
+    }
+
+    #[test]
+    fn directory_source_keeps_the_slide_until_it_is_due() {
+        let dir = tempfile::tempdir().unwrap();
+        write_image(&dir.path().join("a.png"), Rgba([255, 0, 0, 255]));
+        write_image(&dir.path().join("b.png"), Rgba([0, 0, 255, 255]));
+
+        let mut source = DirectorySource::new(dir.path()).unwrap();
+        let first = source.frame().unwrap();
+
+        assert_eq!(source.frame().unwrap(), first);
+        assert_eq!(source.index, 0);
+    }
+
+    #[test]
+    fn save_shot_writes_a_png() {
+        let dir = tempfile::tempdir().unwrap();
+        let shots_dir = dir.path().join("captures");
+        let frame = RgbaImage::from_pixel(8, 6, Rgba([10, 20, 30, 255]));
+
+        let path = save_shot(&frame, &shots_dir).unwrap();
+
+        assert_eq!(path.parent(), Some(shots_dir.as_path()));
+        assert_eq!(path.extension().unwrap(), "png");
+        assert_eq!(image::open(&path).unwrap().to_rgba8(), frame);
+    }
+}
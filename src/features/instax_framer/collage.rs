@@ -0,0 +1,666 @@
+use std::{borrow::Borrow, collections::BTreeSet, path::PathBuf, sync::Arc};
+
+use iced::{
+    Alignment, Border, Element, Task, Theme, mouse,
+    widget::{button, column, container, image as iced_image, mouse_area, pick_list, row, text},
+};
+use image::{DynamicImage, Pixel, Rgba, RgbaImage, imageops};
+use rfd::FileDialog;
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    IMAGE_EXTENSIONS, ImageLoadError, InstaxFramer, InstaxFramerMessage, LoadedImage,
+    PREVIEW_PROXY_SIZE, SlotImage, browser,
+    crop::{self, Crop},
+    frame::{self, Framing},
+};
+
+pub const MIN_SLOTS: usize = 2;
+pub const MAX_SLOTS: usize = 4;
+pub static SLOT_COUNTS: &[usize] = &[2, 3, 4];
+
+const GAP_MM: f32 = 1.0;
+/// Stacked prints are this share of the picture area
+const STACK_CARD_SIZE: f32 = 0.55;
+const STACK_BORDER_MM: f32 = 1.2;
+/// Polaroid-style, thicker at the bottom
+const STACK_BOTTOM_BORDER_MM: f32 = 3.0;
+/// Clockwise tilt of each stacked print, the last one on top
+const STACK_ANGLES: [f32; MAX_SLOTS] = [-7.0, 5.0, -3.0, 2.0];
+const STACK_SHADOW_MM: (f32, f32) = (0.5, 0.8);
+const CARD_COLOR: Rgba<u8> = Rgba([250, 250, 248, 255]);
+const SHADOW_COLOR: Rgba<u8> = Rgba([0, 0, 0, 70]);
+const EMPTY_SLOT_COLOR: Rgba<u8> = Rgba([128, 128, 128, 255]);
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CollageLayout {
+    /// Two columns, the last row spanning the width when the count is odd
+    #[default]
+    Grid,
+    /// Equal bands along the picture area's longer side, like a photo booth strip
+    Strip,
+    /// Tilted prints piled on top of each other
+    Stack,
+}
+
+pub static COLLAGE_LAYOUTS: &[CollageLayout] = &[
+    CollageLayout::Grid,
+    CollageLayout::Strip,
+    CollageLayout::Stack,
+];
+
+impl std::fmt::Display for CollageLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CollageLayout::Grid => f.write_str("Grid"),
+            CollageLayout::Strip => f.write_str("Strip"),
+            CollageLayout::Stack => f.write_str("Polaroid stack"),
+        }
+    }
+}
+
+/// Several images sharing one frame's picture area, each placed by its own crop
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct Collage {
+    #[cfg_attr(feature = "config", serde(default))]
+    pub layout: CollageLayout,
+    pub slots: Vec<CollageSlot>,
+}
+
+impl Default for Collage {
+    fn default() -> Self {
+        Self {
+            layout: CollageLayout::default(),
+            slots: vec![CollageSlot::default(); MIN_SLOTS],
+        }
+    }
+}
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Hash)]
+pub struct CollageSlot {
+    /// Left empty until an image is picked for it
+    pub file: Option<PathBuf>,
+    #[cfg_attr(feature = "config", serde(default))]
+    pub crop: Crop,
+}
+
+/// Where a slot's image lands in the picture area, in pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlotRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Clockwise degrees around the rectangle's center
+    pub angle: f32,
+}
+
+impl Collage {
+    /// Keeps the first slots, or adds empty ones
+    pub fn resize(&mut self, count: usize) {
+        self.slots
+            .resize(count.clamp(MIN_SLOTS, MAX_SLOTS), CollageSlot::default());
+    }
+
+    pub fn slot_rects(&self, (width, height): (u32, u32), pixels_per_mm: f32) -> Vec<SlotRect> {
+        let count = self.slots.len().clamp(MIN_SLOTS, MAX_SLOTS);
+        let gap = (GAP_MM * pixels_per_mm).round() as u32;
+
+        match self.layout {
+            CollageLayout::Grid => {
+                let rows = count.div_ceil(2) as u32;
+                let cell_height = height.saturating_sub(gap * (rows - 1)) / rows;
+
+                (0..count as u32)
+                    .map(|index| {
+                        let row = index / 2;
+                        // NOTE: with an odd count, the last image takes the whole row
+                        let spans_row = index as usize == count - 1 && count % 2 == 1;
+                        let cell_width = if spans_row {
+                            width
+                        } else {
+                            width.saturating_sub(gap) / 2
+                        };
+
+                        SlotRect {
+                            x: if spans_row {
+                                0
+                            } else {
+                                (index % 2) * (cell_width + gap)
+                            },
+                            y: row * (cell_height + gap),
+                            width: cell_width,
+                            height: cell_height,
+                            angle: 0.0,
+                        }
+                    })
+                    .collect()
+            }
+            CollageLayout::Strip => {
+                let count = count as u32;
+                let vertical = height >= width;
+                let (length, breadth) = if vertical {
+                    (height, width)
+                } else {
+                    (width, height)
+                };
+                let band = length.saturating_sub(gap * (count - 1)) / count;
+
+                (0..count)
+                    .map(|index| {
+                        let offset = index * (band + gap);
+
+                        if vertical {
+                            SlotRect {
+                                x: 0,
+                                y: offset,
+                                width: breadth,
+                                height: band,
+                                angle: 0.0,
+                            }
+                        } else {
+                            SlotRect {
+                                x: offset,
+                                y: 0,
+                                width: band,
+                                height: breadth,
+                                angle: 0.0,
+                            }
+                        }
+                    })
+                    .collect()
+            }
+            CollageLayout::Stack => {
+                let card_width = (width as f32 * STACK_CARD_SIZE).round() as u32;
+                let card_height = (height as f32 * STACK_CARD_SIZE).round() as u32;
+                // NOTE: spread the prints along the diagonal, from the top left to the bottom right
+                let spread_x = (width - card_width) as f32 * 0.3;
+                let spread_y = (height - card_height) as f32 * 0.3;
+
+                (0..count)
+                    .map(|index| {
+                        let step = index as f32 / (count - 1) as f32 * 2.0 - 1.0;
+                        let center_x = width as f32 / 2.0 + step * spread_x;
+                        let center_y = height as f32 / 2.0 + step * spread_y;
+
+                        SlotRect {
+                            x: (center_x - card_width as f32 / 2.0).round() as u32,
+                            y: (center_y - card_height as f32 / 2.0).round() as u32,
+                            width: card_width,
+                            height: card_height,
+                            angle: STACK_ANGLES[index],
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// What fills the picture area, the same for the preview and exports
+#[derive(Debug, Clone)]
+pub enum Picture<I> {
+    Single(I),
+    /// A collage with the image of every slot, `None` for empty slots
+    Collage(Collage, Vec<Option<I>>),
+}
+
+impl<I> Picture<I> {
+    pub fn try_map<J, E>(self, mut f: impl FnMut(I) -> Result<J, E>) -> Result<Picture<J>, E> {
+        Ok(match self {
+            Picture::Single(image) => Picture::Single(f(image)?),
+            Picture::Collage(collage, images) => Picture::Collage(
+                collage,
+                images
+                    .into_iter()
+                    .map(|image| image.map(&mut f).transpose())
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+}
+
+impl<I: Borrow<DynamicImage>> Picture<I> {
+    pub fn compose(&self, framing: &Framing, pixels_per_mm: f32) -> RgbaImage {
+        match self {
+            Picture::Single(image) => frame::compose(image.borrow(), framing, pixels_per_mm),
+            Picture::Collage(collage, images) => compose(images, collage, framing, pixels_per_mm),
+        }
+    }
+}
+
+/// Renders every slot into the film's picture area and frames it like a single image, empty
+/// slots and slots without a decoded image showing as gray
+pub fn compose(
+    images: &[Option<impl Borrow<DynamicImage>>],
+    collage: &Collage,
+    framing: &Framing,
+    pixels_per_mm: f32,
+) -> RgbaImage {
+    let picture_rect = framing.film.layout().picture_px(pixels_per_mm);
+    // NOTE: transparent between the slots, to show the template's border there
+    let mut picture = RgbaImage::new(picture_rect.width, picture_rect.height);
+
+    let slot_rects = collage.slot_rects((picture_rect.width, picture_rect.height), pixels_per_mm);
+
+    for (index, (slot, slot_rect)) in collage.slots.iter().zip(slot_rects).enumerate() {
+        let cell = match images.get(index).and_then(Option::as_ref) {
+            Some(image) => crop::render(
+                image.borrow(),
+                &slot.crop,
+                slot_rect.width,
+                slot_rect.height,
+            ),
+            None => RgbaImage::from_pixel(slot_rect.width, slot_rect.height, EMPTY_SLOT_COLOR),
+        };
+
+        if collage.layout == CollageLayout::Stack {
+            draw_card(&mut picture, &cell, &slot_rect, pixels_per_mm);
+        } else {
+            imageops::replace(&mut picture, &cell, slot_rect.x.into(), slot_rect.y.into());
+        }
+    }
+
+    frame::frame_picture(picture, framing, pixels_per_mm)
+}
+
+/// Puts the image on a bordered print and draws it tilted, over a shadow
+fn draw_card(picture: &mut RgbaImage, cell: &RgbaImage, slot_rect: &SlotRect, pixels_per_mm: f32) {
+    let border = (STACK_BORDER_MM * pixels_per_mm).round() as u32;
+    let bottom_border = (STACK_BOTTOM_BORDER_MM * pixels_per_mm).round() as u32;
+
+    // NOTE: a transparent margin of a pixel gives the tilted edges a smooth falloff
+    let (card_width, card_height) = (
+        cell.width() + border * 2,
+        cell.height() + border + bottom_border,
+    );
+    let mut card = RgbaImage::new(card_width + 2, card_height + 2);
+    imageops::replace(
+        &mut card,
+        &RgbaImage::from_pixel(card_width, card_height, CARD_COLOR),
+        1,
+        1,
+    );
+    imageops::replace(&mut card, cell, (border + 1).into(), (border + 1).into());
+
+    let shadow = RgbaImage::from_fn(card.width(), card.height(), |x, y| {
+        if card.get_pixel(x, y)[3] > 0 {
+            SHADOW_COLOR
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    });
+
+    // NOTE: the slot rectangle is the image, the card grows around it, more at the bottom
+    let center_x = slot_rect.x as f32 + slot_rect.width as f32 / 2.0;
+    let center_y = slot_rect.y as f32
+        + slot_rect.height as f32 / 2.0
+        + (bottom_border as f32 - border as f32) / 2.0;
+    let (shadow_x, shadow_y) = STACK_SHADOW_MM;
+
+    draw_rotated(
+        picture,
+        &shadow,
+        (
+            center_x + shadow_x * pixels_per_mm,
+            center_y + shadow_y * pixels_per_mm,
+        ),
+        slot_rect.angle,
+    );
+    draw_rotated(picture, &card, (center_x, center_y), slot_rect.angle);
+}
+
+/// Blends the image onto the picture, centered on `center` and turned by `angle` degrees
+fn draw_rotated(picture: &mut RgbaImage, image: &RgbaImage, center: (f32, f32), angle: f32) {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (half_width, half_height) = (image.width() as f32 / 2.0, image.height() as f32 / 2.0);
+    let (center_x, center_y) = center;
+
+    // NOTE: only visit the picture pixels the turned image's bounding box covers
+    let reach_x = half_width * cos.abs() + half_height * sin.abs();
+    let reach_y = half_width * sin.abs() + half_height * cos.abs();
+    let left = (center_x - reach_x).floor().max(0.0) as u32;
+    let top = (center_y - reach_y).floor().max(0.0) as u32;
+    let right = ((center_x + reach_x).ceil().max(0.0) as u32).min(picture.width());
+    let bottom = ((center_y + reach_y).ceil().max(0.0) as u32).min(picture.height());
+
+    for y in top..bottom {
+        for x in left..right {
+            let dx = x as f32 + 0.5 - center_x;
+            let dy = y as f32 + 0.5 - center_y;
+
+            // NOTE: turn back by the angle to find where the pixel falls on the image
+            let source_x = cos * dx + sin * dy + half_width - 0.5;
+            let source_y = -sin * dx + cos * dy + half_height - 0.5;
+
+            if let Some(pixel) = imageops::interpolate_bilinear(image, source_x, source_y) {
+                picture.get_pixel_mut(x, y).blend(&pixel);
+            }
+        }
+    }
+}
+
+impl InstaxFramer {
+    /// Handles the collage messages
+    pub(super) fn update_collage(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::CollageToggled => {
+                self.show_collage = !self.show_collage;
+
+                // NOTE: start from the opened image, as it is usually meant to be part of it
+                if self.show_collage && self.collage.slots.iter().all(|slot| slot.file.is_none()) {
+                    self.collage_slot = 0;
+                    return Task::done(InstaxFramerMessage::CollageSlotFilled);
+                }
+
+                return Task::batch([self.load_collage_images(), self.compose_preview()]);
+            }
+            InstaxFramerMessage::CollageLayoutSelected(layout) => {
+                self.collage.layout = layout;
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::CollageSlotCountSelected(count) => {
+                self.collage.resize(count);
+                self.collage_slot = self.collage_slot.min(self.collage.slots.len() - 1);
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::CollageSlotPressed(index) => {
+                self.collage_slot = index;
+                self.collage_dragged = Some(index);
+            }
+            InstaxFramerMessage::CollageSlotReleased(index) => {
+                if let Some(dragged) = self.collage_dragged.take()
+                    && dragged != index
+                {
+                    self.collage.slots.swap(dragged, index);
+                    self.collage_slot = index;
+
+                    return self.compose_preview();
+                }
+            }
+            InstaxFramerMessage::CollageSlotDragCancelled => self.collage_dragged = None,
+            InstaxFramerMessage::CollageSlotFilled => {
+                let (Some(selected_file), Some(loaded_image)) =
+                    (self.selected_file.clone(), &self.loaded_image)
+                else {
+                    return Task::none();
+                };
+
+                self.collage_images
+                    .entry(selected_file.clone())
+                    .or_insert_with(|| SlotImage::new(loaded_image.proxy.clone()));
+
+                if let Some(slot) = self.collage.slots.get_mut(self.collage_slot) {
+                    slot.file = Some(selected_file);
+                    slot.crop = Crop::default();
+                }
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::CollageSlotPickRequested => {
+                let file = FileDialog::new()
+                    .add_filter("image", IMAGE_EXTENSIONS)
+                    .pick_file();
+
+                if let Some(file) = file {
+                    return Task::done(InstaxFramerMessage::CollageSlotPicked(file));
+                }
+            }
+            InstaxFramerMessage::CollageSlotPicked(file) => {
+                if let Some(slot) = self.collage.slots.get_mut(self.collage_slot) {
+                    slot.file = Some(file);
+                    slot.crop = Crop::default();
+                }
+
+                return Task::batch([self.load_collage_images(), self.compose_preview()]);
+            }
+            InstaxFramerMessage::CollageSlotCleared => {
+                if let Some(slot) = self.collage.slots.get_mut(self.collage_slot) {
+                    *slot = CollageSlot::default();
+                }
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::CollageImageLoaded(file, slot_image) => {
+                self.collage_loading.remove(&file);
+
+                match slot_image {
+                    Ok(slot_image) => {
+                        self.collage_images.insert(file, slot_image);
+                    }
+                    Err(e) => {
+                        for slot in &mut self.collage.slots {
+                            if slot.file.as_ref() == Some(&file) {
+                                slot.file = None;
+                            }
+                        }
+                        self.notices.push(format!(
+                            "{} was removed from the collage: {e}",
+                            file.to_string_lossy()
+                        ));
+                    }
+                }
+
+                return self.compose_preview();
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    /// Layout pickers and a tile per slot, pressed to select it or dragged onto another to swap
+    pub(super) fn collage_controls(&self) -> Element<'_, InstaxFramerMessage> {
+        let size = browser::THUMBNAIL_SIZE as f32;
+
+        let tiles =
+            self.collage.slots.iter().enumerate().map(|(index, slot)| {
+                let content: Element<'_, InstaxFramerMessage> =
+                    match slot.file.as_ref().map(|file| self.collage_images.get(file)) {
+                        Some(Some(slot_image)) => iced_image(slot_image.thumbnail.clone())
+                            .width(size)
+                            .height(size)
+                            .into(),
+                        Some(None) => container(text("…")).center(size).into(),
+                        None => container(text(format!("{}", index + 1)))
+                            .center(size)
+                            .into(),
+                    };
+                let selected = index == self.collage_slot;
+
+                mouse_area(container(content).padding(2).style(move |theme: &Theme| {
+                    container::Style {
+                        border: Border {
+                            color: if selected {
+                                theme.extended_palette().primary.strong.color
+                            } else {
+                                theme.extended_palette().background.strong.color
+                            },
+                            width: 2.0,
+                            radius: 4.0.into(),
+                        },
+                        ..container::Style::default()
+                    }
+                }))
+                .on_press(InstaxFramerMessage::CollageSlotPressed(index))
+                .on_release(InstaxFramerMessage::CollageSlotReleased(index))
+                .interaction(if self.collage_dragged.is_some() {
+                    mouse::Interaction::Grabbing
+                } else {
+                    mouse::Interaction::Pointer
+                })
+                .into()
+            });
+
+        let slot_filled = self
+            .collage
+            .slots
+            .get(self.collage_slot)
+            .is_some_and(|slot| slot.file.is_some());
+
+        column![
+            row![
+                pick_list(
+                    COLLAGE_LAYOUTS,
+                    Some(self.collage.layout),
+                    InstaxFramerMessage::CollageLayoutSelected
+                ),
+                pick_list(
+                    SLOT_COUNTS,
+                    Some(self.collage.slots.len()),
+                    InstaxFramerMessage::CollageSlotCountSelected
+                ),
+                text("images"),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            mouse_area(row(tiles).spacing(8))
+                .on_exit(InstaxFramerMessage::CollageSlotDragCancelled),
+            row![
+                button(text("Use current image")).on_press(InstaxFramerMessage::CollageSlotFilled),
+                button(text("Pick image..."))
+                    .on_press(InstaxFramerMessage::CollageSlotPickRequested),
+                button(text("Clear"))
+                    .style(button::secondary)
+                    .on_press_maybe(slot_filled.then_some(InstaxFramerMessage::CollageSlotCleared)),
+            ]
+            .spacing(8),
+            text(
+                "Drag a slot onto another to swap them, the crop controls apply to the selected one"
+            )
+            .size(12),
+        ]
+        .spacing(8)
+        .align_x(Alignment::Center)
+        .into()
+    }
+
+    /// Decodes the slot images not decoded yet, or being decoded
+    pub(super) fn load_collage_images(&mut self) -> Task<InstaxFramerMessage> {
+        let files = self
+            .collage
+            .slots
+            .iter()
+            .filter_map(|slot| slot.file.clone())
+            .filter(|file| {
+                !self.collage_images.contains_key(file) && !self.collage_loading.contains(file)
+            })
+            .collect::<BTreeSet<_>>();
+        let max_pixels = self.max_pixels;
+
+        Task::batch(files.into_iter().map(|file| {
+            self.collage_loading.insert(file.clone());
+
+            Task::future(async move {
+                let source = file.clone();
+                let slot_image = tokio::task::spawn_blocking(move || {
+                    let (image, _) = LoadedImage::decode(&source, max_pixels)?;
+
+                    Ok(SlotImage::new(Arc::new(
+                        image.thumbnail(PREVIEW_PROXY_SIZE, PREVIEW_PROXY_SIZE),
+                    )))
+                })
+                .await
+                .unwrap_or_else(|e| Err(ImageLoadError::Io(e.to_string())));
+
+                InstaxFramerMessage::CollageImageLoaded(file, slot_image)
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collage(layout: CollageLayout, count: usize) -> Collage {
+        Collage {
+            layout,
+            slots: vec![CollageSlot::default(); count],
+        }
+    }
+
+    fn rect(x: u32, y: u32, width: u32, height: u32) -> SlotRect {
+        SlotRect {
+            x,
+            y,
+            width,
+            height,
+            angle: 0.0,
+        }
+    }
+
+    #[test]
+    fn odd_grids_give_the_last_image_the_whole_row() {
+        assert_eq!(
+            collage(CollageLayout::Grid, 3).slot_rects((101, 100), 1.0),
+            [
+                rect(0, 0, 50, 49),
+                rect(51, 0, 50, 49),
+                rect(0, 50, 101, 49)
+            ]
+        );
+        assert_eq!(
+            collage(CollageLayout::Grid, 4).slot_rects((101, 100), 1.0),
+            [
+                rect(0, 0, 50, 49),
+                rect(51, 0, 50, 49),
+                rect(0, 50, 50, 49),
+                rect(51, 50, 50, 49)
+            ]
+        );
+    }
+
+    #[test]
+    fn strips_run_along_the_longer_side() {
+        assert_eq!(
+            collage(CollageLayout::Strip, 3).slot_rects((60, 100), 1.0),
+            [rect(0, 0, 60, 32), rect(0, 33, 60, 32), rect(0, 66, 60, 32)]
+        );
+        assert_eq!(
+            collage(CollageLayout::Strip, 3).slot_rects((100, 60), 1.0),
+            [rect(0, 0, 32, 60), rect(33, 0, 32, 60), rect(66, 0, 32, 60)]
+        );
+    }
+
+    #[test]
+    fn stacked_prints_spread_along_the_diagonal_inside_the_picture() {
+        for count in SLOT_COUNTS.iter().copied() {
+            let rects = collage(CollageLayout::Stack, count).slot_rects((100, 80), 1.0);
+
+            assert_eq!(rects.len(), count);
+            for (index, rect) in rects.iter().enumerate() {
+                assert_eq!((rect.width, rect.height), (55, 44));
+                assert!(rect.x + rect.width <= 100 && rect.y + rect.height <= 80);
+                assert_eq!(rect.angle, STACK_ANGLES[index]);
+            }
+            assert!(
+                rects
+                    .windows(2)
+                    .all(|pair| pair[0].x < pair[1].x && pair[0].y < pair[1].y)
+            );
+        }
+    }
+
+    #[test]
+    fn resizing_keeps_the_first_slots_within_bounds() {
+        let mut collage = collage(CollageLayout::Grid, 3);
+        collage.slots[0].file = Some(PathBuf::from("a.jpg"));
+
+        collage.resize(9);
+        assert_eq!(collage.slots.len(), MAX_SLOTS);
+
+        collage.resize(0);
+        assert_eq!(collage.slots.len(), MIN_SLOTS);
+        assert_eq!(collage.slots[0].file, Some(PathBuf::from("a.jpg")));
+    }
+}
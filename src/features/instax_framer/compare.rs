@@ -0,0 +1,261 @@
+use std::{borrow::Borrow, collections::BTreeSet, path::PathBuf, sync::Arc};
+
+use iced::{
+    Alignment, Element, Task,
+    widget::{checkbox, pick_list, row, slider},
+};
+use image::{DynamicImage, Pixel, Rgba, RgbaImage, imageops};
+
+use super::{
+    ImageLoadError, InstaxFramer, InstaxFramerMessage, LoadedImage, adjustments::Adjustments,
+    collage::Picture, frame::Framing,
+};
+
+const DIVIDER_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const DIVIDER_SHADOW_COLOR: Rgba<u8> = Rgba([0, 0, 0, 160]);
+
+/// What the preview shows, to check edits against the untouched image
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompareMode {
+    /// The print as it is exported
+    #[default]
+    Framed,
+    /// The print without adjustments on the left of the divider, with them on the right
+    Split,
+    /// The whole image, before cropping and adjusting
+    Original,
+}
+
+pub static COMPARE_MODES: &[CompareMode] = &[
+    CompareMode::Framed,
+    CompareMode::Split,
+    CompareMode::Original,
+];
+
+impl std::fmt::Display for CompareMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompareMode::Framed => f.write_str("Framed"),
+            CompareMode::Split => f.write_str("Without / with adjustments"),
+            CompareMode::Original => f.write_str("Original"),
+        }
+    }
+}
+
+/// Renders the preview for the mode, `split` being the divider's position from 0.0 to 1.0.
+/// `original` is the image shown whole, the active slot's image for collages
+pub fn render(
+    picture: &Picture<impl Borrow<DynamicImage>>,
+    original: &DynamicImage,
+    framing: &Framing,
+    pixels_per_mm: f32,
+    mode: CompareMode,
+    split: f32,
+) -> RgbaImage {
+    match mode {
+        CompareMode::Framed => picture.compose(framing, pixels_per_mm),
+        CompareMode::Split => {
+            let before = picture.compose(
+                &Framing {
+                    adjustments: Adjustments::default(),
+                    ..framing.clone()
+                },
+                pixels_per_mm,
+            );
+            let mut after = picture.compose(framing, pixels_per_mm);
+
+            let divider_x = (after.width() as f32 * split.clamp(0.0, 1.0)).round() as u32;
+            let before = imageops::crop_imm(&before, 0, 0, divider_x, before.height());
+            imageops::replace(&mut after, &*before, 0, 0);
+            draw_divider(&mut after, divider_x);
+
+            after
+        }
+        CompareMode::Original => original.to_rgba8(),
+    }
+}
+
+/// A line with a faint shadow on both sides, visible over light and dark images alike
+fn draw_divider(image: &mut RgbaImage, divider_x: u32) {
+    let width = image.width();
+
+    for row in 0..image.height() {
+        for (column, color) in [
+            (divider_x.checked_sub(2), DIVIDER_SHADOW_COLOR),
+            (divider_x.checked_sub(1), DIVIDER_COLOR),
+            (Some(divider_x), DIVIDER_COLOR),
+            (Some(divider_x + 1), DIVIDER_SHADOW_COLOR),
+        ] {
+            if let Some(column) = column.filter(|&column| column < width) {
+                image.get_pixel_mut(column, row).blend(&color);
+            }
+        }
+    }
+}
+
+impl InstaxFramer {
+    /// Handles the comparison and actual size messages
+    pub(super) fn update_compare(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::CompareModeSelected(compare_mode) => {
+                self.compare_mode = compare_mode;
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::CompareSplitMoved(compare_split) => {
+                self.compare_split = compare_split;
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::ActualSizeToggled(actual_size) => {
+                self.actual_size = actual_size;
+                if !actual_size {
+                    self.full_images.clear();
+                }
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::FullImageLoaded(file, full_image) => {
+                self.full_loading.remove(&file);
+
+                match full_image {
+                    Ok(full_image) => {
+                        if self.actual_size && self.preview_files().contains(&file) {
+                            self.full_images.insert(file, full_image);
+                        }
+                    }
+                    Err(e) => {
+                        self.actual_size = false;
+                        self.full_images.clear();
+                        self.notices.push(format!(
+                            "{} could not be shown at 100%: {e}",
+                            file.to_string_lossy()
+                        ));
+                    }
+                }
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::GridToggled(show_grid) => {
+                self.show_grid = show_grid;
+
+                return self.compose_preview();
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    /// The files the preview is composed from, the image shown whole included
+    pub(super) fn preview_files(&self) -> BTreeSet<PathBuf> {
+        let mut files = BTreeSet::new();
+
+        if self.show_collage {
+            files.extend(
+                self.collage
+                    .slots
+                    .iter()
+                    .filter_map(|slot| slot.file.clone()),
+            );
+        }
+        files.extend(self.original_file().cloned());
+
+        files
+    }
+
+    /// The file shown whole, the selected slot's one in collage mode or the opened one
+    pub(super) fn original_file(&self) -> Option<&PathBuf> {
+        self.collage
+            .slots
+            .get(self.collage_slot)
+            .and_then(|slot| slot.file.as_ref())
+            .filter(|_| self.show_collage)
+            .or(self.selected_file.as_ref())
+    }
+
+    /// The picture and the image shown whole at full resolution, once all of them are decoded
+    pub(super) fn full_picture(&self) -> Option<(Picture<Arc<DynamicImage>>, Arc<DynamicImage>)> {
+        let full_image = |file: &PathBuf| self.full_images.get(file).cloned();
+
+        let picture = if self.show_collage {
+            Picture::Collage(
+                self.collage.clone(),
+                self.collage
+                    .slots
+                    .iter()
+                    .map(|slot| match &slot.file {
+                        Some(file) => full_image(file).map(Some),
+                        None => Some(None),
+                    })
+                    .collect::<Option<_>>()?,
+            )
+        } else {
+            Picture::Single(full_image(self.selected_file.as_ref()?)?)
+        };
+
+        Some((picture, full_image(self.original_file()?)?))
+    }
+
+    /// Decodes the preview's files at full resolution for the actual size view, forgetting the
+    /// ones the preview no longer uses
+    pub(super) fn load_full_images(&mut self) -> Task<InstaxFramerMessage> {
+        let files = self.preview_files();
+        self.full_images.retain(|file, _| files.contains(file));
+        let max_pixels = self.max_pixels;
+
+        Task::batch(
+            files
+                .into_iter()
+                .filter(|file| {
+                    !self.full_images.contains_key(file) && !self.full_loading.contains(file)
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|file| {
+                    self.full_loading.insert(file.clone());
+
+                    Task::future(async move {
+                        let source = file.clone();
+                        let full_image = tokio::task::spawn_blocking(move || {
+                            let (image, _) = LoadedImage::decode(&source, max_pixels)?;
+
+                            Ok(Arc::new(image))
+                        })
+                        .await
+                        .unwrap_or_else(|e| Err(ImageLoadError::Io(e.to_string())));
+
+                        InstaxFramerMessage::FullImageLoaded(file, full_image)
+                    })
+                }),
+        )
+    }
+
+    pub(super) fn compare_controls(&self) -> Element<'_, InstaxFramerMessage> {
+        row![pick_list(
+            COMPARE_MODES,
+            Some(self.compare_mode),
+            InstaxFramerMessage::CompareModeSelected
+        )]
+        .push((self.compare_mode == CompareMode::Split).then(|| {
+            slider(
+                0.0..=1.0,
+                self.compare_split,
+                InstaxFramerMessage::CompareSplitMoved,
+            )
+            .step(0.01)
+            .width(160)
+        }))
+        .push(
+            checkbox(self.actual_size)
+                .label("100%")
+                .on_toggle(InstaxFramerMessage::ActualSizeToggled),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into()
+    }
+}
@@ -0,0 +1,438 @@
+use std::{
+    borrow::Cow,
+    hash::{Hash, Hasher},
+};
+
+use iced::{
+    Alignment, Element, Task,
+    widget::{button, checkbox, row, slider, text},
+};
+use image::{DynamicImage, Rgba, RgbaImage, imageops::FilterType};
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+
+use super::{InstaxFramer, InstaxFramerMessage};
+
+pub const MAX_ZOOM: f32 = 8.0;
+pub const MAX_FINE_ROTATION: f32 = 15.0;
+
+/// How the picked image is placed inside the frame's picture area, independent of resolution
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crop {
+    /// Pan within the room left around the visible area, from -1.0 to 1.0 on each axis
+    pub offset_x: f32,
+    pub offset_y: f32,
+    /// 1.0 is the largest view that still fills the picture area
+    pub zoom: f32,
+    /// Clockwise 90° steps
+    pub quarter_turns: u8,
+    /// Clockwise degrees, on top of the quarter turns
+    pub fine_rotation: f32,
+}
+
+impl Default for Crop {
+    fn default() -> Self {
+        Self {
+            offset_x: 0.0,
+            offset_y: 0.0,
+            zoom: 1.0,
+            quarter_turns: 0,
+            fine_rotation: 0.0,
+        }
+    }
+}
+
+impl Hash for Crop {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.offset_x.to_bits().hash(state);
+        self.offset_y.to_bits().hash(state);
+        self.zoom.to_bits().hash(state);
+        self.quarter_turns.hash(state);
+        self.fine_rotation.to_bits().hash(state);
+    }
+}
+
+/// Where the picture area lands on the (quarter-turned) source image
+struct Placement {
+    center_x: f32,
+    center_y: f32,
+    /// Source pixels per picture area pixel
+    scale: f32,
+    slack_x: f32,
+    slack_y: f32,
+    sin: f32,
+    cos: f32,
+}
+
+impl Crop {
+    pub fn turn(&mut self, clockwise: bool) {
+        self.quarter_turns = if clockwise {
+            (self.quarter_turns + 1) % 4
+        } else {
+            (self.quarter_turns + 3) % 4
+        };
+    }
+
+    pub fn tilt(&mut self, degrees: f32) {
+        self.fine_rotation = degrees.clamp(-MAX_FINE_ROTATION, MAX_FINE_ROTATION);
+    }
+
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.zoom = (self.zoom * factor).clamp(1.0, MAX_ZOOM);
+    }
+
+    /// Moves the image along with a drag of `delta` picture area pixels
+    pub fn pan_by(
+        &mut self,
+        (delta_x, delta_y): (f32, f32),
+        source_size: (u32, u32),
+        picture_size: (u32, u32),
+    ) {
+        let (source_width, source_height) = self.turned_size(source_size);
+        let placement = self.placement(source_width, source_height, picture_size);
+
+        // NOTE: the image follows the cursor, so the visible area moves the opposite way
+        let shift_x = -placement.scale * (placement.cos * delta_x - placement.sin * delta_y);
+        let shift_y = -placement.scale * (placement.sin * delta_x + placement.cos * delta_y);
+
+        if placement.slack_x > 0.0 {
+            self.offset_x = (self.offset_x + shift_x * 2.0 / placement.slack_x).clamp(-1.0, 1.0);
+        }
+        if placement.slack_y > 0.0 {
+            self.offset_y = (self.offset_y + shift_y * 2.0 / placement.slack_y).clamp(-1.0, 1.0);
+        }
+    }
+
+    fn turned_size(&self, (width, height): (u32, u32)) -> (f32, f32) {
+        if self.quarter_turns % 2 == 1 {
+            (height as f32, width as f32)
+        } else {
+            (width as f32, height as f32)
+        }
+    }
+
+    fn placement(
+        &self,
+        source_width: f32,
+        source_height: f32,
+        (picture_width, picture_height): (u32, u32),
+    ) -> Placement {
+        let (picture_width, picture_height) = (picture_width as f32, picture_height as f32);
+        let (sin, cos) = self.fine_rotation.to_radians().sin_cos();
+
+        // NOTE: the tilted picture area must stay inside the source, so size its bounding box
+        let bounding_width = picture_width * cos.abs() + picture_height * sin.abs();
+        let bounding_height = picture_width * sin.abs() + picture_height * cos.abs();
+
+        let scale = (source_width / bounding_width).min(source_height / bounding_height)
+            / self.zoom.clamp(1.0, MAX_ZOOM);
+        let slack_x = (source_width - bounding_width * scale).max(0.0);
+        let slack_y = (source_height - bounding_height * scale).max(0.0);
+
+        Placement {
+            center_x: source_width / 2.0 + self.offset_x * slack_x / 2.0,
+            center_y: source_height / 2.0 + self.offset_y * slack_y / 2.0,
+            scale,
+            slack_x,
+            slack_y,
+            sin,
+            cos,
+        }
+    }
+}
+
+/// Renders the cropped, rotated and zoomed image at the size of the picture area
+pub fn render(image: &DynamicImage, crop: &Crop, width: u32, height: u32) -> RgbaImage {
+    let turned_image = match crop.quarter_turns % 4 {
+        1 => Cow::Owned(image.rotate90()),
+        2 => Cow::Owned(image.rotate180()),
+        3 => Cow::Owned(image.rotate270()),
+        _ => Cow::Borrowed(image),
+    };
+    let (source_width, source_height) = (turned_image.width(), turned_image.height());
+    let mut placement = crop.placement(source_width as f32, source_height as f32, (width, height));
+
+    // NOTE: bilinear sampling aliases when shrinking, so downscale close to 1:1 beforehand
+    let source = if placement.scale > 1.0 {
+        let resized_width = ((source_width as f32 / placement.scale).ceil() as u32).max(1);
+        let resized_height = ((source_height as f32 / placement.scale).ceil() as u32).max(1);
+        let ratio = resized_width as f32 / source_width as f32;
+
+        placement.center_x *= ratio;
+        placement.center_y *= ratio;
+        placement.scale *= ratio;
+
+        turned_image
+            .resize_exact(resized_width, resized_height, FilterType::Triangle)
+            .to_rgba8()
+    } else {
+        turned_image.to_rgba8()
+    };
+
+    let max_x = source.width().saturating_sub(1) as f32;
+    let max_y = source.height().saturating_sub(1) as f32;
+    let (half_width, half_height) = (width as f32 / 2.0, height as f32 / 2.0);
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let dx = x as f32 + 0.5 - half_width;
+        let dy = y as f32 + 0.5 - half_height;
+
+        let source_x =
+            placement.center_x + placement.scale * (placement.cos * dx - placement.sin * dy) - 0.5;
+        let source_y =
+            placement.center_y + placement.scale * (placement.sin * dx + placement.cos * dy) - 0.5;
+
+        sample_bilinear(
+            &source,
+            source_x.clamp(0.0, max_x),
+            source_y.clamp(0.0, max_y),
+        )
+    })
+}
+
+fn sample_bilinear(image: &RgbaImage, x: f32, y: f32) -> Rgba<u8> {
+    let (left, top) = (x.floor() as u32, y.floor() as u32);
+    let right = (left + 1).min(image.width() - 1);
+    let bottom = (top + 1).min(image.height() - 1);
+    let (weight_x, weight_y) = (x.fract(), y.fract());
+
+    let [top_left, top_right, bottom_left, bottom_right] = [
+        image.get_pixel(left, top),
+        image.get_pixel(right, top),
+        image.get_pixel(left, bottom),
+        image.get_pixel(right, bottom),
+    ];
+
+    Rgba(std::array::from_fn(|channel| {
+        let top = f32::from(top_left[channel]) * (1.0 - weight_x)
+            + f32::from(top_right[channel]) * weight_x;
+        let bottom = f32::from(bottom_left[channel]) * (1.0 - weight_x)
+            + f32::from(bottom_right[channel]) * weight_x;
+
+        (top * (1.0 - weight_y) + bottom * weight_y).round() as u8
+    }))
+}
+
+impl InstaxFramer {
+    /// Handles the crop messages
+    pub(super) fn update_crop(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::CropDragStarted => self.is_dragging = true,
+            InstaxFramerMessage::CropCursorMoved(position) => {
+                let last_position = self.cursor_position.replace(position);
+
+                if let (true, Some(last_position), Some((source_size, picture_size))) =
+                    (self.is_dragging, last_position, self.crop_sizes())
+                {
+                    let delta = position - last_position;
+
+                    if let Some(crop) = self.crop_mut() {
+                        crop.pan_by((delta.x, delta.y), source_size, picture_size);
+                    }
+
+                    return self.compose_preview();
+                }
+            }
+            InstaxFramerMessage::CropDragEnded => {
+                self.is_dragging = false;
+                self.cursor_position = None;
+            }
+            InstaxFramerMessage::CropZoomed(factor) => {
+                if let Some(crop) = self.crop_mut() {
+                    crop.zoom_by(factor);
+                }
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::CropTurned { clockwise } => {
+                if let Some(crop) = self.crop_mut() {
+                    crop.turn(clockwise);
+                }
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::CropTilted(degrees) => {
+                if let Some(crop) = self.crop_mut() {
+                    crop.tilt(degrees);
+                }
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::CropReset => {
+                if self.show_collage {
+                    if let Some(crop) = self.crop_mut() {
+                        *crop = Crop::default();
+                    }
+                } else if let Some(selected_file) = &self.selected_file {
+                    self.crops.remove(selected_file);
+                }
+
+                return self.compose_preview();
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    pub(super) fn crop_controls(&self) -> Element<'_, InstaxFramerMessage> {
+        let crop = self.crop();
+
+        row![
+            button(text("⟲ 90°")).on_press(InstaxFramerMessage::CropTurned { clockwise: false }),
+            button(text("⟳ 90°")).on_press(InstaxFramerMessage::CropTurned { clockwise: true }),
+            text(format!("Tilt {:+.1}°", crop.fine_rotation)),
+            slider(
+                -MAX_FINE_ROTATION..=MAX_FINE_ROTATION,
+                crop.fine_rotation,
+                InstaxFramerMessage::CropTilted
+            )
+            .step(0.5)
+            .width(100),
+            button(text("Reset"))
+                .style(button::secondary)
+                .on_press_maybe(
+                    (crop != Crop::default()).then_some(InstaxFramerMessage::CropReset)
+                ),
+            checkbox(self.show_grid)
+                .label("Grid")
+                .on_toggle(InstaxFramerMessage::GridToggled),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 6×2 image whose red channel tells the column
+    fn columns() -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(6, 2, |x, _| {
+            Rgba([x as u8 * 40, 0, 0, 255])
+        }))
+    }
+
+    fn red_channel(image: &RgbaImage) -> Vec<u8> {
+        image.pixels().map(|pixel| pixel[0]).collect()
+    }
+
+    #[test]
+    fn wide_images_are_centered_then_panned_to_either_edge() {
+        let mut crop = Crop::default();
+
+        assert_eq!(
+            red_channel(&render(&columns(), &crop, 2, 2)),
+            [80, 120, 80, 120]
+        );
+
+        crop.offset_x = -1.0;
+        assert_eq!(
+            red_channel(&render(&columns(), &crop, 2, 2)),
+            [0, 40, 0, 40]
+        );
+
+        crop.offset_x = 1.0;
+        assert_eq!(
+            red_channel(&render(&columns(), &crop, 2, 2)),
+            [160, 200, 160, 200]
+        );
+    }
+
+    #[test]
+    fn the_image_follows_the_drag() {
+        let mut crop = Crop::default();
+
+        crop.pan_by((1.0, 1.0), (6, 2), (2, 2));
+        assert_eq!((crop.offset_x, crop.offset_y), (-0.5, 0.0));
+
+        crop.pan_by((100.0, 0.0), (6, 2), (2, 2));
+        assert_eq!(crop.offset_x, -1.0);
+    }
+
+    #[test]
+    fn quarter_turns_rotate_clockwise() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(2, 1, |x, _| {
+            Rgba([x as u8 * 200, 0, 0, 255])
+        }));
+        let mut crop = Crop::default();
+
+        crop.turn(true);
+        assert_eq!(red_channel(&render(&image, &crop, 1, 2)), [0, 200]);
+
+        crop.turn(false);
+        crop.turn(false);
+        assert_eq!(crop.quarter_turns, 3);
+        assert_eq!(red_channel(&render(&image, &crop, 1, 2)), [200, 0]);
+    }
+
+    #[test]
+    fn zoom_and_tilt_are_clamped() {
+        let mut crop = Crop::default();
+
+        crop.zoom_by(0.5);
+        assert_eq!(crop.zoom, 1.0);
+        crop.zoom_by(100.0);
+        assert_eq!(crop.zoom, MAX_ZOOM);
+
+        crop.tilt(-40.0);
+        assert_eq!(crop.fine_rotation, -MAX_FINE_ROTATION);
+    }
+
+    #[test]
+    fn tilted_picture_areas_stay_inside_the_source() {
+        let (source_width, source_height) = (400.0, 300.0);
+        let (picture_width, picture_height) = (54, 62);
+
+        for fine_rotation in [-MAX_FINE_ROTATION, -4.0, 7.5, MAX_FINE_ROTATION] {
+            for (offset_x, offset_y) in [(0.0, 0.0), (-1.0, -1.0), (1.0, 1.0), (1.0, -1.0)] {
+                let crop = Crop {
+                    offset_x,
+                    offset_y,
+                    fine_rotation,
+                    ..Crop::default()
+                };
+                let placement =
+                    crop.placement(source_width, source_height, (picture_width, picture_height));
+                let (half_width, half_height) =
+                    (picture_width as f32 / 2.0, picture_height as f32 / 2.0);
+
+                for (dx, dy) in [
+                    (-half_width, -half_height),
+                    (half_width, -half_height),
+                    (-half_width, half_height),
+                    (half_width, half_height),
+                ] {
+                    let x = placement.center_x
+                        + placement.scale * (placement.cos * dx - placement.sin * dy);
+                    let y = placement.center_y
+                        + placement.scale * (placement.sin * dx + placement.cos * dy);
+
+                    assert!(
+                        (-0.01..=source_width + 0.01).contains(&x),
+                        "{x} at {crop:?}"
+                    );
+                    assert!(
+                        (-0.01..=source_height + 0.01).contains(&y),
+                        "{y} at {crop:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn samples_between_pixels_are_blended() {
+        let image = RgbaImage::from_fn(2, 2, |x, y| Rgba([x as u8 * 200, y as u8 * 100, 0, 255]));
+
+        assert_eq!(sample_bilinear(&image, 0.0, 0.0), Rgba([0, 0, 0, 255]));
+        assert_eq!(sample_bilinear(&image, 0.5, 0.5), Rgba([100, 50, 0, 255]));
+        assert_eq!(sample_bilinear(&image, 1.0, 0.25), Rgba([200, 25, 0, 255]));
+    }
+}
@@ -0,0 +1,550 @@
+use std::{borrow::Cow, io::Cursor, path::PathBuf};
+
+use iced::{
+    Alignment, Element, Task,
+    futures::{SinkExt, channel::mpsc},
+    stream,
+    widget::{button, column, pick_list, progress_bar, row, slider, text},
+};
+use image::{
+    DynamicImage, ImageEncoder, ImageError, ImageResult, RgbaImage,
+    codecs::{
+        jpeg::{JpegEncoder, PixelDensity},
+        png::PngEncoder,
+        webp::WebPEncoder,
+    },
+};
+use rfd::{FileDialog, MessageDialog, MessageLevel};
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+
+use super::{InstaxFramer, InstaxFramerMessage, LoadedImage, Picture, metadata};
+
+const MM_PER_INCH: f32 = 25.4;
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    #[default]
+    Png,
+    Jpeg,
+    WebP,
+}
+
+pub static EXPORT_FORMATS: &[ExportFormat] =
+    &[ExportFormat::Png, ExportFormat::Jpeg, ExportFormat::WebP];
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::Png => f.write_str("PNG"),
+            ExportFormat::Jpeg => f.write_str("JPEG"),
+            // NOTE: the image crate only encodes lossless WebP
+            ExportFormat::WebP => f.write_str("WebP (lossless)"),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Jpeg => "jpg",
+            ExportFormat::WebP => "webp",
+        }
+    }
+}
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ExportDpi {
+    #[default]
+    Dpi300,
+    Dpi600,
+}
+
+pub static EXPORT_DPIS: &[ExportDpi] = &[ExportDpi::Dpi300, ExportDpi::Dpi600];
+
+impl std::fmt::Display for ExportDpi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} DPI", self.value())
+    }
+}
+
+impl ExportDpi {
+    pub fn value(self) -> u16 {
+        match self {
+            ExportDpi::Dpi300 => 300,
+            ExportDpi::Dpi600 => 600,
+        }
+    }
+
+    pub fn pixels_per_mm(self) -> f32 {
+        f32::from(self.value()) / MM_PER_INCH
+    }
+}
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ExportMetadata {
+    Strip,
+    #[default]
+    KeepWithoutGps,
+    KeepAll,
+}
+
+pub static EXPORT_METADATA: &[ExportMetadata] = &[
+    ExportMetadata::Strip,
+    ExportMetadata::KeepWithoutGps,
+    ExportMetadata::KeepAll,
+];
+
+impl std::fmt::Display for ExportMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportMetadata::Strip => f.write_str("Strip EXIF"),
+            ExportMetadata::KeepWithoutGps => f.write_str("Keep EXIF, no GPS"),
+            ExportMetadata::KeepAll => f.write_str("Keep all EXIF"),
+        }
+    }
+}
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExportSettings {
+    pub format: ExportFormat,
+    pub dpi: ExportDpi,
+    pub jpeg_quality: u8,
+    #[cfg_attr(feature = "config", serde(default))]
+    pub metadata: ExportMetadata,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::default(),
+            dpi: ExportDpi::default(),
+            jpeg_quality: 92,
+            metadata: ExportMetadata::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportStage {
+    Loading,
+    Composing,
+    Encoding,
+    Writing,
+}
+
+impl std::fmt::Display for ExportStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportStage::Loading => f.write_str("Loading full resolution..."),
+            ExportStage::Composing => f.write_str("Composing frame..."),
+            ExportStage::Encoding => f.write_str("Encoding..."),
+            ExportStage::Writing => f.write_str("Writing file..."),
+        }
+    }
+}
+
+impl ExportStage {
+    pub const COUNT: f32 = 4.0;
+
+    /// Number of stages already finished when this one starts
+    pub fn progress(self) -> f32 {
+        match self {
+            ExportStage::Loading => 0.0,
+            ExportStage::Composing => 1.0,
+            ExportStage::Encoding => 2.0,
+            ExportStage::Writing => 3.0,
+        }
+    }
+}
+
+/// An encoded image, with why the source metadata was left out when it could not be kept
+#[derive(Debug)]
+pub struct Encoded {
+    pub bytes: Vec<u8>,
+    pub metadata_error: Option<String>,
+}
+
+pub fn encode(
+    framed_image: RgbaImage,
+    settings: &ExportSettings,
+    source_exif: Option<&[u8]>,
+) -> ImageResult<Encoded> {
+    let exif = source_exif
+        .and_then(|source_exif| match settings.metadata {
+            ExportMetadata::Strip => None,
+            ExportMetadata::KeepWithoutGps => Some(metadata::exif_for_export(source_exif, false)),
+            ExportMetadata::KeepAll => Some(metadata::exif_for_export(source_exif, true)),
+        })
+        .transpose();
+    // NOTE: the image is still worth writing, the caller tells that its metadata was dropped
+    let (exif, metadata_error) = match exif {
+        Ok(exif) => (exif, None),
+        Err(e) => (None, Some(e)),
+    };
+    let framed_image = DynamicImage::ImageRgba8(framed_image);
+    let mut bytes = Cursor::new(vec![]);
+
+    match settings.format {
+        ExportFormat::Png => write(framed_image, PngEncoder::new(&mut bytes), exif)?,
+        ExportFormat::WebP => write(framed_image, WebPEncoder::new_lossless(&mut bytes), exif)?,
+        ExportFormat::Jpeg => {
+            let mut encoder =
+                JpegEncoder::new_with_quality(&mut bytes, settings.jpeg_quality.clamp(1, 100));
+            encoder.set_pixel_density(PixelDensity::dpi(settings.dpi.value()));
+
+            // NOTE: JPEG has no alpha channel
+            write(
+                DynamicImage::ImageRgb8(framed_image.to_rgb8()),
+                encoder,
+                exif,
+            )?
+        }
+    }
+
+    Ok(Encoded {
+        bytes: bytes.into_inner(),
+        metadata_error,
+    })
+}
+
+fn write(
+    image: DynamicImage,
+    mut encoder: impl ImageEncoder,
+    exif: Option<Vec<u8>>,
+) -> ImageResult<()> {
+    if let Some(exif) = exif {
+        encoder
+            .set_exif_metadata(exif)
+            .map_err(ImageError::Unsupported)?;
+    }
+
+    image.write_with_encoder(encoder)
+}
+
+impl InstaxFramer {
+    /// Handles the export messages
+    pub(super) fn update_export(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::ExportFormatSelected(format) => {
+                self.export_settings.format = format;
+            }
+            InstaxFramerMessage::ExportDpiSelected(dpi) => {
+                self.export_settings.dpi = dpi;
+
+                if self.actual_size {
+                    return self.compose_preview();
+                }
+            }
+            InstaxFramerMessage::ExportMetadataSelected(metadata) => {
+                self.export_settings.metadata = metadata;
+            }
+            InstaxFramerMessage::JpegQualityChanged(quality) => {
+                self.export_settings.jpeg_quality = quality;
+            }
+            InstaxFramerMessage::ExportRequested => {
+                let format = self.export_settings.format;
+                let file_stem = self
+                    .selected_file
+                    .as_ref()
+                    .and_then(|path| path.file_stem())
+                    .map(|file_stem| file_stem.to_string_lossy())
+                    .unwrap_or(Cow::Borrowed("image"));
+
+                let file = FileDialog::new()
+                    .add_filter(format.to_string(), &[format.extension()])
+                    .set_file_name(format!("{file_stem}_instax.{}", format.extension()))
+                    .save_file();
+
+                if let Some(mut file) = file {
+                    if file.extension().is_none() {
+                        file.set_extension(format.extension());
+                    }
+
+                    self.export_stage = Some(ExportStage::Loading);
+                    self.last_exported_file = None;
+
+                    return self.export(file);
+                }
+            }
+            InstaxFramerMessage::ExportProgressed(stage) => self.export_stage = Some(stage),
+            InstaxFramerMessage::ExportFinished(path, notices) => {
+                let was_sheet = std::mem::take(&mut self.sheet_exporting);
+                self.export_stage = None;
+                self.notices.extend(notices);
+                self.last_exported_file = Some(path);
+
+                // NOTE: the current image is done with, a sheet gathers other images
+                if !was_sheet {
+                    return self.open_next_dropped();
+                }
+            }
+            InstaxFramerMessage::ExportFailed(message) => {
+                self.export_stage = None;
+                self.sheet_exporting = false;
+
+                let _ = MessageDialog::new()
+                    .set_title("Export failed...!")
+                    .set_description(message)
+                    .set_level(MessageLevel::Error)
+                    .show();
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    pub(super) fn export_controls(&self) -> Element<'_, InstaxFramerMessage> {
+        let settings = &self.export_settings;
+
+        let controls = row![
+            pick_list(
+                EXPORT_FORMATS,
+                Some(settings.format),
+                InstaxFramerMessage::ExportFormatSelected
+            ),
+            pick_list(
+                EXPORT_DPIS,
+                Some(settings.dpi),
+                InstaxFramerMessage::ExportDpiSelected
+            ),
+            pick_list(
+                EXPORT_METADATA,
+                Some(settings.metadata),
+                InstaxFramerMessage::ExportMetadataSelected
+            ),
+        ]
+        .push((settings.format == ExportFormat::Jpeg).then(|| {
+            row![
+                text(format!("Quality {}", settings.jpeg_quality)),
+                slider(
+                    1..=100,
+                    settings.jpeg_quality,
+                    InstaxFramerMessage::JpegQualityChanged
+                )
+                .width(80),
+            ]
+            .spacing(4)
+            .align_y(Alignment::Center)
+        }))
+        .push(
+            button(text("Export")).on_press_maybe(
+                self.export_stage
+                    .is_none()
+                    .then_some(InstaxFramerMessage::ExportRequested),
+            ),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .wrap();
+
+        let status: Option<Element<'_, InstaxFramerMessage>> =
+            match (&self.export_stage, &self.last_exported_file) {
+                (Some(stage), _) => Some(
+                    row![
+                        progress_bar(0.0..=ExportStage::COUNT, stage.progress())
+                            .length(120)
+                            .girth(8),
+                        text(stage.to_string()),
+                    ]
+                    .spacing(8)
+                    .align_y(Alignment::Center)
+                    .into(),
+                ),
+                (None, Some(exported_file)) => {
+                    Some(text(format!("Saved to {}", exported_file.to_string_lossy())).into())
+                }
+                (None, None) => None,
+            };
+
+        column![controls]
+            .push(self.project_controls())
+            .push(status)
+            .spacing(4)
+            .align_x(Alignment::Center)
+            .into()
+    }
+
+    pub(super) fn export(&self, path: PathBuf) -> Task<InstaxFramerMessage> {
+        let source = if self.show_collage {
+            Picture::Collage(
+                self.collage.clone(),
+                self.collage
+                    .slots
+                    .iter()
+                    .map(|slot| slot.file.clone())
+                    .collect(),
+            )
+        } else {
+            let Some(selected_file) = self.selected_file.clone() else {
+                return Task::none();
+            };
+
+            Picture::Single(selected_file)
+        };
+        let max_pixels = self.max_pixels;
+        let framing = self.framing();
+        let settings = self.export_settings.clone();
+
+        Task::stream(stream::channel(
+            4,
+            async move |mut output: mpsc::Sender<InstaxFramerMessage>| {
+                let mut report = async |stage| {
+                    let _ = output
+                        .send(InstaxFramerMessage::ExportProgressed(stage))
+                        .await;
+                };
+
+                let export_result = async {
+                    report(ExportStage::Loading).await;
+                    let (picture, exif) = tokio::task::spawn_blocking(move || {
+                        // NOTE: a collage keeps the EXIF of its first image that has any
+                        let mut exif = None;
+                        let picture = source.try_map(|file| {
+                            let (image, image_exif) = LoadedImage::decode(&file, max_pixels)
+                                .map_err(|e| {
+                                    format!(
+                                        "Failed to load the full resolution image {}: {e}",
+                                        file.to_string_lossy()
+                                    )
+                                })?;
+                            exif = exif.take().or(image_exif);
+
+                            Ok::<_, String>(image)
+                        })?;
+
+                        Ok::<_, String>((picture, exif))
+                    })
+                    .await
+                    .map_err(|e| e.to_string())??;
+
+                    report(ExportStage::Composing).await;
+                    let pixels_per_mm = settings.dpi.pixels_per_mm();
+                    let framed_image = tokio::task::spawn_blocking(move || {
+                        picture.compose(&framing, pixels_per_mm)
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?;
+
+                    report(ExportStage::Encoding).await;
+                    let encoded = tokio::task::spawn_blocking(move || {
+                        encode(framed_image, &settings, exif.as_deref())
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| format!("Failed to encode image: {e}"))?;
+
+                    report(ExportStage::Writing).await;
+                    let destination = path.clone();
+                    tokio::task::spawn_blocking(move || std::fs::write(destination, encoded.bytes))
+                        .await
+                        .map_err(|e| e.to_string())?
+                        .map_err(|e| format!("Failed to write {}: {e}", path.to_string_lossy()))?;
+
+                    let notices = encoded
+                        .metadata_error
+                        .map(|e| {
+                            format!(
+                                "{} was exported without its metadata: {e}",
+                                path.to_string_lossy()
+                            )
+                        })
+                        .into_iter()
+                        .collect();
+
+                    Ok::<_, String>((path, notices))
+                }
+                .await;
+
+                let _ = output
+                    .send(match export_result {
+                        Ok((path, notices)) => InstaxFramerMessage::ExportFinished(path, notices),
+                        Err(e) => InstaxFramerMessage::ExportFailed(e),
+                    })
+                    .await;
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageFormat, Rgba};
+
+    use super::*;
+
+    fn settings(format: ExportFormat, metadata: ExportMetadata) -> ExportSettings {
+        ExportSettings {
+            format,
+            metadata,
+            ..ExportSettings::default()
+        }
+    }
+
+    #[test]
+    fn every_format_decodes_back() {
+        let framed_image = RgbaImage::from_pixel(6, 4, Rgba([10, 200, 30, 255]));
+
+        for (format, image_format) in [
+            (ExportFormat::Png, ImageFormat::Png),
+            (ExportFormat::Jpeg, ImageFormat::Jpeg),
+            (ExportFormat::WebP, ImageFormat::WebP),
+        ] {
+            let encoded = encode(
+                framed_image.clone(),
+                &settings(format, ExportMetadata::default()),
+                None,
+            )
+            .unwrap();
+            let decoded = image::load_from_memory_with_format(&encoded.bytes, image_format)
+                .unwrap()
+                .to_rgba8();
+
+            assert_eq!(decoded.dimensions(), (6, 4));
+            assert!(encoded.metadata_error.is_none());
+            if format != ExportFormat::Jpeg {
+                assert_eq!(decoded, framed_image);
+            }
+        }
+    }
+
+    #[test]
+    fn unreadable_metadata_is_reported_not_fatal() {
+        let framed_image = RgbaImage::from_pixel(2, 2, Rgba([0, 0, 0, 255]));
+
+        let kept = encode(
+            framed_image.clone(),
+            &settings(ExportFormat::Png, ExportMetadata::KeepAll),
+            Some(b"not exif"),
+        )
+        .unwrap();
+        let stripped = encode(
+            framed_image,
+            &settings(ExportFormat::Png, ExportMetadata::Strip),
+            Some(b"not exif"),
+        )
+        .unwrap();
+
+        assert!(kept.metadata_error.is_some());
+        assert!(image::load_from_memory(&kept.bytes).is_ok());
+        assert!(stripped.metadata_error.is_none());
+    }
+
+    #[test]
+    fn resolutions_are_in_print_millimeters() {
+        assert!((ExportDpi::Dpi300.pixels_per_mm() - 11.811).abs() < 0.001);
+        assert_eq!(
+            ExportDpi::Dpi600.pixels_per_mm(),
+            ExportDpi::Dpi300.pixels_per_mm() * 2.0
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use std::sync::Arc;
+
+use image::{DynamicImage, Pixel, Rgba, RgbaImage, imageops};
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    adjustments::{self, Adjustments},
+    crop::{self, Crop},
+    inscription::{self, Inscription},
+    templates::FrameTemplate,
+};
+
+const GRID_COLOR: Rgba<u8> = Rgba([255, 255, 255, 160]);
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum InstaxFilm {
+    #[default]
+    Mini,
+    Square,
+    Wide,
+}
+
+pub static INSTAX_FILMS: &[InstaxFilm] = &[InstaxFilm::Mini, InstaxFilm::Square, InstaxFilm::Wide];
+
+impl std::fmt::Display for InstaxFilm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstaxFilm::Mini => f.write_str("Instax Mini"),
+            InstaxFilm::Square => f.write_str("Instax Square"),
+            InstaxFilm::Wide => f.write_str("Instax Wide"),
+        }
+    }
+}
+
+/// Physical layout of a print, in millimeters from its top-left corner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameLayout {
+    pub width: f32,
+    pub height: f32,
+    pub picture_x: f32,
+    pub picture_y: f32,
+    pub picture_width: f32,
+    pub picture_height: f32,
+}
+
+/// A rectangle in pixels, for a [`FrameLayout`] rendered at a given resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl InstaxFilm {
+    pub fn layout(self) -> FrameLayout {
+        // NOTE: every film shares the 6.5mm top border and the thicker 17.5mm bottom border
+        let (width, picture_width) = match self {
+            InstaxFilm::Mini => (54.0, 46.0),
+            InstaxFilm::Square => (72.0, 62.0),
+            InstaxFilm::Wide => (108.0, 99.0),
+        };
+
+        FrameLayout {
+            width,
+            height: 86.0,
+            picture_x: (width - picture_width) / 2.0,
+            picture_y: 6.5,
+            picture_width,
+            picture_height: 62.0,
+        }
+    }
+}
+
+impl FrameLayout {
+    pub fn size_px(&self, pixels_per_mm: f32) -> (u32, u32) {
+        (
+            to_px(self.width, pixels_per_mm),
+            to_px(self.height, pixels_per_mm),
+        )
+    }
+
+    pub fn picture_px(&self, pixels_per_mm: f32) -> PixelRect {
+        PixelRect {
+            x: to_px(self.picture_x, pixels_per_mm),
+            y: to_px(self.picture_y, pixels_per_mm),
+            width: to_px(self.picture_width, pixels_per_mm),
+            height: to_px(self.picture_height, pixels_per_mm),
+        }
+    }
+}
+
+fn to_px(millimeters: f32, pixels_per_mm: f32) -> u32 {
+    (millimeters * pixels_per_mm).round() as u32
+}
+
+/// Everything deciding how a print looks, independent of resolution
+#[derive(Debug, Clone, Default)]
+pub struct Framing {
+    pub film: InstaxFilm,
+    pub crop: Crop,
+    pub adjustments: Adjustments,
+    pub inscription: Inscription,
+    pub template: Arc<FrameTemplate>,
+}
+
+/// Crops and adjusts the image to the film's picture area, places it on the template's border
+/// and writes on it
+pub fn compose(image: &DynamicImage, framing: &Framing, pixels_per_mm: f32) -> RgbaImage {
+    let picture_rect = framing.film.layout().picture_px(pixels_per_mm);
+
+    let picture = crop::render(
+        image,
+        &framing.crop,
+        picture_rect.width,
+        picture_rect.height,
+    );
+
+    frame_picture(picture, framing, pixels_per_mm)
+}
+
+/// Adjusts a picture already rendered at the size of the film's picture area, places it on the
+/// template's border and writes on it. Transparent parts of the picture show the border
+pub fn frame_picture(mut picture: RgbaImage, framing: &Framing, pixels_per_mm: f32) -> RgbaImage {
+    let layout = framing.film.layout();
+    let (width, height) = layout.size_px(pixels_per_mm);
+    let picture_rect = layout.picture_px(pixels_per_mm);
+
+    adjustments::apply(&mut picture, &framing.adjustments, pixels_per_mm);
+
+    let mut frame = framing.template.background(width, height, pixels_per_mm);
+    imageops::overlay(
+        &mut frame,
+        &picture,
+        picture_rect.x.into(),
+        picture_rect.y.into(),
+    );
+    framing.template.overlay(&mut frame);
+    inscription::draw(&mut frame, &layout, &framing.inscription, pixels_per_mm);
+
+    frame
+}
+
+/// Draws rule-of-thirds guides over the picture area, for the preview only
+pub fn draw_thirds(framed_image: &mut RgbaImage, picture_rect: PixelRect) {
+    let PixelRect {
+        x,
+        y,
+        width,
+        height,
+    } = picture_rect;
+
+    for third in [1, 2] {
+        let guide_x = x + width * third / 3;
+        let guide_y = y + height * third / 3;
+
+        for row in y..y + height {
+            framed_image.get_pixel_mut(guide_x, row).blend(&GRID_COLOR);
+        }
+        for column in x..x + width {
+            framed_image
+                .get_pixel_mut(column, guide_y)
+                .blend(&GRID_COLOR);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([220, 20, 20, 255]);
+
+    #[test]
+    fn films_share_their_borders() {
+        for film in INSTAX_FILMS.iter().copied() {
+            let layout = film.layout();
+
+            assert_eq!(layout.picture_y, 6.5);
+            assert_eq!(
+                layout.height - layout.picture_y - layout.picture_height,
+                17.5
+            );
+            assert_eq!(layout.picture_x * 2.0 + layout.picture_width, layout.width);
+        }
+    }
+
+    #[test]
+    fn layouts_round_to_whole_pixels() {
+        let layout = InstaxFilm::Mini.layout();
+
+        assert_eq!(layout.size_px(10.0), (540, 860));
+        assert_eq!(
+            layout.picture_px(10.0),
+            PixelRect {
+                x: 40,
+                y: 65,
+                width: 460,
+                height: 620,
+            }
+        );
+        assert_eq!(layout.size_px(300.0 / 25.4), (638, 1016));
+    }
+
+    #[test]
+    fn the_image_fills_the_picture_area_on_the_border() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(300, 200, RED));
+        let framing = Framing {
+            film: InstaxFilm::Square,
+            ..Framing::default()
+        };
+
+        let framed_image = compose(&image, &framing, 4.0);
+        let PixelRect {
+            x,
+            y,
+            width,
+            height,
+        } = InstaxFilm::Square.layout().picture_px(4.0);
+        let border = *framing.template.background(1, 1, 4.0).get_pixel(0, 0);
+
+        assert_eq!(framed_image.dimensions(), (288, 344));
+        assert_eq!(*framed_image.get_pixel(x, y), RED);
+        assert_eq!(*framed_image.get_pixel(x + width - 1, y + height - 1), RED);
+        assert_eq!(*framed_image.get_pixel(x - 1, y), border);
+        assert_eq!(*framed_image.get_pixel(x, y + height), border);
+    }
+
+    #[test]
+    fn thirds_are_drawn_inside_the_picture_area() {
+        let picture_rect = PixelRect {
+            x: 3,
+            y: 3,
+            width: 9,
+            height: 9,
+        };
+        let mut framed_image = RgbaImage::from_pixel(15, 15, Rgba([0, 0, 0, 255]));
+
+        draw_thirds(&mut framed_image, picture_rect);
+
+        assert_ne!(*framed_image.get_pixel(6, 4), Rgba([0, 0, 0, 255]));
+        assert_ne!(*framed_image.get_pixel(4, 9), Rgba([0, 0, 0, 255]));
+        assert_eq!(*framed_image.get_pixel(4, 4), Rgba([0, 0, 0, 255]));
+        assert_eq!(*framed_image.get_pixel(6, 2), Rgba([0, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,606 @@
+use std::{
+    hash::{Hash, Hasher},
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ab_glyph::{Font, FontArc, FontVec, Glyph, PxScale, ScaleFont, point};
+use fontdb::{Database, Family, Query, Style, Weight};
+use iced::{
+    Alignment, Element, Task,
+    widget::{checkbox, pick_list, row, slider, text, text_input},
+};
+use image::{Pixel, Rgba, RgbaImage};
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    InstaxFramer, InstaxFramerMessage,
+    frame::{FrameLayout, PixelRect},
+};
+
+pub const MIN_CAPTION_SIZE: f32 = 3.0;
+pub const MAX_CAPTION_SIZE: f32 = 9.0;
+
+/// Height of the date stamp, in millimeters
+const DATE_STAMP_SIZE: f32 = 2.8;
+/// Distance of the date stamp from the picture edges, in millimeters
+const DATE_STAMP_MARGIN: f32 = 3.0;
+const DATE_STAMP_COLOR: Rgba<u8> = Rgba([255, 146, 36, 235]);
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CaptionFont {
+    #[default]
+    Handwriting,
+    Sans,
+    Serif,
+    Monospace,
+}
+
+pub static CAPTION_FONTS: &[CaptionFont] = &[
+    CaptionFont::Handwriting,
+    CaptionFont::Sans,
+    CaptionFont::Serif,
+    CaptionFont::Monospace,
+];
+
+impl std::fmt::Display for CaptionFont {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptionFont::Handwriting => f.write_str("Handwriting"),
+            CaptionFont::Sans => f.write_str("Sans"),
+            CaptionFont::Serif => f.write_str("Serif"),
+            CaptionFont::Monospace => f.write_str("Monospace"),
+        }
+    }
+}
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CaptionColor {
+    #[default]
+    BlackInk,
+    BlueInk,
+    RedInk,
+    Silver,
+}
+
+pub static CAPTION_COLORS: &[CaptionColor] = &[
+    CaptionColor::BlackInk,
+    CaptionColor::BlueInk,
+    CaptionColor::RedInk,
+    CaptionColor::Silver,
+];
+
+impl std::fmt::Display for CaptionColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptionColor::BlackInk => f.write_str("Black ink"),
+            CaptionColor::BlueInk => f.write_str("Blue ink"),
+            CaptionColor::RedInk => f.write_str("Red ink"),
+            CaptionColor::Silver => f.write_str("Silver"),
+        }
+    }
+}
+
+impl CaptionColor {
+    fn rgba(self) -> Rgba<u8> {
+        // NOTE: slightly translucent, so the paper shows through like real ink
+        match self {
+            CaptionColor::BlackInk => Rgba([30, 30, 36, 235]),
+            CaptionColor::BlueInk => Rgba([28, 58, 150, 235]),
+            CaptionColor::RedInk => Rgba([172, 32, 42, 235]),
+            CaptionColor::Silver => Rgba([150, 150, 158, 245]),
+        }
+    }
+}
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CaptionAlignment {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+pub static CAPTION_ALIGNMENTS: &[CaptionAlignment] = &[
+    CaptionAlignment::Left,
+    CaptionAlignment::Center,
+    CaptionAlignment::Right,
+];
+
+impl std::fmt::Display for CaptionAlignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptionAlignment::Left => f.write_str("Left"),
+            CaptionAlignment::Center => f.write_str("Center"),
+            CaptionAlignment::Right => f.write_str("Right"),
+        }
+    }
+}
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaptionStyle {
+    pub font: CaptionFont,
+    pub color: CaptionColor,
+    pub alignment: CaptionAlignment,
+    /// Font size, in millimeters on the print
+    pub size: f32,
+}
+
+impl Default for CaptionStyle {
+    fn default() -> Self {
+        Self {
+            font: CaptionFont::default(),
+            color: CaptionColor::default(),
+            alignment: CaptionAlignment::default(),
+            size: 5.0,
+        }
+    }
+}
+
+impl Hash for CaptionStyle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.font.hash(state);
+        self.color.hash(state);
+        self.alignment.hash(state);
+        self.size.to_bits().hash(state);
+    }
+}
+
+/// The day a photo was taken, as printed by the date stamp of film cameras
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StampDate {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl std::fmt::Display for StampDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{:02} {:>2} {:>2}",
+            self.year.rem_euclid(100),
+            self.month,
+            self.day
+        )
+    }
+}
+
+impl StampDate {
+    /// Parses EXIF dates, formatted as "YYYY:MM:DD HH:MM:SS"
+    pub fn from_exif(date_time: &str) -> Option<StampDate> {
+        let mut parts = date_time.get(..10)?.split(':');
+        let date = StampDate {
+            year: parts.next()?.parse().ok()?,
+            month: parts.next()?.parse().ok()?,
+            day: parts.next()?.parse().ok()?,
+        };
+
+        // NOTE: cameras with an unset clock write zeroes
+        ((1..=12).contains(&date.month) && (1..=31).contains(&date.day)).then_some(date)
+    }
+
+    /// The UTC day of a file timestamp
+    pub fn from_system_time(time: SystemTime) -> Option<StampDate> {
+        let seconds = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let days = i64::try_from(seconds / 86_400).ok()?;
+
+        // NOTE: days to civil date, from Howard Hinnant's `civil_from_days`
+        let shifted_days = days + 719_468;
+        let era = shifted_days.div_euclid(146_097);
+        let day_of_era = shifted_days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Some(StampDate {
+            year: i32::try_from(year).ok()?,
+            month: u8::try_from(month).ok()?,
+            day: u8::try_from(day).ok()?,
+        })
+    }
+}
+
+/// Everything written on a print, drawn identically into the preview and the export
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Inscription {
+    pub caption: String,
+    pub style: CaptionStyle,
+    pub date_stamp: Option<StampDate>,
+}
+
+/// Writes the caption on the bottom border and the date stamp in the picture's corner
+pub fn draw(
+    frame: &mut RgbaImage,
+    layout: &FrameLayout,
+    inscription: &Inscription,
+    pixels_per_mm: f32,
+) {
+    let picture_rect = layout.picture_px(pixels_per_mm);
+    let picture_bottom = picture_rect.y + picture_rect.height;
+
+    let caption = inscription.caption.trim();
+    if !caption.is_empty()
+        && let Some(font) = font(inscription.style.font)
+    {
+        let border = PixelRect {
+            x: picture_rect.x,
+            y: picture_bottom,
+            width: picture_rect.width,
+            height: frame.height().saturating_sub(picture_bottom),
+        };
+
+        draw_text(
+            frame,
+            font,
+            caption,
+            inscription.style.size * pixels_per_mm,
+            inscription.style.color.rgba(),
+            border,
+            inscription.style.alignment,
+        );
+    }
+
+    if let (Some(date), Some(font)) = (inscription.date_stamp, font(CaptionFont::Monospace)) {
+        let size = DATE_STAMP_SIZE * pixels_per_mm;
+        let margin = DATE_STAMP_MARGIN * pixels_per_mm;
+        let height = (size * 1.5).round() as u32;
+
+        let corner = PixelRect {
+            x: picture_rect.x + margin as u32,
+            y: (picture_bottom as f32 - margin) as u32 - height,
+            width: picture_rect.width.saturating_sub(2 * margin as u32),
+            height,
+        };
+
+        draw_text(
+            frame,
+            font,
+            &date.to_string(),
+            size,
+            DATE_STAMP_COLOR,
+            corner,
+            CaptionAlignment::Right,
+        );
+    }
+}
+
+/// Draws a single line centered vertically in `area`, shrunk to fit its width and clipped to it
+fn draw_text(
+    target: &mut RgbaImage,
+    font: &FontArc,
+    text: &str,
+    size: f32,
+    color: Rgba<u8>,
+    area: PixelRect,
+    alignment: CaptionAlignment,
+) {
+    let (_, natural_width) = layout_line(font, text, PxScale::from(size));
+    let fitted_size = if natural_width > area.width as f32 {
+        size * area.width as f32 / natural_width
+    } else {
+        size
+    };
+
+    let scale = PxScale::from(fitted_size);
+    let (glyphs, width) = layout_line(font, text, scale);
+    let scaled_font = font.as_scaled(scale);
+
+    let left = match alignment {
+        CaptionAlignment::Left => area.x as f32,
+        CaptionAlignment::Center => area.x as f32 + (area.width as f32 - width) / 2.0,
+        CaptionAlignment::Right => (area.x + area.width) as f32 - width,
+    };
+    let text_height = scaled_font.ascent() - scaled_font.descent();
+    let baseline = area.y as f32 + (area.height as f32 - text_height) / 2.0 + scaled_font.ascent();
+
+    let (clip_right, clip_bottom) = (
+        (area.x + area.width).min(target.width()),
+        (area.y + area.height).min(target.height()),
+    );
+
+    for mut glyph in glyphs {
+        glyph.position = point(left + glyph.position.x, baseline);
+
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+
+        outline.draw(|x, y, coverage| {
+            let (Ok(x), Ok(y)) = (
+                u32::try_from(bounds.min.x as i32 + x as i32),
+                u32::try_from(bounds.min.y as i32 + y as i32),
+            ) else {
+                return;
+            };
+
+            if (area.x..clip_right).contains(&x) && (area.y..clip_bottom).contains(&y) {
+                let mut ink = color;
+                ink[3] = (f32::from(color[3]) * coverage.min(1.0)).round() as u8;
+
+                target.get_pixel_mut(x, y).blend(&ink);
+            }
+        });
+    }
+}
+
+/// Positions the glyphs of a line from a zero baseline, returning them with the line's width
+fn layout_line(font: &FontArc, text: &str, scale: PxScale) -> (Vec<Glyph>, f32) {
+    let scaled_font = font.as_scaled(scale);
+    let mut glyphs = vec![];
+    let mut caret = 0.0;
+    let mut previous = None;
+
+    for character in text.chars().filter(|character| !character.is_control()) {
+        let id = scaled_font.glyph_id(character);
+
+        if let Some(previous) = previous {
+            caret += scaled_font.kern(previous, id);
+        }
+
+        glyphs.push(id.with_scale_and_position(scale, point(caret, 0.0)));
+        caret += scaled_font.h_advance(id);
+        previous = Some(id);
+    }
+
+    (glyphs, caret)
+}
+
+/// System fonts for every [`CaptionFont`], looked up once since scanning them is slow
+fn font(caption_font: CaptionFont) -> Option<&'static FontArc> {
+    static FONTS: OnceLock<Vec<Option<FontArc>>> = OnceLock::new();
+
+    FONTS
+        .get_or_init(|| {
+            let mut database = Database::new();
+            database.load_system_fonts();
+
+            let fonts = CAPTION_FONTS
+                .iter()
+                .map(|&caption_font| load_font(&database, caption_font))
+                .collect::<Vec<_>>();
+
+            if fonts.iter().all(Option::is_none) {
+                eprintln!("No usable system font found, captions will not be drawn");
+            }
+
+            fonts
+        })
+        .get(caption_font as usize)?
+        .as_ref()
+}
+
+fn load_font(database: &Database, caption_font: CaptionFont) -> Option<FontArc> {
+    const SANS: &[Family] = &[
+        Family::Name("DejaVu Sans"),
+        Family::Name("Noto Sans"),
+        Family::Name("Liberation Sans"),
+        Family::Name("Segoe UI"),
+        Family::Name("Helvetica"),
+        Family::SansSerif,
+    ];
+    const SERIF: &[Family] = &[
+        Family::Name("DejaVu Serif"),
+        Family::Name("Noto Serif"),
+        Family::Name("Liberation Serif"),
+        Family::Name("Georgia"),
+        Family::Serif,
+    ];
+    const MONOSPACE: &[Family] = &[
+        Family::Name("DejaVu Sans Mono"),
+        Family::Name("Noto Sans Mono"),
+        Family::Name("Liberation Mono"),
+        Family::Name("Consolas"),
+        Family::Name("Menlo"),
+        Family::Monospace,
+    ];
+    // NOTE: generic families are rarely configured, so name common fonts of every platform
+    const HANDWRITING: &[Family] = &[
+        Family::Name("Comic Neue"),
+        Family::Name("Segoe Print"),
+        Family::Name("Bradley Hand"),
+        Family::Name("Comic Sans MS"),
+        Family::Cursive,
+    ];
+
+    let (families, style) = match caption_font {
+        // NOTE: without a handwriting font, a slanted sans looks the most hand-written
+        CaptionFont::Handwriting => (HANDWRITING.iter().chain(SANS), Style::Italic),
+        CaptionFont::Sans => (SANS.iter().chain(&[]), Style::Normal),
+        CaptionFont::Serif => (SERIF.iter().chain(SANS), Style::Normal),
+        CaptionFont::Monospace => (MONOSPACE.iter().chain(SANS), Style::Normal),
+    };
+
+    let id = database
+        .query(&Query {
+            families: &families.cloned().collect::<Vec<_>>(),
+            weight: Weight::NORMAL,
+            style,
+            ..Default::default()
+        })
+        .or_else(|| database.faces().next().map(|face| face.id))?;
+
+    database.with_face_data(id, |data, index| {
+        FontVec::try_from_vec_and_index(data.to_vec(), index)
+            .map(FontArc::new)
+            .ok()
+    })?
+}
+
+impl InstaxFramer {
+    /// Handles the caption and date stamp messages
+    pub(super) fn update_inscription(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::CaptionEdited(caption) => {
+                if let Some(selected_file) = self.selected_file.clone() {
+                    if caption.is_empty() {
+                        self.captions.remove(&selected_file);
+                    } else {
+                        self.captions.insert(selected_file, caption);
+                    }
+                }
+
+                return self.compose_preview();
+            }
+            // NOTE: only there to save the config once the caption is entered or the slider let go,
+            // rather than on every keystroke and slider step
+            InstaxFramerMessage::CaptionSubmitted | InstaxFramerMessage::CaptionSizeReleased => {}
+            InstaxFramerMessage::CaptionFontSelected(font) => {
+                self.caption_style.font = font;
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::CaptionColorSelected(color) => {
+                self.caption_style.color = color;
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::CaptionAlignmentSelected(alignment) => {
+                self.caption_style.alignment = alignment;
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::CaptionSizeChanged(size) => {
+                self.caption_style.size = size;
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::DateStampToggled(date_stamp) => {
+                self.date_stamp = date_stamp;
+
+                return self.compose_preview();
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    pub(super) fn caption_controls(&self) -> Element<'_, InstaxFramerMessage> {
+        let style = &self.caption_style;
+
+        row![
+            text_input("Caption", self.caption())
+                .on_input(InstaxFramerMessage::CaptionEdited)
+                .on_submit(InstaxFramerMessage::CaptionSubmitted)
+                .width(140),
+            pick_list(
+                CAPTION_FONTS,
+                Some(style.font),
+                InstaxFramerMessage::CaptionFontSelected
+            ),
+            pick_list(
+                CAPTION_COLORS,
+                Some(style.color),
+                InstaxFramerMessage::CaptionColorSelected
+            ),
+            pick_list(
+                CAPTION_ALIGNMENTS,
+                Some(style.alignment),
+                InstaxFramerMessage::CaptionAlignmentSelected
+            ),
+            row![
+                text(format!("Size {:.1}mm", style.size)),
+                slider(
+                    MIN_CAPTION_SIZE..=MAX_CAPTION_SIZE,
+                    style.size,
+                    InstaxFramerMessage::CaptionSizeChanged
+                )
+                .on_release(InstaxFramerMessage::CaptionSizeReleased)
+                .step(0.5)
+                .width(80),
+            ]
+            .spacing(4)
+            .align_y(Alignment::Center),
+            checkbox(self.date_stamp)
+                .label("Date stamp")
+                .on_toggle(InstaxFramerMessage::DateStampToggled),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .wrap()
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn date(year: i32, month: u8, day: u8) -> Option<StampDate> {
+        Some(StampDate { year, month, day })
+    }
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn reads_exif_dates() {
+        assert_eq!(
+            StampDate::from_exif("2024:03:07 18:42:05"),
+            date(2024, 3, 7)
+        );
+        assert_eq!(StampDate::from_exif("2024:03:07"), date(2024, 3, 7));
+    }
+
+    #[test]
+    fn refuses_unset_and_malformed_exif_dates() {
+        assert_eq!(StampDate::from_exif("0000:00:00 00:00:00"), None);
+        assert_eq!(StampDate::from_exif("2024:13:07 18:42:05"), None);
+        assert_eq!(StampDate::from_exif("2024-03-07 18:42:05"), None);
+        assert_eq!(StampDate::from_exif("2024:03"), None);
+        assert_eq!(StampDate::from_exif("    :  :   "), None);
+    }
+
+    #[test]
+    fn prints_like_a_film_camera() {
+        assert_eq!(date(2024, 3, 7).unwrap().to_string(), "'24  3  7");
+        assert_eq!(date(2009, 11, 28).unwrap().to_string(), "'09 11 28");
+    }
+
+    #[test]
+    fn timestamps_turn_into_utc_days() {
+        assert_eq!(StampDate::from_system_time(UNIX_EPOCH), date(1970, 1, 1));
+        assert_eq!(StampDate::from_system_time(at(86_399)), date(1970, 1, 1));
+        assert_eq!(
+            StampDate::from_system_time(at(951_782_400)),
+            date(2000, 2, 29)
+        );
+        assert_eq!(
+            StampDate::from_system_time(at(1_735_689_599)),
+            date(2024, 12, 31)
+        );
+        assert_eq!(
+            StampDate::from_system_time(at(4_107_542_400)),
+            date(2100, 3, 1)
+        );
+    }
+
+    #[test]
+    fn timestamps_before_1970_have_no_day() {
+        assert_eq!(
+            StampDate::from_system_time(UNIX_EPOCH - Duration::from_secs(1)),
+            None
+        );
+    }
+}
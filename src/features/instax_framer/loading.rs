@@ -0,0 +1,92 @@
+use std::io;
+
+use image::{ImageError, error::ImageFormatHint};
+
+/// File extensions of the formats built in, see the format features in `Cargo.toml`
+pub const IMAGE_EXTENSIONS: &[&str] = &[
+    "jpg",
+    "jpeg",
+    "png",
+    "webp",
+    #[cfg(feature = "gif")]
+    "gif",
+    #[cfg(feature = "bmp")]
+    "bmp",
+    #[cfg(feature = "tiff")]
+    "tif",
+    #[cfg(feature = "tiff")]
+    "tiff",
+    #[cfg(feature = "avif")]
+    "avif",
+    #[cfg(feature = "qoi")]
+    "qoi",
+];
+
+/// Why an image could not be opened, worded for the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageLoadError {
+    NotFound,
+    PermissionDenied,
+    /// Not an image, or a format this build does not include
+    UnsupportedFormat(String),
+    Corrupt(String),
+    TooLarge(String),
+    Io(String),
+}
+
+impl std::fmt::Display for ImageLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageLoadError::NotFound => f.write_str("The file does not exist"),
+            ImageLoadError::PermissionDenied => f.write_str("Permission to read the file denied"),
+            ImageLoadError::UnsupportedFormat(format) if format.is_empty() => write!(
+                f,
+                "Not an image in a supported format, supported are {}",
+                IMAGE_EXTENSIONS.join("/")
+            ),
+            ImageLoadError::UnsupportedFormat(format) => write!(
+                f,
+                "The {format} format is not supported by this build, supported are {}",
+                IMAGE_EXTENSIONS.join("/")
+            ),
+            ImageLoadError::Corrupt(details) => write!(f, "The image data is corrupt: {details}"),
+            ImageLoadError::TooLarge(details) => write!(f, "The image is too large: {details}"),
+            ImageLoadError::Io(details) => write!(f, "The file could not be read: {details}"),
+        }
+    }
+}
+
+impl From<io::Error> for ImageLoadError {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => ImageLoadError::NotFound,
+            io::ErrorKind::PermissionDenied => ImageLoadError::PermissionDenied,
+            io::ErrorKind::OutOfMemory => ImageLoadError::TooLarge(error.to_string()),
+            // NOTE: decoders report truncated files as I/O errors
+            io::ErrorKind::UnexpectedEof | io::ErrorKind::InvalidData => {
+                ImageLoadError::Corrupt(error.to_string())
+            }
+            _ => ImageLoadError::Io(error.to_string()),
+        }
+    }
+}
+
+impl From<ImageError> for ImageLoadError {
+    fn from(error: ImageError) -> Self {
+        match error {
+            ImageError::IoError(error) => error.into(),
+            ImageError::Unsupported(error) => {
+                ImageLoadError::UnsupportedFormat(match error.format_hint() {
+                    ImageFormatHint::Exact(format) => format!("{format:?}"),
+                    ImageFormatHint::Name(name) => name,
+                    // NOTE: only guessed from the extension, the contents matched no format
+                    _ => String::new(),
+                })
+            }
+            ImageError::Decoding(error) => ImageLoadError::Corrupt(error.to_string()),
+            ImageError::Limits(error) => ImageLoadError::TooLarge(error.to_string()),
+            ImageError::Parameter(error) => ImageLoadError::Corrupt(error.to_string()),
+            ImageError::Encoding(error) => ImageLoadError::Io(error.to_string()),
+        }
+    }
+}
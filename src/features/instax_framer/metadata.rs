@@ -0,0 +1,251 @@
+use std::{
+    io::{BufReader, Cursor},
+    path::Path,
+};
+
+use exif::{Context, Field, In, Reader, Tag, Value, experimental::Writer};
+use iced::{
+    Element,
+    widget::{column, row, text},
+};
+use image::{DynamicImage, ImageFormat, metadata::Orientation};
+
+use super::{InstaxFramer, InstaxFramerMessage, inscription::StampDate};
+
+/// The EXIF fields worth showing next to the preview
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageMetadata {
+    pub camera: Option<String>,
+    pub lens: Option<String>,
+    pub exposure: Option<String>,
+    pub captured_at: Option<String>,
+    pub captured_on: Option<StampDate>,
+    /// Dimensions after the EXIF orientation was applied
+    pub width: u32,
+    pub height: u32,
+    pub has_gps: bool,
+}
+
+/// Fields describing the source file rather than the photo, wrong once the image is framed
+const SOURCE_ONLY_TAGS: &[Tag] = &[
+    Tag::Orientation,
+    Tag::ImageWidth,
+    Tag::ImageLength,
+    Tag::PixelXDimension,
+    Tag::PixelYDimension,
+    Tag::MakerNote,
+];
+
+impl ImageMetadata {
+    pub fn read(raw_exif: Option<&[u8]>, width: u32, height: u32) -> ImageMetadata {
+        let Some(exif) =
+            raw_exif.and_then(|raw_exif| Reader::new().read_raw(raw_exif.to_vec()).ok())
+        else {
+            return Self {
+                width,
+                height,
+                ..Default::default()
+            };
+        };
+
+        let field = |tag| exif.get_field(tag, In::PRIMARY);
+        let ascii = |tag| {
+            field(tag)
+                .and_then(|field| match &field.value {
+                    Value::Ascii(values) => values.first(),
+                    _ => None,
+                })
+                .map(|value| {
+                    String::from_utf8_lossy(value)
+                        .trim_end_matches('\0')
+                        .trim()
+                        .to_owned()
+                })
+                .filter(|value| !value.is_empty())
+        };
+        let display =
+            |tag| field(tag).map(|field| field.display_value().with_unit(&exif).to_string());
+
+        let camera = match (ascii(Tag::Make), ascii(Tag::Model)) {
+            // NOTE: most models already start with the make, e.g. "Canon" and "Canon EOS R6"
+            (Some(make), Some(model)) if !model.starts_with(&make) => {
+                Some(format!("{make} {model}"))
+            }
+            (make, model) => model.or(make),
+        };
+
+        let exposure = [
+            display(Tag::ExposureTime),
+            display(Tag::FNumber),
+            display(Tag::PhotographicSensitivity).map(|iso| format!("ISO {iso}")),
+            display(Tag::FocalLength),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        Self {
+            camera,
+            lens: ascii(Tag::LensModel),
+            exposure: (!exposure.is_empty()).then(|| exposure.join(" · ")),
+            captured_at: display(Tag::DateTimeOriginal).or_else(|| display(Tag::DateTime)),
+            captured_on: ascii(Tag::DateTimeOriginal)
+                .or_else(|| ascii(Tag::DateTime))
+                .and_then(|date_time| StampDate::from_exif(&date_time)),
+            width,
+            height,
+            has_gps: exif
+                .fields()
+                .any(|field| field.tag.context() == Context::Gps),
+        }
+    }
+}
+
+/// Rewrites the EXIF of the source for the framed output, optionally dropping every GPS field.
+/// Fields of types the writer does not know, common in camera files, are left out
+pub fn exif_for_export(raw_exif: &[u8], keep_gps: bool) -> Result<Vec<u8>, String> {
+    let exif = Reader::new()
+        .read_raw(raw_exif.to_vec())
+        .map_err(|e| format!("Unreadable EXIF: {e}"))?;
+
+    // NOTE: the pixels are already upright, so the output must not be rotated again
+    let orientation = Field {
+        tag: Tag::Orientation,
+        ifd_num: In::PRIMARY,
+        value: Value::Short(vec![1]),
+    };
+
+    let mut writer = Writer::new();
+
+    exif.fields()
+        .filter(|field| field.ifd_num == In::PRIMARY)
+        .filter(|field| !SOURCE_ONLY_TAGS.contains(&field.tag))
+        .filter(|field| keep_gps || field.tag.context() != Context::Gps)
+        .filter(|field| !matches!(field.value, Value::Unknown(..)))
+        .for_each(|field| writer.push_field(field));
+    writer.push_field(&orientation);
+
+    let mut buffer = Cursor::new(vec![]);
+    writer
+        .write(&mut buffer, exif.little_endian())
+        .map_err(|e| format!("Failed to rewrite EXIF: {e}"))?;
+
+    Ok(buffer.into_inner())
+}
+
+/// The preview cameras embed in a JPEG's EXIF, upright, a fraction of the cost of decoding the
+/// image itself
+pub fn embedded_thumbnail(path: &Path) -> Option<DynamicImage> {
+    // NOTE: reading the EXIF of other containers, TIFF first, may read the whole file
+    let is_jpeg = path.extension().is_some_and(|extension| {
+        extension.eq_ignore_ascii_case("jpg") || extension.eq_ignore_ascii_case("jpeg")
+    });
+    if !is_jpeg {
+        return None;
+    }
+
+    let file = std::fs::File::open(path).ok()?;
+    let exif = Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+    let uint = |tag, ifd_num| exif.get_field(tag, ifd_num)?.value.get_uint(0);
+
+    let offset = usize::try_from(uint(Tag::JPEGInterchangeFormat, In::THUMBNAIL)?).ok()?;
+    let length = usize::try_from(uint(Tag::JPEGInterchangeFormatLength, In::THUMBNAIL)?).ok()?;
+    let jpeg = exif.buf().get(offset..offset.checked_add(length)?)?;
+
+    let mut thumbnail = image::load_from_memory_with_format(jpeg, ImageFormat::Jpeg).ok()?;
+    if let Some(orientation) = uint(Tag::Orientation, In::PRIMARY)
+        .and_then(|orientation| u8::try_from(orientation).ok())
+        .and_then(Orientation::from_exif)
+    {
+        thumbnail.apply_orientation(orientation);
+    }
+
+    Some(thumbnail)
+}
+
+impl InstaxFramer {
+    pub(super) fn metadata_panel(&self) -> Option<Element<'_, InstaxFramerMessage>> {
+        let metadata = &self.loaded_image.as_ref()?.metadata;
+
+        if !self.show_metadata {
+            return None;
+        }
+
+        let line = |label: &'static str, value: Option<String>| {
+            row![
+                text(label).size(12).width(64),
+                text(value.unwrap_or_else(|| "—".to_owned())).size(12),
+            ]
+        };
+
+        Some(
+            column![
+                line("Camera", metadata.camera.clone()),
+                line("Lens", metadata.lens.clone()),
+                line("Exposure", metadata.exposure.clone()),
+                line("Captured", metadata.captured_at.clone()),
+                line(
+                    "Size",
+                    Some(format!("{} × {} px", metadata.width, metadata.height))
+                ),
+                line(
+                    "GPS",
+                    Some(if metadata.has_gps { "Present" } else { "None" }.to_owned())
+                ),
+            ]
+            .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A little-endian TIFF header and one IFD of 12-byte entries holding inline values
+    fn raw_exif(entries: &[(u16, u16, u32, [u8; 4])]) -> Vec<u8> {
+        let mut raw = b"II*\0".to_vec();
+        raw.extend_from_slice(&8u32.to_le_bytes());
+        raw.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for (tag, kind, count, value) in entries {
+            raw.extend_from_slice(&tag.to_le_bytes());
+            raw.extend_from_slice(&kind.to_le_bytes());
+            raw.extend_from_slice(&count.to_le_bytes());
+            raw.extend_from_slice(value);
+        }
+        raw.extend_from_slice(&0u32.to_le_bytes());
+
+        raw
+    }
+
+    const ORIENTATION_ROTATED: (u16, u16, u32, [u8; 4]) = (0x0112, 3, 1, [6, 0, 0, 0]);
+    const MAKE: (u16, u16, u32, [u8; 4]) = (0x010f, 2, 4, *b"Fuj\0");
+    /// A field of a type neither EXIF nor the writer knows
+    const UNKNOWN_TYPE: (u16, u16, u32, [u8; 4]) = (0xc000, 99, 1, [1, 2, 3, 4]);
+
+    #[test]
+    fn export_skips_fields_it_cannot_write() {
+        let raw = raw_exif(&[MAKE, ORIENTATION_ROTATED, UNKNOWN_TYPE]);
+
+        let exported = exif_for_export(&raw, true).unwrap();
+        let exif = Reader::new().read_raw(exported).unwrap();
+
+        assert!(
+            exif.get_field(Tag::Make, In::PRIMARY)
+                .is_some_and(|field| field.display_value().to_string() == "\"Fuj\"")
+        );
+        assert_eq!(
+            exif.get_field(Tag::Orientation, In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0)),
+            Some(1)
+        );
+        assert_eq!(exif.fields().count(), 2);
+    }
+
+    #[test]
+    fn export_reports_unreadable_exif() {
+        assert!(exif_for_export(b"not exif", true).is_err());
+    }
+}
@@ -0,0 +1,1542 @@
+mod adjustments;
+mod batch;
+mod browser;
+#[cfg(feature = "camera")]
+mod capture;
+mod collage;
+mod compare;
+mod crop;
+mod export;
+mod frame;
+mod inscription;
+mod loading;
+mod metadata;
+#[cfg(feature = "camera")]
+mod photobooth;
+#[cfg(feature = "config")]
+mod project;
+mod sheet;
+mod templates;
+mod workers;
+
+#[cfg(feature = "config")]
+use iced::widget::checkbox;
+use iced::{
+    Alignment, Border, Element, Length, Point, Subscription, Task, Theme, event,
+    keyboard::{self, key},
+    mouse::{self, ScrollDelta},
+    task, time,
+    widget::{
+        button, column, container, image as iced_image, image::FilterMethod, mouse_area, pick_list,
+        responsive, row, scrollable, text,
+    },
+};
+use image::{DynamicImage, ImageDecoder, ImageReader, Limits};
+use rfd::{FileDialog, MessageDialog, MessageLevel};
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "camera")]
+use std::time::Instant;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+pub use adjustments::{Adjustment, Adjustments, FilmPreset};
+pub use batch::{BatchRun, DEFAULT_NAME_PATTERN, FramedFile};
+pub use browser::{FolderBrowser, RecentFile};
+#[cfg(feature = "camera")]
+pub use capture::{CapturedFrame, FrameSourceKind, Viewfinder};
+pub use collage::{Collage, CollageLayout, Picture};
+pub use compare::CompareMode;
+pub use crop::Crop;
+
+pub use export::{ExportDpi, ExportFormat, ExportMetadata, ExportSettings, ExportStage};
+pub use frame::{Framing, INSTAX_FILMS, InstaxFilm};
+pub use inscription::{
+    CaptionAlignment, CaptionColor, CaptionFont, CaptionStyle, Inscription, StampDate,
+};
+pub use loading::{IMAGE_EXTENSIONS, ImageLoadError};
+pub use metadata::ImageMetadata;
+#[cfg(feature = "camera")]
+pub use photobooth::{PhotoboothRun, PhotoboothSettings};
+#[cfg(feature = "config")]
+pub use project::Project;
+pub use sheet::{PaperSize, SheetJob, SheetLayout, SheetOutput, SheetSettings, SheetThumbnail};
+pub use templates::{BUILT_IN_TEMPLATES, FrameTemplate};
+
+/// Resolution of the on-screen preview, far below print resolution to keep re-framing cheap
+const PREVIEW_PIXELS_PER_MM: f32 = 6.0;
+/// Longest edge of the downscaled copy the live preview is composed from
+const PREVIEW_PROXY_SIZE: u32 = 1600;
+const ZOOM_STEP: f32 = 1.1;
+pub const DEFAULT_MAX_PIXELS: u64 = 100_000_000;
+const SPINNER_FRAMES: &[&str] = &["◐", "◓", "◑", "◒"];
+#[cfg(feature = "config")]
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default)]
+pub struct InstaxFramer {
+    pub(crate) selected_file: Option<PathBuf>,
+    /// Framing of every image picked so far, restored when it is opened again
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) crops: BTreeMap<PathBuf, Crop>,
+    /// Film look and corrections of every image, applied when framing
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) adjustments: BTreeMap<PathBuf, Adjustments>,
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) film: InstaxFilm,
+    /// Name of the selected frame template, the classic white border when unset or missing
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) template: Option<String>,
+    /// Templates read from the templates directory, listed after the built-in ones
+    #[cfg_attr(feature = "config", serde(skip))]
+    pub(crate) user_templates: Vec<Arc<FrameTemplate>>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    loaded_image: Option<LoadedImage>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    image_load: Option<ImageLoad>,
+    /// Bumped on every pick, so that the result of a replaced load is dropped
+    #[cfg_attr(feature = "config", serde(skip))]
+    load_generation: u64,
+    #[cfg_attr(feature = "config", serde(skip))]
+    spinner_frame: usize,
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) export_settings: ExportSettings,
+    /// Caption written on the bottom border of every image
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) captions: BTreeMap<PathBuf, String>,
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) caption_style: CaptionStyle,
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) date_stamp: bool,
+    #[cfg_attr(feature = "config", serde(skip))]
+    preview: Option<iced_image::Handle>,
+    /// Bumped on every re-compose, so that slower, outdated previews are dropped
+    #[cfg_attr(feature = "config", serde(skip))]
+    preview_generation: u64,
+    #[cfg_attr(feature = "config", serde(skip))]
+    show_grid: bool,
+    #[cfg_attr(feature = "config", serde(skip))]
+    compare_mode: CompareMode,
+    /// Position of the divider between the print without and with adjustments, from 0.0 to 1.0
+    #[cfg_attr(feature = "config", serde(skip, default = "default_compare_split"))]
+    compare_split: f32,
+    /// Shows the preview pixel for pixel, composed at the export resolution
+    #[cfg_attr(feature = "config", serde(skip))]
+    actual_size: bool,
+    /// The images of the picture at full resolution, decoded for the actual size view only
+    #[cfg_attr(feature = "config", serde(skip))]
+    full_images: BTreeMap<PathBuf, Arc<DynamicImage>>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    full_loading: BTreeSet<PathBuf>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    show_metadata: bool,
+    #[cfg_attr(feature = "config", serde(skip))]
+    show_adjustments: bool,
+    #[cfg_attr(feature = "config", serde(skip))]
+    is_dragging: bool,
+    #[cfg_attr(feature = "config", serde(skip))]
+    cursor_position: Option<Point>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    export_stage: Option<ExportStage>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    last_exported_file: Option<PathBuf>,
+    /// File names of batch outputs, see [`batch`] for the placeholders
+    #[cfg_attr(feature = "config", serde(default = "default_name_pattern"))]
+    pub(crate) batch_name_pattern: String,
+    #[cfg_attr(feature = "config", serde(skip))]
+    batch: Option<BatchRun>,
+    /// Images tiled on the print sheet, in order
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) sheet_files: Vec<PathBuf>,
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) sheet_settings: SheetSettings,
+    #[cfg_attr(feature = "config", serde(skip))]
+    show_sheet: bool,
+    #[cfg_attr(feature = "config", serde(skip))]
+    sheet_page: usize,
+    #[cfg_attr(feature = "config", serde(skip))]
+    sheet_preview: Option<iced_image::Handle>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    sheet_generation: u64,
+    #[cfg_attr(feature = "config", serde(skip))]
+    sheet_thumbnails: BTreeMap<PathBuf, SheetThumbnail>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    sheet_exporting: bool,
+    /// Images sharing the frame while in collage mode, kept when leaving it
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) collage: Collage,
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) show_collage: bool,
+    /// Index of the slot the crop controls apply to
+    #[cfg_attr(feature = "config", serde(skip))]
+    collage_slot: usize,
+    /// Slot being dragged onto another one to swap them
+    #[cfg_attr(feature = "config", serde(skip))]
+    collage_dragged: Option<usize>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    collage_images: BTreeMap<PathBuf, SlotImage>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    collage_loading: BTreeSet<PathBuf>,
+    /// Files are being dragged over the window
+    #[cfg_attr(feature = "config", serde(skip))]
+    drop_hovered: bool,
+    /// Dropped images waiting for their turn, the next one opens once the current one is
+    /// exported or fails to open
+    #[cfg_attr(feature = "config", serde(skip))]
+    drop_queue: VecDeque<PathBuf>,
+    /// A project file is being read, the load of its image follows
+    #[cfg(feature = "config")]
+    #[serde(skip)]
+    opening_project: bool,
+    /// Why dropped or recent files were not opened, shown until dismissed
+    #[cfg_attr(feature = "config", serde(skip))]
+    notices: Vec<String>,
+    /// Most recently opened images first, see [`browser::remember`]
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) recent_files: Vec<RecentFile>,
+    #[cfg_attr(feature = "config", serde(skip))]
+    show_recent_files: bool,
+    #[cfg_attr(feature = "config", serde(skip))]
+    folder: Option<FolderBrowser>,
+    /// Shown instead of the framer while open
+    #[cfg(feature = "camera")]
+    #[cfg_attr(feature = "config", serde(skip))]
+    viewfinder: Option<Viewfinder>,
+    #[cfg(feature = "camera")]
+    #[cfg_attr(feature = "config", serde(default))]
+    pub(crate) photobooth: PhotoboothSettings,
+    /// Copy the image into saved projects instead of referencing its path
+    #[cfg(feature = "config")]
+    #[serde(default)]
+    pub(crate) embed_in_project: bool,
+    /// Project file last opened or saved, suggested when saving again
+    #[cfg(feature = "config")]
+    #[serde(skip)]
+    project_file: Option<PathBuf>,
+    /// Hash of the project last autosaved, opened or saved, to only autosave changes
+    #[cfg(feature = "config")]
+    #[serde(skip)]
+    autosaved_hash: Option<u64>,
+    /// Images with more pixels are refused instead of decoded, only settable in the config file
+    #[cfg_attr(feature = "config", serde(default = "default_max_pixels"))]
+    pub(crate) max_pixels: u64,
+}
+
+/// An image being decoded, replaced by the next pick
+#[derive(Debug, Clone)]
+struct ImageLoad {
+    generation: u64,
+    handle: task::Handle,
+    /// Shown again when the load is cancelled or fails
+    previous: Option<(PathBuf, LoadedImage)>,
+}
+
+/// Decoded image of a collage slot, downscaled like [`LoadedImage`]
+#[derive(Debug, Clone)]
+pub struct SlotImage {
+    proxy: Arc<DynamicImage>,
+    thumbnail: iced_image::Handle,
+}
+
+impl SlotImage {
+    fn new(proxy: Arc<DynamicImage>) -> SlotImage {
+        let size = browser::THUMBNAIL_SIZE * 2;
+        let thumbnail = proxy.thumbnail(size, size).to_rgba8();
+
+        Self {
+            proxy,
+            thumbnail: iced_image::Handle::from_rgba(
+                thumbnail.width(),
+                thumbnail.height(),
+                thumbnail.into_raw(),
+            ),
+        }
+    }
+}
+
+/// A small copy of the image for the live preview to re-frame cheaply, the full resolution is
+/// only decoded again when exporting or viewing at actual size
+#[derive(Debug, Clone)]
+pub struct LoadedImage {
+    proxy: Arc<DynamicImage>,
+    metadata: Arc<ImageMetadata>,
+    /// From the EXIF capture date, or the file's modification date without one
+    taken_on: Option<StampDate>,
+}
+
+impl LoadedImage {
+    fn new(image: DynamicImage, exif: Option<Vec<u8>>) -> LoadedImage {
+        let proxy = image.thumbnail(PREVIEW_PROXY_SIZE, PREVIEW_PROXY_SIZE);
+        let metadata = ImageMetadata::read(exif.as_deref(), image.width(), image.height());
+
+        Self {
+            proxy: Arc::new(proxy),
+            taken_on: metadata.captured_on,
+            metadata: Arc::new(metadata),
+        }
+    }
+
+    fn open(path: &Path, max_pixels: u64) -> Result<LoadedImage, ImageLoadError> {
+        let (image, exif) = LoadedImage::decode(path, max_pixels)?;
+
+        let mut loaded_image = LoadedImage::new(image, exif);
+        loaded_image.taken_on = LoadedImage::taken_on(path, &loaded_image.metadata);
+
+        Ok(loaded_image)
+    }
+
+    /// Decodes the image upright, following its EXIF orientation, along with its raw EXIF.
+    /// Images above `max_pixels` are refused before anything is allocated for them
+    fn decode(
+        path: &Path,
+        max_pixels: u64,
+    ) -> Result<(DynamicImage, Option<Vec<u8>>), ImageLoadError> {
+        let mut decoder = ImageReader::open(path)?
+            .with_guessed_format()?
+            .into_decoder()?;
+
+        let (width, height) = decoder.dimensions();
+        let pixels = u64::from(width) * u64::from(height);
+        if pixels > max_pixels {
+            return Err(ImageLoadError::TooLarge(format!(
+                "{width} × {height} px is {:.1} megapixels, the limit is {:.1}",
+                pixels as f64 / 1e6,
+                max_pixels as f64 / 1e6
+            )));
+        }
+
+        // NOTE: room for 16-bit RGBA, the widest color type decoders produce
+        let mut limits = Limits::default();
+        limits.max_alloc = Some(max_pixels.saturating_mul(8));
+        decoder.set_limits(limits)?;
+
+        let exif = decoder.exif_metadata()?;
+        let orientation = decoder.orientation()?;
+
+        let mut image = DynamicImage::from_decoder(decoder)?;
+        image.apply_orientation(orientation);
+
+        Ok((image, exif))
+    }
+
+    /// The EXIF capture date, or the file's modification date without one
+    fn taken_on(path: &Path, metadata: &ImageMetadata) -> Option<StampDate> {
+        metadata.captured_on.or_else(|| {
+            std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(StampDate::from_system_time)
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum InstaxFramerMessage {
+    PickImage,
+    ImagePicked(PathBuf),
+    ImageLoadingFailed(u64, ImageLoadError),
+    ImageLoadingFinished(u64, LoadedImage),
+    ImageLoadingCancelled,
+    LoadingSpinnerTicked,
+    FilmSelected(InstaxFilm),
+    TemplateSelected(String),
+    #[cfg(feature = "config")]
+    TemplatesReloadRequested,
+    #[cfg(feature = "config")]
+    TemplatesLoaded(Vec<Arc<FrameTemplate>>, Vec<String>),
+    #[cfg(feature = "config")]
+    ProjectOpenRequested,
+    #[cfg(feature = "config")]
+    ProjectOpened(PathBuf, Result<(PathBuf, Project), String>),
+    #[cfg(feature = "config")]
+    ProjectSaveRequested,
+    #[cfg(feature = "config")]
+    ProjectSaved(Result<PathBuf, String>),
+    #[cfg(feature = "config")]
+    EmbedInProjectToggled(bool),
+    #[cfg(feature = "config")]
+    AutosaveTicked,
+    /// Offers to reopen the autosave, left behind when the app did not close cleanly
+    #[cfg(feature = "config")]
+    AutosaveRecoveryChecked,
+    #[cfg(feature = "camera")]
+    ViewfinderOpened,
+    #[cfg(feature = "camera")]
+    ViewfinderSourcesListed(Vec<FrameSourceKind>),
+    #[cfg(feature = "camera")]
+    ViewfinderSourceSelected(FrameSourceKind),
+    #[cfg(feature = "camera")]
+    ViewfinderFolderRequested,
+    #[cfg(feature = "camera")]
+    ViewfinderFrame(Result<CapturedFrame, String>),
+    #[cfg(feature = "camera")]
+    ShotTaken,
+    /// Opens the saved shot for framing
+    #[cfg(feature = "camera")]
+    ShotSaved(Result<PathBuf, String>),
+    #[cfg(feature = "camera")]
+    ViewfinderClosed,
+    #[cfg(feature = "camera")]
+    PhotoboothCountdownSelected(u32),
+    #[cfg(feature = "camera")]
+    PhotoboothShotsSelected(u32),
+    #[cfg(feature = "camera")]
+    PhotoboothIntervalSelected(u32),
+    #[cfg(feature = "camera")]
+    PhotoboothStarted,
+    #[cfg(feature = "camera")]
+    PhotoboothStopped,
+    #[cfg(feature = "camera")]
+    PhotoboothTicked(Instant),
+    #[cfg(feature = "camera")]
+    PhotoboothShotFramed(Result<PathBuf, String>),
+    PreviewComposed(u64, iced_image::Handle),
+    CropDragStarted,
+    CropCursorMoved(Point),
+    CropDragEnded,
+    CropZoomed(f32),
+    CropTurned {
+        clockwise: bool,
+    },
+    CropTilted(f32),
+    CropReset,
+    GridToggled(bool),
+    CompareModeSelected(CompareMode),
+    CompareSplitMoved(f32),
+    ActualSizeToggled(bool),
+    FullImageLoaded(PathBuf, Result<Arc<DynamicImage>, ImageLoadError>),
+    AdjustmentsToggled,
+    FilmPresetSelected(FilmPreset),
+    AdjustmentChanged(Adjustment, f32),
+    AdjustmentReleased,
+    AdjustmentsReset,
+    MetadataToggled,
+    ExportMetadataSelected(ExportMetadata),
+    CaptionEdited(String),
+    CaptionSubmitted,
+    CaptionFontSelected(CaptionFont),
+    CaptionColorSelected(CaptionColor),
+    CaptionAlignmentSelected(CaptionAlignment),
+    CaptionSizeChanged(f32),
+    CaptionSizeReleased,
+    DateStampToggled(bool),
+    ExportFormatSelected(ExportFormat),
+    ExportDpiSelected(ExportDpi),
+    JpegQualityChanged(u8),
+    ExportRequested,
+    ExportProgressed(ExportStage),
+    /// The exported file, with notices of what had to be left out of it
+    ExportFinished(PathBuf, Vec<String>),
+    ExportFailed(String),
+    BatchNamePatternEdited(String),
+    BatchFolderRequested,
+    BatchFilesRequested,
+    BatchFileFinished(PathBuf, Result<FramedFile, String>),
+    BatchFinished,
+    BatchCancelled,
+    BatchReportDismissed,
+    SheetToggled,
+    SheetImageToggled,
+    SheetCleared,
+    SheetPaperSelected(PaperSize),
+    SheetMarginChanged(f32),
+    SheetGapChanged(f32),
+    SheetSettingsReleased,
+    SheetPageChanged(usize),
+    SheetPreviewRendered(u64, iced_image::Handle, Vec<(PathBuf, SheetThumbnail)>),
+    SheetExportRequested(SheetOutput),
+    CollageToggled,
+    CollageLayoutSelected(CollageLayout),
+    CollageSlotCountSelected(usize),
+    /// Pressing a slot selects it and starts dragging it
+    CollageSlotPressed(usize),
+    CollageSlotReleased(usize),
+    CollageSlotDragCancelled,
+    CollageSlotFilled,
+    CollageSlotPickRequested,
+    CollageSlotPicked(PathBuf),
+    CollageSlotCleared,
+    CollageImageLoaded(PathBuf, Result<SlotImage, ImageLoadError>),
+    FileHovered,
+    FilesHoveredLeft,
+    FileDropped(PathBuf),
+    NextDroppedImage,
+    NoticesDismissed,
+    RecentFilesToggled,
+    RecentFilePinToggled(PathBuf),
+    RecentFilesCleared,
+    FolderThumbnailLoaded(PathBuf, iced_image::Handle),
+    AdjacentImageRequested {
+        forward: bool,
+    },
+}
+
+fn default_name_pattern() -> String {
+    DEFAULT_NAME_PATTERN.to_owned()
+}
+
+fn default_max_pixels() -> u64 {
+    DEFAULT_MAX_PIXELS
+}
+
+fn default_compare_split() -> f32 {
+    0.5
+}
+
+impl InstaxFramer {
+    pub(crate) fn init() -> InstaxFramer {
+        Self {
+            show_grid: true,
+            compare_split: default_compare_split(),
+            batch_name_pattern: default_name_pattern(),
+            max_pixels: default_max_pixels(),
+            ..Default::default()
+        }
+    }
+
+    /// Crop of the image, or of the selected slot in collage mode
+    fn crop(&self) -> Crop {
+        if self.show_collage {
+            return self
+                .collage
+                .slots
+                .get(self.collage_slot)
+                .map(|slot| slot.crop)
+                .unwrap_or_default();
+        }
+
+        self.selected_file
+            .as_ref()
+            .and_then(|selected_file| self.crops.get(selected_file))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn crop_mut(&mut self) -> Option<&mut Crop> {
+        if self.show_collage {
+            return self
+                .collage
+                .slots
+                .get_mut(self.collage_slot)
+                .map(|slot| &mut slot.crop);
+        }
+
+        let selected_file = self.selected_file.clone()?;
+
+        Some(self.crops.entry(selected_file).or_default())
+    }
+
+    /// Sizes of the image the crop moves and of the area it fills in the preview
+    fn crop_sizes(&self) -> Option<((u32, u32), (u32, u32))> {
+        let picture_rect = self.film.layout().picture_px(PREVIEW_PIXELS_PER_MM);
+
+        if self.show_collage {
+            let slot_image = self
+                .collage
+                .slots
+                .get(self.collage_slot)
+                .and_then(|slot| slot.file.as_ref())
+                .and_then(|file| self.collage_images.get(file))?;
+            let slot_rect = *self
+                .collage
+                .slot_rects(
+                    (picture_rect.width, picture_rect.height),
+                    PREVIEW_PIXELS_PER_MM,
+                )
+                .get(self.collage_slot)?;
+
+            Some((
+                (slot_image.proxy.width(), slot_image.proxy.height()),
+                (slot_rect.width, slot_rect.height),
+            ))
+        } else {
+            let loaded_image = self.loaded_image.as_ref()?;
+
+            Some((
+                (loaded_image.proxy.width(), loaded_image.proxy.height()),
+                (picture_rect.width, picture_rect.height),
+            ))
+        }
+    }
+
+    /// The images filling the picture area, the collage's when in collage mode
+    fn picture(&self) -> Option<Picture<Arc<DynamicImage>>> {
+        if self.show_collage {
+            return Some(Picture::Collage(
+                self.collage.clone(),
+                self.collage
+                    .slots
+                    .iter()
+                    .map(|slot| {
+                        slot.file
+                            .as_ref()
+                            .and_then(|file| self.collage_images.get(file))
+                            .map(|slot_image| slot_image.proxy.clone())
+                    })
+                    .collect(),
+            ));
+        }
+
+        Some(Picture::Single(self.loaded_image.as_ref()?.proxy.clone()))
+    }
+
+    fn adjustments(&self) -> Adjustments {
+        self.selected_file
+            .as_ref()
+            .and_then(|selected_file| self.adjustments.get(selected_file))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn adjustments_mut(&mut self) -> Option<&mut Adjustments> {
+        let selected_file = self.selected_file.clone()?;
+
+        Some(self.adjustments.entry(selected_file).or_default())
+    }
+
+    fn caption(&self) -> &str {
+        self.selected_file
+            .as_ref()
+            .and_then(|selected_file| self.captions.get(selected_file))
+            .map_or("", String::as_str)
+    }
+
+    fn templates(&self) -> impl Iterator<Item = &Arc<FrameTemplate>> {
+        BUILT_IN_TEMPLATES.iter().chain(&self.user_templates)
+    }
+
+    fn selected_template(&self) -> Arc<FrameTemplate> {
+        self.template
+            .as_ref()
+            .and_then(|name| self.templates().find(|template| &template.name == name))
+            .unwrap_or(&BUILT_IN_TEMPLATES[0])
+            .clone()
+    }
+
+    fn framing(&self) -> Framing {
+        Framing {
+            film: self.film,
+            crop: self.crop(),
+            adjustments: self.adjustments(),
+            inscription: self.inscription(),
+            template: self.selected_template(),
+        }
+    }
+
+    /// Framing of any image with its own crop, look and caption, without a date stamp
+    fn framing_for(&self, file: &Path) -> Framing {
+        Framing {
+            film: self.film,
+            crop: self.crops.get(file).copied().unwrap_or_default(),
+            adjustments: self.adjustments.get(file).copied().unwrap_or_default(),
+            inscription: Inscription {
+                caption: self.captions.get(file).cloned().unwrap_or_default(),
+                style: self.caption_style,
+                date_stamp: None,
+            },
+            template: self.selected_template(),
+        }
+    }
+
+    fn sheet_job(&self) -> SheetJob {
+        SheetJob {
+            entries: self
+                .sheet_files
+                .iter()
+                .map(|file| (file.clone(), self.framing_for(file)))
+                .collect(),
+            date_stamp: self.date_stamp,
+            layout: SheetLayout::new(&self.sheet_settings, self.film),
+            max_pixels: self.max_pixels,
+        }
+    }
+
+    fn inscription(&self) -> Inscription {
+        Inscription {
+            caption: self.caption().to_owned(),
+            style: self.caption_style,
+            date_stamp: self
+                .loaded_image
+                .as_ref()
+                .and_then(|loaded_image| loaded_image.taken_on)
+                .filter(|_| self.date_stamp),
+        }
+    }
+
+    pub(crate) fn view(&self) -> Element<'_, InstaxFramerMessage> {
+        let content = column![self.framer_view()]
+            .push(self.notices_panel())
+            .spacing(8)
+            .align_x(Alignment::Center);
+
+        container(content)
+            .padding(4)
+            .style(|theme: &Theme| {
+                if !self.drop_hovered {
+                    return container::Style::default();
+                }
+
+                let primary = theme.extended_palette().primary;
+
+                container::Style {
+                    background: Some(primary.weak.color.scale_alpha(0.15).into()),
+                    border: Border {
+                        color: primary.strong.color,
+                        width: 2.0,
+                        radius: 8.0.into(),
+                    },
+                    ..container::Style::default()
+                }
+            })
+            .into()
+    }
+
+    fn framer_view(&self) -> Element<'_, InstaxFramerMessage> {
+        #[cfg(feature = "camera")]
+        if let Some(viewfinder) = &self.viewfinder {
+            return capture::viewfinder_view(viewfinder, self.photobooth);
+        }
+
+        match (&self.selected_file, &self.loaded_image) {
+            (None, _) => column![
+                button(text("Pick an image file")).on_press(InstaxFramerMessage::PickImage),
+                text("or drop one onto the window").size(12),
+            ]
+            .push(open_project_button())
+            .push(camera_button())
+            .push(self.recent_files_list())
+            .spacing(4)
+            .align_x(Alignment::Center)
+            .into(),
+            (Some(selected_file), None) => row![
+                text(SPINNER_FRAMES[self.spinner_frame % SPINNER_FRAMES.len()]).size(20),
+                text(format!(
+                    "Loading {}...",
+                    selected_file
+                        .file_name()
+                        .map_or(selected_file.to_string_lossy(), |file_name| file_name
+                            .to_string_lossy())
+                )),
+                button(text("Cancel")).style(button::danger).on_press_maybe(
+                    self.image_load
+                        .is_some()
+                        .then_some(InstaxFramerMessage::ImageLoadingCancelled)
+                ),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into(),
+            (Some(_), Some(_)) => column![
+                row![
+                    button(text("Pick another image")).on_press(InstaxFramerMessage::PickImage),
+                    pick_list(
+                        INSTAX_FILMS,
+                        Some(self.film),
+                        InstaxFramerMessage::FilmSelected
+                    ),
+                    pick_list(
+                        self.templates()
+                            .map(|template| template.name.clone())
+                            .collect::<Vec<_>>(),
+                        Some(self.selected_template().name.clone()),
+                        InstaxFramerMessage::TemplateSelected
+                    ),
+                ]
+                .push(reload_templates_button())
+                .push(camera_button())
+                .push(
+                    row![
+                        button(text("ⓘ Info"))
+                            .style(if self.show_metadata {
+                                button::primary
+                            } else {
+                                button::secondary
+                            })
+                            .on_press(InstaxFramerMessage::MetadataToggled),
+                        button(text("◐ Adjust"))
+                            .style(if self.show_adjustments {
+                                button::primary
+                            } else {
+                                button::secondary
+                            })
+                            .on_press(InstaxFramerMessage::AdjustmentsToggled),
+                        button(text("🕘 Recent"))
+                            .style(if self.show_recent_files {
+                                button::primary
+                            } else {
+                                button::secondary
+                            })
+                            .on_press(InstaxFramerMessage::RecentFilesToggled),
+                        button(text("▦ Sheet"))
+                            .style(if self.show_sheet {
+                                button::primary
+                            } else {
+                                button::secondary
+                            })
+                            .on_press(InstaxFramerMessage::SheetToggled),
+                        button(text("⊞ Collage"))
+                            .style(if self.show_collage {
+                                button::primary
+                            } else {
+                                button::secondary
+                            })
+                            .on_press(InstaxFramerMessage::CollageToggled),
+                    ]
+                    .spacing(8)
+                )
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .wrap(),
+            ]
+            .push(
+                self.show_recent_files
+                    .then(|| self.recent_files_list())
+                    .flatten(),
+            )
+            .push(self.folder_strip())
+            .push(self.metadata_panel())
+            .push(if self.show_sheet {
+                self.sheet_area()
+            } else {
+                column![
+                    self.preview_area(),
+                    self.crop_controls(),
+                    self.compare_controls()
+                ]
+                .push(self.show_collage.then(|| self.collage_controls()))
+                .spacing(8)
+                .align_x(Alignment::Center)
+                .into()
+            })
+            .push(self.adjustment_controls())
+            .push(self.caption_controls())
+            .push(self.export_controls())
+            .push(self.batch_controls())
+            .spacing(8)
+            .align_x(Alignment::Center)
+            .into(),
+        }
+    }
+
+    fn notices_panel(&self) -> Option<Element<'_, InstaxFramerMessage>> {
+        if self.drop_queue.is_empty() && self.notices.is_empty() {
+            return None;
+        }
+
+        let next = (!self.drop_queue.is_empty()).then(|| {
+            button(text(format!(
+                "Next dropped image ({} left)",
+                self.drop_queue.len()
+            )))
+            .on_press(InstaxFramerMessage::NextDroppedImage)
+        });
+        let notices = (!self.notices.is_empty()).then(|| {
+            column(
+                self.notices
+                    .iter()
+                    .map(|notice| text(notice).size(12).into()),
+            )
+            .push(
+                button(text("Dismiss"))
+                    .style(button::secondary)
+                    .on_press(InstaxFramerMessage::NoticesDismissed),
+            )
+            .spacing(2)
+            .align_x(Alignment::Center)
+        });
+
+        Some(column![].push(next).push(notices).spacing(4).into())
+    }
+
+    /// Forgets the current and recent files that no longer exist, e.g. since the last session
+    #[cfg(feature = "config")]
+    pub(crate) fn prune_missing_files(&mut self) {
+        self.recent_files
+            .retain(|recent_file| recent_file.path.is_file());
+
+        if self
+            .selected_file
+            .as_ref()
+            .is_some_and(|selected_file| !selected_file.is_file())
+        {
+            self.selected_file = None;
+        }
+    }
+
+    fn preview_area(&self) -> Element<'_, InstaxFramerMessage> {
+        let Some(preview) = &self.preview else {
+            return text("Framing...").into();
+        };
+
+        if self.actual_size && !self.full_loading.is_empty() {
+            return text("Loading the full resolution...").into();
+        }
+        // NOTE: cropping by dragging is left to the fitted framed view, where it maps cleanly
+        if self.actual_size {
+            return scrollable(iced_image(preview.clone()).filter_method(FilterMethod::Nearest))
+                .direction(scrollable::Direction::Both {
+                    vertical: scrollable::Scrollbar::default(),
+                    horizontal: scrollable::Scrollbar::default(),
+                })
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into();
+        }
+        if self.compare_mode == CompareMode::Original {
+            return iced_image(preview.clone())
+                .content_fit(iced::ContentFit::Contain)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into();
+        }
+        let (frame_width, frame_height) = self.film.layout().size_px(PREVIEW_PIXELS_PER_MM);
+
+        responsive(move |size| {
+            // NOTE: map cursor positions to preview pixels, the unit Crop::pan_by works in
+            let scale = (size.width / frame_width as f32).min(size.height / frame_height as f32);
+
+            mouse_area(
+                iced_image(preview.clone())
+                    .content_fit(iced::ContentFit::Contain)
+                    .width(Length::Fill)
+                    .height(Length::Fill),
+            )
+            .on_press(InstaxFramerMessage::CropDragStarted)
+            .on_release(InstaxFramerMessage::CropDragEnded)
+            .on_exit(InstaxFramerMessage::CropDragEnded)
+            .on_move(move |position| {
+                InstaxFramerMessage::CropCursorMoved(Point::new(
+                    position.x / scale,
+                    position.y / scale,
+                ))
+            })
+            .on_scroll(|delta| {
+                let steps = match delta {
+                    ScrollDelta::Lines { y, .. } => y,
+                    ScrollDelta::Pixels { y, .. } => y / 40.0,
+                };
+
+                InstaxFramerMessage::CropZoomed(ZOOM_STEP.powf(steps))
+            })
+            .interaction(if self.is_dragging {
+                mouse::Interaction::Grabbing
+            } else {
+                mouse::Interaction::Grab
+            })
+            .into()
+        })
+        .into()
+    }
+
+    fn compose_preview(&mut self) -> Task<InstaxFramerMessage> {
+        let render_sheet = self.render_sheet();
+        let (Some(loaded_image), Some(picture)) = (&self.loaded_image, self.picture()) else {
+            return render_sheet;
+        };
+        // NOTE: the whole image of the selected slot, or the opened image while it is empty
+        let original = self
+            .collage
+            .slots
+            .get(self.collage_slot)
+            .and_then(|slot| slot.file.as_ref())
+            .and_then(|file| self.collage_images.get(file))
+            .filter(|_| self.show_collage)
+            .map_or_else(
+                || loaded_image.proxy.clone(),
+                |slot_image| slot_image.proxy.clone(),
+            );
+        let framing = self.framing();
+        let show_grid =
+            self.show_grid && !self.show_collage && self.compare_mode != CompareMode::Original;
+        let compare_mode = self.compare_mode;
+        let compare_split = self.compare_split;
+        let (picture, original, pixels_per_mm) = if self.actual_size {
+            let load_full_images = self.load_full_images();
+            let Some((picture, original)) = self.full_picture() else {
+                return Task::batch([load_full_images, render_sheet]);
+            };
+
+            (picture, original, self.export_settings.dpi.pixels_per_mm())
+        } else {
+            (picture, original, PREVIEW_PIXELS_PER_MM)
+        };
+
+        self.preview_generation += 1;
+        let generation = self.preview_generation;
+
+        let compose_preview = Task::future(async move {
+            let compose_result = tokio::task::spawn_blocking(move || {
+                let mut framed_image = compare::render(
+                    &picture,
+                    &original,
+                    &framing,
+                    pixels_per_mm,
+                    compare_mode,
+                    compare_split,
+                );
+
+                if show_grid {
+                    frame::draw_thirds(
+                        &mut framed_image,
+                        framing.film.layout().picture_px(pixels_per_mm),
+                    );
+                }
+
+                framed_image
+            })
+            .await;
+
+            match compose_result {
+                Ok(framed_image) => Some(InstaxFramerMessage::PreviewComposed(
+                    generation,
+                    iced_image::Handle::from_rgba(
+                        framed_image.width(),
+                        framed_image.height(),
+                        framed_image.into_raw(),
+                    ),
+                )),
+                Err(e) => {
+                    eprintln!("Blocking preview composing task panicked or was cancelled: {e:?}");
+                    None
+                }
+            }
+        })
+        .and_then(Task::done);
+
+        Task::batch([compose_preview, render_sheet])
+    }
+
+    pub(crate) fn update(&mut self, message: InstaxFramerMessage) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::PickImage => {
+                let file = FileDialog::new()
+                    .add_filter("image", IMAGE_EXTENSIONS)
+                    .pick_file();
+
+                if let Some(file) = file {
+                    return Task::done(InstaxFramerMessage::ImagePicked(file));
+                }
+            }
+            InstaxFramerMessage::ImagePicked(selected_file) => {
+                if !selected_file.is_file() {
+                    let recent_files_count = self.recent_files.len();
+                    self.recent_files
+                        .retain(|recent_file| recent_file.path != selected_file);
+
+                    // NOTE: dropped files and `--open` paths were never in the recent files
+                    self.notices
+                        .push(if self.recent_files.len() < recent_files_count {
+                            format!(
+                                "{} no longer exists, it was removed from the recent files",
+                                selected_file.to_string_lossy()
+                            )
+                        } else {
+                            format!("{} does not exist", selected_file.to_string_lossy())
+                        });
+
+                    return Task::none();
+                }
+
+                #[cfg(feature = "config")]
+                if project::is_project(&selected_file) {
+                    self.opening_project = true;
+
+                    return Task::future(async move {
+                        let file = selected_file.clone();
+                        let opened =
+                            tokio::task::spawn_blocking(move || Project::open(&selected_file))
+                                .await
+                                .unwrap_or_else(|e| Err(e.to_string()));
+
+                        InstaxFramerMessage::ProjectOpened(file, opened)
+                    });
+                }
+
+                // NOTE: a load still running is replaced, it keeps the image shown before it
+                let previous = match self.image_load.take() {
+                    Some(image_load) => {
+                        image_load.handle.abort();
+                        image_load.previous
+                    }
+                    None => self.selected_file.clone().zip(self.loaded_image.take()),
+                };
+
+                browser::remember(&mut self.recent_files, &selected_file);
+                self.selected_file = Some(selected_file.clone());
+                self.loaded_image = None;
+                self.preview = None;
+
+                self.load_generation += 1;
+                let generation = self.load_generation;
+                let max_pixels = self.max_pixels;
+
+                let (task, handle) = Task::future(async move {
+                    let image_load_result = tokio::task::spawn_blocking(move || {
+                        LoadedImage::open(&selected_file, max_pixels)
+                    })
+                    .await;
+
+                    match image_load_result {
+                        Ok(Ok(image)) => {
+                            InstaxFramerMessage::ImageLoadingFinished(generation, image)
+                        }
+                        Ok(Err(e)) => InstaxFramerMessage::ImageLoadingFailed(generation, e),
+                        Err(e) => {
+                            eprintln!(
+                                "Blocking image loading task panicked or was cancelled: {e:?}"
+                            );
+                            InstaxFramerMessage::ImageLoadingFailed(
+                                generation,
+                                ImageLoadError::Io(e.to_string()),
+                            )
+                        }
+                    }
+                })
+                .abortable();
+
+                self.image_load = Some(ImageLoad {
+                    generation,
+                    handle,
+                    previous,
+                });
+
+                return task;
+            }
+            InstaxFramerMessage::ImageLoadingCancelled => {
+                if let Some(image_load) = self.image_load.take() {
+                    image_load.handle.abort();
+
+                    return self.show_previous(image_load.previous);
+                }
+            }
+            InstaxFramerMessage::LoadingSpinnerTicked => {
+                self.spinner_frame = self.spinner_frame.wrapping_add(1);
+            }
+            InstaxFramerMessage::ImageLoadingFailed(generation, error) => {
+                let Some(image_load) = self
+                    .image_load
+                    .take_if(|image_load| image_load.generation == generation)
+                else {
+                    return Task::none();
+                };
+
+                let _ = MessageDialog::new()
+                    .set_title("Image loading failed...!")
+                    .set_description(format!(
+                        "Failed to load image at {}\n\n{error}",
+                        self.selected_file
+                            .as_ref()
+                            .map(|path| path.to_string_lossy())
+                            .unwrap_or(Cow::Borrowed("Unknown")),
+                    ))
+                    .set_level(MessageLevel::Error)
+                    .show();
+
+                if let Some(selected_file) = self.selected_file.take() {
+                    self.recent_files
+                        .retain(|recent_file| recent_file.path != selected_file);
+                }
+
+                let show_previous = self.show_previous(image_load.previous);
+
+                return Task::batch([show_previous, self.open_next_dropped()]);
+            }
+            InstaxFramerMessage::ImageLoadingFinished(generation, loaded_image) => {
+                if self
+                    .image_load
+                    .take_if(|image_load| image_load.generation == generation)
+                    .is_none()
+                {
+                    return Task::none();
+                }
+
+                self.loaded_image = Some(loaded_image);
+
+                // NOTE: only edits made from here on are worth autosaving
+                #[cfg(feature = "config")]
+                {
+                    self.autosaved_hash = self.project_hash().map(|(hash, _)| hash);
+                }
+
+                let browse_folder = match self.selected_file.clone() {
+                    Some(selected_file) => self.browse_folder(&selected_file),
+                    None => Task::none(),
+                };
+                let load_collage_images = if self.show_collage {
+                    self.load_collage_images()
+                } else {
+                    Task::none()
+                };
+
+                return Task::batch([self.compose_preview(), browse_folder, load_collage_images]);
+            }
+            InstaxFramerMessage::FilmSelected(film) => {
+                self.film = film;
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::TemplateSelected(name) => {
+                self.template = Some(name);
+
+                return self.compose_preview();
+            }
+            InstaxFramerMessage::PreviewComposed(generation, preview) => {
+                if generation == self.preview_generation {
+                    self.preview = Some(preview);
+                }
+            }
+            InstaxFramerMessage::FileHovered => self.drop_hovered = true,
+            InstaxFramerMessage::FilesHoveredLeft => self.drop_hovered = false,
+            InstaxFramerMessage::FileDropped(path) => {
+                self.drop_hovered = false;
+
+                if !can_open(&path) {
+                    let file_name = path
+                        .file_name()
+                        .map_or(path.to_string_lossy(), |file_name| {
+                            file_name.to_string_lossy()
+                        });
+
+                    self.notices.push(if path.is_dir() {
+                        format!("{file_name} is a folder, use \"Frame folder...\" to frame all of its images")
+                    } else {
+                        format!(
+                            "{file_name} was not opened, only {} images are supported",
+                            IMAGE_EXTENSIONS.join("/")
+                        )
+                    });
+                } else if self.is_opening() || !self.drop_queue.is_empty() {
+                    self.drop_queue.push_back(path);
+                } else {
+                    // NOTE: files of a multi-file drop arrive one message each, the load must
+                    // start right away for the following ones to queue behind it
+                    return self.update(InstaxFramerMessage::ImagePicked(path));
+                }
+            }
+            InstaxFramerMessage::NextDroppedImage => {
+                if let Some(path) = self.drop_queue.pop_front() {
+                    return self.update(InstaxFramerMessage::ImagePicked(path));
+                }
+            }
+            InstaxFramerMessage::NoticesDismissed => self.notices.clear(),
+            message @ (InstaxFramerMessage::CropDragStarted
+            | InstaxFramerMessage::CropCursorMoved(..)
+            | InstaxFramerMessage::CropDragEnded
+            | InstaxFramerMessage::CropZoomed(..)
+            | InstaxFramerMessage::CropTurned { .. }
+            | InstaxFramerMessage::CropTilted(..)
+            | InstaxFramerMessage::CropReset) => return self.update_crop(message),
+            message @ (InstaxFramerMessage::CompareModeSelected(..)
+            | InstaxFramerMessage::CompareSplitMoved(..)
+            | InstaxFramerMessage::ActualSizeToggled(..)
+            | InstaxFramerMessage::FullImageLoaded(..)
+            | InstaxFramerMessage::GridToggled(..)) => return self.update_compare(message),
+            InstaxFramerMessage::MetadataToggled => self.show_metadata = !self.show_metadata,
+            message @ (InstaxFramerMessage::AdjustmentsToggled
+            | InstaxFramerMessage::FilmPresetSelected(..)
+            | InstaxFramerMessage::AdjustmentChanged(..)
+            | InstaxFramerMessage::AdjustmentReleased
+            | InstaxFramerMessage::AdjustmentsReset) => return self.update_adjustments(message),
+            message @ (InstaxFramerMessage::CaptionEdited(..)
+            | InstaxFramerMessage::CaptionSubmitted
+            | InstaxFramerMessage::CaptionSizeReleased
+            | InstaxFramerMessage::CaptionFontSelected(..)
+            | InstaxFramerMessage::CaptionColorSelected(..)
+            | InstaxFramerMessage::CaptionAlignmentSelected(..)
+            | InstaxFramerMessage::CaptionSizeChanged(..)
+            | InstaxFramerMessage::DateStampToggled(..)) => {
+                return self.update_inscription(message);
+            }
+            message @ (InstaxFramerMessage::ExportFormatSelected(..)
+            | InstaxFramerMessage::ExportDpiSelected(..)
+            | InstaxFramerMessage::ExportMetadataSelected(..)
+            | InstaxFramerMessage::JpegQualityChanged(..)
+            | InstaxFramerMessage::ExportRequested
+            | InstaxFramerMessage::ExportProgressed(..)
+            | InstaxFramerMessage::ExportFinished(..)
+            | InstaxFramerMessage::ExportFailed(..)) => return self.update_export(message),
+            message @ (InstaxFramerMessage::BatchNamePatternEdited(..)
+            | InstaxFramerMessage::BatchFolderRequested
+            | InstaxFramerMessage::BatchFilesRequested
+            | InstaxFramerMessage::BatchFileFinished(..)
+            | InstaxFramerMessage::BatchFinished
+            | InstaxFramerMessage::BatchCancelled
+            | InstaxFramerMessage::BatchReportDismissed) => return self.update_batch(message),
+            message @ (InstaxFramerMessage::CollageToggled
+            | InstaxFramerMessage::CollageLayoutSelected(..)
+            | InstaxFramerMessage::CollageSlotCountSelected(..)
+            | InstaxFramerMessage::CollageSlotPressed(..)
+            | InstaxFramerMessage::CollageSlotReleased(..)
+            | InstaxFramerMessage::CollageSlotDragCancelled
+            | InstaxFramerMessage::CollageSlotFilled
+            | InstaxFramerMessage::CollageSlotPickRequested
+            | InstaxFramerMessage::CollageSlotPicked(..)
+            | InstaxFramerMessage::CollageSlotCleared
+            | InstaxFramerMessage::CollageImageLoaded(..)) => return self.update_collage(message),
+            message @ (InstaxFramerMessage::SheetToggled
+            | InstaxFramerMessage::SheetImageToggled
+            | InstaxFramerMessage::SheetCleared
+            | InstaxFramerMessage::SheetPaperSelected(..)
+            | InstaxFramerMessage::SheetMarginChanged(..)
+            | InstaxFramerMessage::SheetGapChanged(..)
+            | InstaxFramerMessage::SheetSettingsReleased
+            | InstaxFramerMessage::SheetPageChanged(..)
+            | InstaxFramerMessage::SheetPreviewRendered(..)
+            | InstaxFramerMessage::SheetExportRequested(..)) => return self.update_sheet(message),
+            message @ (InstaxFramerMessage::RecentFilesToggled
+            | InstaxFramerMessage::RecentFilePinToggled(..)
+            | InstaxFramerMessage::RecentFilesCleared
+            | InstaxFramerMessage::FolderThumbnailLoaded(..)
+            | InstaxFramerMessage::AdjacentImageRequested { .. }) => {
+                return self.update_browser(message);
+            }
+            #[cfg(feature = "config")]
+            message @ (InstaxFramerMessage::ProjectOpenRequested
+            | InstaxFramerMessage::ProjectOpened(..)
+            | InstaxFramerMessage::ProjectSaveRequested
+            | InstaxFramerMessage::ProjectSaved(..)
+            | InstaxFramerMessage::EmbedInProjectToggled(..)
+            | InstaxFramerMessage::AutosaveTicked
+            | InstaxFramerMessage::AutosaveRecoveryChecked) => return self.update_project(message),
+            #[cfg(feature = "camera")]
+            message @ (InstaxFramerMessage::ViewfinderOpened
+            | InstaxFramerMessage::ViewfinderSourcesListed(..)
+            | InstaxFramerMessage::ViewfinderSourceSelected(..)
+            | InstaxFramerMessage::ViewfinderFolderRequested
+            | InstaxFramerMessage::ViewfinderFrame(..)
+            | InstaxFramerMessage::ShotTaken
+            | InstaxFramerMessage::ShotSaved(..)
+            | InstaxFramerMessage::ViewfinderClosed) => return self.update_viewfinder(message),
+            #[cfg(feature = "camera")]
+            message @ (InstaxFramerMessage::PhotoboothCountdownSelected(..)
+            | InstaxFramerMessage::PhotoboothShotsSelected(..)
+            | InstaxFramerMessage::PhotoboothIntervalSelected(..)
+            | InstaxFramerMessage::PhotoboothStarted
+            | InstaxFramerMessage::PhotoboothStopped
+            | InstaxFramerMessage::PhotoboothTicked(..)
+            | InstaxFramerMessage::PhotoboothShotFramed(..)) => {
+                return self.update_photobooth(message);
+            }
+            #[cfg(feature = "config")]
+            message @ (InstaxFramerMessage::TemplatesReloadRequested
+            | InstaxFramerMessage::TemplatesLoaded(..)) => return self.update_templates(message),
+        }
+
+        Task::none()
+    }
+
+    /// An image or project is being opened
+    fn is_opening(&self) -> bool {
+        #[cfg(feature = "config")]
+        if self.opening_project {
+            return true;
+        }
+
+        self.image_load.is_some()
+    }
+
+    /// Opens the next dropped image waiting in the queue, if any
+    fn open_next_dropped(&mut self) -> Task<InstaxFramerMessage> {
+        if self.is_opening() {
+            return Task::none();
+        }
+
+        match self.drop_queue.pop_front() {
+            Some(path) => self.update(InstaxFramerMessage::ImagePicked(path)),
+            None => Task::none(),
+        }
+    }
+
+    /// Goes back to the image shown before a load that did not finish
+    fn show_previous(
+        &mut self,
+        previous: Option<(PathBuf, LoadedImage)>,
+    ) -> Task<InstaxFramerMessage> {
+        let Some((file, loaded_image)) = previous else {
+            self.selected_file = None;
+
+            return Task::none();
+        };
+
+        self.selected_file = Some(file);
+        self.loaded_image = Some(loaded_image);
+
+        self.compose_preview()
+    }
+
+    fn project_controls(&self) -> Option<Element<'_, InstaxFramerMessage>> {
+        #[cfg(feature = "config")]
+        {
+            Some(
+                row![
+                    button(text("Save project..."))
+                        .on_press(InstaxFramerMessage::ProjectSaveRequested),
+                    checkbox(self.embed_in_project)
+                        .label("Embed images")
+                        .on_toggle(InstaxFramerMessage::EmbedInProjectToggled),
+                ]
+                .push(open_project_button())
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .into(),
+            )
+        }
+        #[cfg(not(feature = "config"))]
+        {
+            None
+        }
+    }
+
+    pub(crate) fn subscription(&self) -> Subscription<InstaxFramerMessage> {
+        let spinner = if self.image_load.is_some() {
+            time::every(Duration::from_millis(120))
+                .map(|_| InstaxFramerMessage::LoadingSpinnerTicked)
+        } else {
+            Subscription::none()
+        };
+
+        let events = iced::event::listen_with(|event, status, _window| match event {
+            // NOTE: arrows typed into a text input are not meant for the browser
+            iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                key: keyboard::Key::Named(named @ (key::Named::ArrowLeft | key::Named::ArrowRight)),
+                ..
+            }) if status == event::Status::Ignored => {
+                Some(InstaxFramerMessage::AdjacentImageRequested {
+                    forward: named == key::Named::ArrowRight,
+                })
+            }
+            iced::Event::Window(iced::window::Event::FileHovered(_)) => {
+                Some(InstaxFramerMessage::FileHovered)
+            }
+            iced::Event::Window(iced::window::Event::FilesHoveredLeft) => {
+                Some(InstaxFramerMessage::FilesHoveredLeft)
+            }
+            iced::Event::Window(iced::window::Event::FileDropped(path)) => {
+                Some(InstaxFramerMessage::FileDropped(path))
+            }
+            _ => None,
+        });
+
+        #[cfg(feature = "config")]
+        let autosave = time::every(AUTOSAVE_INTERVAL).map(|_| InstaxFramerMessage::AutosaveTicked);
+        #[cfg(not(feature = "config"))]
+        let autosave = Subscription::none();
+
+        #[cfg(feature = "camera")]
+        let viewfinder = match &self.viewfinder {
+            Some(viewfinder) => Subscription::batch([
+                viewfinder
+                    .source
+                    .clone()
+                    .map_or_else(Subscription::none, |source| {
+                        Subscription::run_with(source, capture::frames)
+                            .map(InstaxFramerMessage::ViewfinderFrame)
+                    }),
+                if viewfinder
+                    .photobooth
+                    .as_ref()
+                    .is_some_and(PhotoboothRun::is_capturing)
+                {
+                    time::every(photobooth::TICK).map(InstaxFramerMessage::PhotoboothTicked)
+                } else {
+                    Subscription::none()
+                },
+            ]),
+            None => Subscription::none(),
+        };
+        #[cfg(not(feature = "camera"))]
+        let viewfinder = Subscription::none();
+
+        Subscription::batch([events, spinner, autosave, viewfinder])
+    }
+
+    pub(crate) fn start_up_tasks(&self) -> Task<InstaxFramerMessage> {
+        let load_image = if let Some(selected_file) = &self.selected_file {
+            Task::done(InstaxFramerMessage::ImagePicked(selected_file.to_owned()))
+        } else {
+            Task::none()
+        };
+
+        #[cfg(feature = "config")]
+        {
+            Task::batch([load_image, templates::load_templates_task()])
+        }
+        #[cfg(not(feature = "config"))]
+        {
+            load_image
+        }
+    }
+}
+
+fn is_supported_image(path: &Path) -> bool {
+    path.is_file()
+        && path.extension().is_some_and(|extension| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|supported| extension.eq_ignore_ascii_case(supported))
+        })
+}
+
+/// Images, and project files with the config feature
+fn can_open(path: &Path) -> bool {
+    #[cfg(feature = "config")]
+    if project::is_project(path) {
+        return path.is_file();
+    }
+
+    is_supported_image(path)
+}
+
+fn open_project_button<'a>() -> Option<Element<'a, InstaxFramerMessage>> {
+    #[cfg(feature = "config")]
+    {
+        Some(
+            button(text("Open project..."))
+                .style(button::secondary)
+                .on_press(InstaxFramerMessage::ProjectOpenRequested)
+                .into(),
+        )
+    }
+    #[cfg(not(feature = "config"))]
+    {
+        None
+    }
+}
+
+fn camera_button<'a>() -> Option<Element<'a, InstaxFramerMessage>> {
+    #[cfg(feature = "camera")]
+    {
+        Some(
+            button(text("📷 Camera"))
+                .style(button::secondary)
+                .on_press(InstaxFramerMessage::ViewfinderOpened)
+                .into(),
+        )
+    }
+    #[cfg(not(feature = "camera"))]
+    {
+        None
+    }
+}
+
+fn reload_templates_button<'a>() -> Option<Element<'a, InstaxFramerMessage>> {
+    #[cfg(feature = "config")]
+    {
+        Some(
+            button(text("↻"))
+                .style(button::secondary)
+                .on_press(InstaxFramerMessage::TemplatesReloadRequested)
+                .into(),
+        )
+    }
+    #[cfg(not(feature = "config"))]
+    {
+        None
+    }
+}
@@ -0,0 +1,422 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use iced::{
+    Alignment, Element, Task,
+    widget::{button, column, pick_list, row, text},
+};
+use image::{DynamicImage, RgbaImage};
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Adjustments, Crop, ExportSettings, Framing, Inscription, InstaxFramer, InstaxFramerMessage,
+    StampDate, Viewfinder, capture, export, frame,
+};
+
+/// Seconds counted down before each shot, none takes it right away
+pub static COUNTDOWNS: &[u32] = &[0, 3, 5, 10];
+pub static BURST_SIZES: &[u32] = &[1, 2, 3, 4, 6, 8];
+/// Seconds between the shots of a burst
+pub static BURST_INTERVALS: &[u32] = &[1, 2, 3, 5];
+/// How often a running photobooth checks for a due shot, also pacing the countdown overlay
+pub const TICK: Duration = Duration::from_millis(100);
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PhotoboothSettings {
+    pub countdown: u32,
+    pub shots: u32,
+    pub interval: u32,
+}
+
+impl Default for PhotoboothSettings {
+    fn default() -> Self {
+        Self {
+            countdown: 3,
+            shots: 4,
+            interval: 3,
+        }
+    }
+}
+
+/// A burst of shots, each framed and exported to the session folder as soon as it is taken.
+/// Timing only depends on the instants passed in, so any clock drives it
+#[derive(Debug, Clone)]
+pub struct PhotoboothRun {
+    pub settings: PhotoboothSettings,
+    pub session_dir: PathBuf,
+    started: Instant,
+    /// Shots taken so far, framed or not
+    pub taken: u32,
+    pub framed: Vec<PathBuf>,
+    pub errors: Vec<String>,
+}
+
+impl PhotoboothRun {
+    pub fn new(settings: PhotoboothSettings, session_dir: PathBuf, now: Instant) -> PhotoboothRun {
+        Self {
+            settings,
+            session_dir,
+            started: now,
+            taken: 0,
+            framed: vec![],
+            errors: vec![],
+        }
+    }
+
+    /// The first shot after the countdown, every following one an interval later
+    fn next_shot_at(&self) -> Option<Instant> {
+        (self.taken < self.settings.shots).then(|| {
+            self.started
+                + Duration::from_secs(self.settings.countdown.into())
+                + Duration::from_secs(self.settings.interval.into()) * self.taken
+        })
+    }
+
+    /// Whole seconds left until the next shot, rounded up, `None` when it is due or all are taken
+    pub fn seconds_left(&self, now: Instant) -> Option<u32> {
+        let left = self.next_shot_at()?.checked_duration_since(now)?;
+
+        Some(left.as_secs_f32().ceil() as u32).filter(|&seconds| seconds > 0)
+    }
+
+    /// Counts the next shot as taken once it is due, returning its number counted from 1
+    pub fn take_due(&mut self, now: Instant) -> Option<u32> {
+        if self.next_shot_at()? > now {
+            return None;
+        }
+
+        self.taken += 1;
+
+        Some(self.taken)
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.taken < self.settings.shots
+    }
+
+    /// Skips the shots not taken yet, those taken are still framed
+    pub fn stop(&mut self) {
+        self.settings.shots = self.taken;
+    }
+
+    /// Every shot is taken and framed, or failed to be
+    pub fn is_finished(&self) -> bool {
+        !self.is_capturing() && self.framed.len() + self.errors.len() >= self.taken as usize
+    }
+}
+
+/// A new folder in the captures directory, named after the time the session starts
+pub fn session_dir(now: SystemTime) -> PathBuf {
+    let millis = now
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis());
+
+    capture::captures_dir().join(format!("session-{millis}"))
+}
+
+/// Keeps the shot as taken and exports it framed next to it, returning the framed file
+pub fn frame_shot(
+    shot: &RgbaImage,
+    number: u32,
+    session_dir: &Path,
+    framing: &Framing,
+    settings: &ExportSettings,
+) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(session_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", session_dir.display()))?;
+
+    shot.save(session_dir.join(format!("shot-{number:02}.png")))
+        .map_err(|e| format!("Failed to save shot {number}: {e}"))?;
+
+    let framed_image = frame::compose(
+        &DynamicImage::ImageRgba8(shot.clone()),
+        framing,
+        settings.dpi.pixels_per_mm(),
+    );
+    let encoded = export::encode(framed_image, settings, None)
+        .map_err(|e| format!("Failed to encode shot {number}: {e}"))?;
+
+    let destination = session_dir.join(format!(
+        "shot-{number:02}_instax.{}",
+        settings.format.extension()
+    ));
+    std::fs::write(&destination, encoded.bytes)
+        .map_err(|e| format!("Failed to write shot {number}: {e}"))?;
+
+    Ok(destination)
+}
+
+impl InstaxFramer {
+    /// Handles the photobooth messages
+    pub(super) fn update_photobooth(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::PhotoboothCountdownSelected(countdown) => {
+                self.photobooth.countdown = countdown;
+            }
+            InstaxFramerMessage::PhotoboothShotsSelected(shots) => self.photobooth.shots = shots,
+            InstaxFramerMessage::PhotoboothIntervalSelected(interval) => {
+                self.photobooth.interval = interval;
+            }
+            InstaxFramerMessage::PhotoboothStarted => {
+                if let Some(viewfinder) = &mut self.viewfinder {
+                    viewfinder.photobooth = Some(PhotoboothRun::new(
+                        self.photobooth,
+                        session_dir(std::time::SystemTime::now()),
+                        Instant::now(),
+                    ));
+                }
+            }
+            InstaxFramerMessage::PhotoboothStopped => {
+                if let Some(run) = self
+                    .viewfinder
+                    .as_mut()
+                    .and_then(|viewfinder| viewfinder.photobooth.as_mut())
+                {
+                    run.stop();
+                }
+            }
+            InstaxFramerMessage::PhotoboothTicked(now) => {
+                let Some(viewfinder) = &mut self.viewfinder else {
+                    return Task::none();
+                };
+                let Some(run) = &mut viewfinder.photobooth else {
+                    return Task::none();
+                };
+                let Some(number) = run.take_due(now) else {
+                    return Task::none();
+                };
+                let Some(frame) = viewfinder.frame.clone() else {
+                    run.errors
+                        .push(format!("Shot {number}: no frame from the source"));
+
+                    return Task::none();
+                };
+                let session_dir = run.session_dir.clone();
+
+                // NOTE: every shot is new, framed as it comes without a crop or adjustments
+                let framing = Framing {
+                    film: self.film,
+                    crop: Crop::default(),
+                    adjustments: Adjustments::default(),
+                    inscription: Inscription {
+                        caption: String::new(),
+                        style: self.caption_style,
+                        date_stamp: self
+                            .date_stamp
+                            .then(|| StampDate::from_system_time(std::time::SystemTime::now()))
+                            .flatten(),
+                    },
+                    template: self.selected_template(),
+                };
+                let settings = self.export_settings.clone();
+
+                return Task::future(async move {
+                    let framed = tokio::task::spawn_blocking(move || {
+                        frame_shot(&frame.image, number, &session_dir, &framing, &settings)
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()));
+
+                    InstaxFramerMessage::PhotoboothShotFramed(framed)
+                });
+            }
+            InstaxFramerMessage::PhotoboothShotFramed(framed) => {
+                if let Some(run) = self
+                    .viewfinder
+                    .as_mut()
+                    .and_then(|viewfinder| viewfinder.photobooth.as_mut())
+                {
+                    match framed {
+                        Ok(file) => run.framed.push(file),
+                        Err(e) => run.errors.push(e),
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+}
+
+/// Burst settings and progress, then where the session's shots went
+pub(super) fn photobooth_controls(
+    viewfinder: &Viewfinder,
+    settings: PhotoboothSettings,
+) -> Element<'_, InstaxFramerMessage> {
+    let run = viewfinder.photobooth.as_ref();
+    let capturing = run.is_some_and(PhotoboothRun::is_capturing);
+
+    let controls = row![
+        text("Photobooth"),
+        text("Countdown (s)").size(12),
+        pick_list(
+            COUNTDOWNS,
+            Some(settings.countdown),
+            InstaxFramerMessage::PhotoboothCountdownSelected
+        ),
+        text("Shots").size(12),
+        pick_list(
+            BURST_SIZES,
+            Some(settings.shots),
+            InstaxFramerMessage::PhotoboothShotsSelected
+        ),
+        text("Every (s)").size(12),
+        pick_list(
+            BURST_INTERVALS,
+            Some(settings.interval),
+            InstaxFramerMessage::PhotoboothIntervalSelected
+        ),
+        if capturing {
+            button(text("Stop"))
+                .style(button::danger)
+                .on_press(InstaxFramerMessage::PhotoboothStopped)
+        } else {
+            button(text("▶ Start")).on_press_maybe(
+                (viewfinder.frame.is_some() && !viewfinder.saving)
+                    .then_some(InstaxFramerMessage::PhotoboothStarted),
+            )
+        },
+    ]
+    .spacing(8)
+    .align_y(Alignment::Center)
+    .wrap();
+
+    let status = run.map(|run| {
+        let summary = if run.is_capturing() {
+            format!("Shot {} of {}", run.taken + 1, run.settings.shots)
+        } else if run.is_finished() {
+            format!(
+                "Framed {} of {} shots in {}",
+                run.framed.len(),
+                run.taken,
+                run.session_dir.to_string_lossy()
+            )
+        } else {
+            format!("Framing shots ({} of {})...", run.framed.len(), run.taken)
+        };
+
+        column![text(summary).size(12)]
+            .extend(run.errors.iter().map(|error| text(error).size(12).into()))
+            .spacing(2)
+            .align_x(Alignment::Center)
+    });
+
+    column![controls]
+        .push(status)
+        .spacing(4)
+        .align_x(Alignment::Center)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::capture::{FrameSource, TestPattern};
+    use super::*;
+
+    const SETTINGS: PhotoboothSettings = PhotoboothSettings {
+        countdown: 3,
+        shots: 3,
+        interval: 2,
+    };
+
+    fn at(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn countdown_rounds_up_to_whole_seconds() {
+        let start = Instant::now();
+        let run = PhotoboothRun::new(SETTINGS, PathBuf::new(), start);
+
+        assert_eq!(run.seconds_left(start), Some(3));
+        assert_eq!(run.seconds_left(at(start, 100)), Some(3));
+        assert_eq!(run.seconds_left(at(start, 1_000)), Some(2));
+        assert_eq!(run.seconds_left(at(start, 2_900)), Some(1));
+        assert_eq!(run.seconds_left(at(start, 3_000)), None);
+    }
+
+    #[test]
+    fn shots_are_taken_an_interval_apart() {
+        let start = Instant::now();
+        let mut run = PhotoboothRun::new(SETTINGS, PathBuf::new(), start);
+
+        assert_eq!(run.take_due(at(start, 2_999)), None);
+        assert_eq!(run.take_due(at(start, 3_000)), Some(1));
+        assert_eq!(run.take_due(at(start, 3_000)), None);
+        assert_eq!(run.seconds_left(at(start, 3_000)), Some(2));
+        assert_eq!(run.take_due(at(start, 4_999)), None);
+        assert_eq!(run.take_due(at(start, 5_000)), Some(2));
+        assert_eq!(run.take_due(at(start, 7_500)), Some(3));
+        assert!(!run.is_capturing());
+        assert_eq!(run.take_due(at(start, 60_000)), None);
+        assert_eq!(run.seconds_left(at(start, 7_500)), None);
+    }
+
+    #[test]
+    fn stop_skips_the_rest_of_the_burst() {
+        let start = Instant::now();
+        let mut run = PhotoboothRun::new(SETTINGS, PathBuf::new(), start);
+        assert_eq!(run.take_due(at(start, 3_000)), Some(1));
+
+        run.stop();
+
+        assert!(!run.is_capturing());
+        assert_eq!(run.taken, 1);
+        assert_eq!(run.take_due(at(start, 60_000)), None);
+        assert_eq!(run.seconds_left(at(start, 4_000)), None);
+    }
+
+    #[test]
+    fn finishes_once_every_shot_is_framed_or_failed() {
+        let start = Instant::now();
+        let mut run = PhotoboothRun::new(
+            PhotoboothSettings {
+                shots: 2,
+                ..SETTINGS
+            },
+            PathBuf::new(),
+            start,
+        );
+        assert!(!run.is_finished());
+
+        run.take_due(at(start, 3_000));
+        run.framed.push(PathBuf::from("shot-01_instax.png"));
+        assert!(!run.is_finished(), "the second shot is not taken yet");
+
+        run.take_due(at(start, 5_000));
+        assert!(!run.is_finished(), "the second shot is not framed yet");
+
+        run.errors.push("Failed to encode shot 2".to_owned());
+        assert!(run.is_finished());
+    }
+
+    #[test]
+    fn frame_shot_keeps_the_shot_and_writes_it_framed() {
+        let dir = tempfile::tempdir().unwrap();
+        let session_dir = dir.path().join("session");
+        let shot = TestPattern::default().frame().unwrap();
+        let settings = ExportSettings::default();
+
+        let framed = frame_shot(&shot, 1, &session_dir, &Framing::default(), &settings).unwrap();
+
+        assert_eq!(
+            image::open(session_dir.join("shot-01.png"))
+                .unwrap()
+                .to_rgba8(),
+            shot
+        );
+        assert_eq!(
+            framed,
+            session_dir.join(format!("shot-01_instax.{}", settings.format.extension()))
+        );
+        assert!(image::open(&framed).is_ok());
+    }
+}
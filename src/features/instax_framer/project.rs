@@ -0,0 +1,608 @@
+use std::{
+    borrow::Cow,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use iced::Task;
+use rfd::{FileDialog, MessageDialog, MessageLevel};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Adjustments, CaptionStyle, Collage, Crop, InstaxFilm, InstaxFramer, InstaxFramerMessage,
+};
+
+pub const PROJECT_EXTENSION: &str = "omniframe";
+/// Bumped on incompatible changes to [`Project`], projects of newer versions are refused
+pub const PROJECT_VERSION: u32 = 1;
+
+/// An image with everything needed to frame it again, saved as a `.omniframe` file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    pub version: u32,
+    pub source: ProjectSource,
+    pub film: InstaxFilm,
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub crop: Crop,
+    #[serde(default)]
+    pub adjustments: Adjustments,
+    #[serde(default)]
+    pub caption: String,
+    #[serde(default)]
+    pub caption_style: CaptionStyle,
+    #[serde(default)]
+    pub date_stamp: bool,
+    /// Set for collages, whose slots reference their images by path
+    #[serde(default)]
+    pub collage: Option<Collage>,
+    /// Copies of the collage's slot images when embedded, in slot order, `None` for empty slots
+    #[serde(default)]
+    pub embedded_slots: Vec<Option<EmbeddedImage>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ProjectSource {
+    Path(PathBuf),
+    /// A copy of the image file, for projects moved to another computer
+    Embedded {
+        file_name: String,
+        #[serde(with = "base64")]
+        bytes: Vec<u8>,
+    },
+}
+
+/// A copy of an image file, for projects moved to another computer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddedImage {
+    pub file_name: String,
+    #[serde(with = "base64")]
+    pub bytes: Vec<u8>,
+}
+
+impl EmbeddedImage {
+    fn read(path: &Path) -> Result<EmbeddedImage, String> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("Failed to read {} to embed it: {e}", path.display()))?;
+
+        Ok(Self {
+            file_name: path
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            bytes,
+        })
+    }
+}
+
+/// Read first, to refuse projects of newer versions with a clear error
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+pub fn is_project(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(PROJECT_EXTENSION))
+}
+
+impl Project {
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Writes the project, with a copy of its images, collage slots included, when `embed` is
+    /// set
+    pub fn save(mut self, path: &Path, embed: bool) -> Result<(), String> {
+        if let (true, ProjectSource::Path(source)) = (embed, &self.source) {
+            let EmbeddedImage { file_name, bytes } = EmbeddedImage::read(source)?;
+
+            self.source = ProjectSource::Embedded { file_name, bytes };
+        }
+        if let (true, Some(collage)) = (embed, &self.collage) {
+            self.embedded_slots = collage
+                .slots
+                .iter()
+                .map(|slot| slot.file.as_deref().map(EmbeddedImage::read).transpose())
+                .collect::<Result<_, _>>()?;
+        }
+
+        std::fs::write(path, self.to_ron()?).map_err(|e| e.to_string())
+    }
+
+    /// Reads a project along with the path of its image, extracting an embedded one first
+    pub fn open(path: &Path) -> Result<(PathBuf, Project), String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+        let VersionProbe { version } =
+            ron::from_str(&contents).map_err(|e| format!("Not a project file: {e}"))?;
+        if version > PROJECT_VERSION {
+            return Err(format!(
+                "The project was saved by a newer version of the app (project version {version}), update to open it"
+            ));
+        }
+
+        let mut project: Project = ron::from_str(&contents).map_err(|e| e.to_string())?;
+
+        let image = match &project.source {
+            ProjectSource::Path(image) => image.clone(),
+            ProjectSource::Embedded { file_name, bytes } => extract(file_name, bytes)?,
+        };
+        if let Some(collage) = &mut project.collage {
+            for (slot, embedded) in collage.slots.iter_mut().zip(&project.embedded_slots) {
+                if let Some(EmbeddedImage { file_name, bytes }) = embedded {
+                    slot.file = Some(extract(file_name, bytes)?);
+                }
+            }
+        }
+
+        Ok((image, project))
+    }
+}
+
+/// The `projects` directory next to the config file, holding the autosave and embedded images
+fn projects_dir() -> Option<PathBuf> {
+    let config_file = confy::get_configuration_file_path(crate::constants::APP_NAME, None).ok()?;
+
+    Some(config_file.parent()?.join("projects"))
+}
+
+pub fn autosave_path() -> Option<PathBuf> {
+    Some(projects_dir()?.join(format!("autosave.{PROJECT_EXTENSION}")))
+}
+
+pub fn write_autosave(contents: &str) -> Result<(), String> {
+    let path = autosave_path().ok_or("No config directory")?;
+
+    std::fs::create_dir_all(path.parent().unwrap_or(&path))
+        .and_then(|()| std::fs::write(&path, contents))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+pub fn discard_autosave() {
+    if let Some(path) = autosave_path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Writes an embedded image out, named after its contents so that reopening reuses the copy
+fn extract(file_name: &str, bytes: &[u8]) -> Result<PathBuf, String> {
+    let dir = projects_dir()
+        .ok_or("No config directory to extract the embedded image to")?
+        .join("embedded");
+    // NOTE: only the name of the file, an embedded name must not point outside the directory
+    let file_name = Path::new(file_name)
+        .file_name()
+        .map_or("image".into(), |file_name| file_name.to_string_lossy());
+    let path = dir.join(format!("{:016x}-{file_name}", fnv1a(bytes)));
+
+    if !path.is_file() {
+        std::fs::create_dir_all(&dir)
+            .and_then(|()| std::fs::write(&path, bytes))
+            .map_err(|e| format!("Failed to extract the embedded image: {e}"))?;
+    }
+
+    Ok(path)
+}
+
+/// 64-bit FNV-1a, unlike `DefaultHasher` the same across Rust releases so that the names of
+/// extracted images stay the same
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Embedded images as base64 text, a quarter larger instead of the threefold of a number list
+mod base64 {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+        for chunk in bytes.chunks(3) {
+            let group = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |group, (index, &byte)| {
+                    group | u32::from(byte) << (16 - index * 8)
+                });
+
+            for index in 0..4 {
+                encoded.push(if index <= chunk.len() {
+                    char::from(ALPHABET[(group >> (18 - index * 6)) as usize & 0x3f])
+                } else {
+                    '='
+                });
+            }
+        }
+
+        serializer.serialize_str(&encoded)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let encoded = encoded.trim_end_matches('=').as_bytes();
+        let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+
+        let mut values = [None; 256];
+        for (value, &symbol) in ALPHABET.iter().enumerate() {
+            values[usize::from(symbol)] = Some(value as u32);
+        }
+
+        for chunk in encoded.chunks(4) {
+            let mut group = 0u32;
+
+            for (index, &symbol) in chunk.iter().enumerate() {
+                let value = values[usize::from(symbol)]
+                    .ok_or_else(|| D::Error::custom("invalid base64 in the embedded image"))?;
+
+                group |= value << (18 - index * 6);
+            }
+
+            // NOTE: a trailing group of n symbols holds n - 1 bytes
+            for index in 0..chunk.len().saturating_sub(1) {
+                bytes.push((group >> (16 - index * 8)) as u8);
+            }
+        }
+
+        Ok(bytes)
+    }
+}
+
+impl InstaxFramer {
+    /// Handles the project and autosave messages
+    pub(super) fn update_project(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::ProjectOpenRequested => {
+                let file = FileDialog::new()
+                    .add_filter("project", &[PROJECT_EXTENSION])
+                    .pick_file();
+
+                if let Some(file) = file {
+                    return Task::done(InstaxFramerMessage::ImagePicked(file));
+                }
+            }
+            InstaxFramerMessage::ProjectOpened(file, opened) => match opened {
+                Ok((image, project)) => {
+                    self.opening_project = false;
+
+                    return self.open_project(file, image, project);
+                }
+                Err(e) => {
+                    self.opening_project = false;
+
+                    // NOTE: a project passed on the command line is set as the image at start-up
+                    if self.selected_file.as_ref() == Some(&file) {
+                        self.selected_file = None;
+                    }
+
+                    let _ = MessageDialog::new()
+                        .set_title("Failed to open project")
+                        .set_description(format!("{}\n\n{e}", file.to_string_lossy()))
+                        .set_level(MessageLevel::Error)
+                        .show();
+
+                    return self.open_next_dropped();
+                }
+            },
+            InstaxFramerMessage::ProjectSaveRequested => {
+                let Some(project) = self.project() else {
+                    return Task::none();
+                };
+                let file_name = self
+                    .project_file
+                    .as_ref()
+                    .and_then(|file| file.file_name())
+                    .or_else(|| self.selected_file.as_ref()?.file_stem())
+                    .map_or(Cow::Borrowed("image"), |file_name| {
+                        file_name.to_string_lossy()
+                    });
+
+                let file = FileDialog::new()
+                    .add_filter("project", &[PROJECT_EXTENSION])
+                    .set_file_name(if file_name.ends_with(PROJECT_EXTENSION) {
+                        file_name.into_owned()
+                    } else {
+                        format!("{file_name}.{PROJECT_EXTENSION}")
+                    })
+                    .save_file();
+
+                if let Some(mut file) = file {
+                    if file.extension().is_none() {
+                        file.set_extension(PROJECT_EXTENSION);
+                    }
+                    let embed = self.embed_in_project;
+
+                    return Task::future(async move {
+                        let destination = file.clone();
+                        let saved =
+                            tokio::task::spawn_blocking(move || project.save(&destination, embed))
+                                .await
+                                .unwrap_or_else(|e| Err(e.to_string()));
+
+                        InstaxFramerMessage::ProjectSaved(saved.map(|()| file))
+                    });
+                }
+            }
+            InstaxFramerMessage::ProjectSaved(saved) => match saved {
+                Ok(file) => {
+                    self.project_file = Some(file);
+                    self.autosaved_hash = self.project_hash().map(|(hash, _)| hash);
+                    discard_autosave();
+                }
+                Err(e) => {
+                    let _ = MessageDialog::new()
+                        .set_title("Failed to save project")
+                        .set_description(e)
+                        .set_level(MessageLevel::Error)
+                        .show();
+                }
+            },
+            InstaxFramerMessage::EmbedInProjectToggled(embed_in_project) => {
+                self.embed_in_project = embed_in_project;
+            }
+            InstaxFramerMessage::AutosaveTicked => {
+                let Some((hash, contents)) = self.project_hash() else {
+                    return Task::none();
+                };
+                if self.autosaved_hash == Some(hash) {
+                    return Task::none();
+                }
+                self.autosaved_hash = Some(hash);
+
+                return Task::future(async move {
+                    match tokio::task::spawn_blocking(move || write_autosave(&contents)).await {
+                        Ok(Err(e)) => eprintln!("Autosave failed: {e}"),
+                        Err(e) => {
+                            eprintln!("Blocking autosave task panicked or was cancelled: {e:?}");
+                        }
+                        Ok(Ok(())) => {}
+                    }
+                })
+                .discard();
+            }
+            InstaxFramerMessage::AutosaveRecoveryChecked => {
+                let Some(autosave) = autosave_path().filter(|path| path.is_file()) else {
+                    return Task::none();
+                };
+
+                let recover = MessageDialog::new()
+                    .set_title("Recover unsaved work?")
+                    .set_description(
+                        "The app did not close properly last time. Reopen the image and its framing from the autosave?",
+                    )
+                    .set_level(MessageLevel::Warning)
+                    .set_buttons(rfd::MessageButtons::YesNo)
+                    .show();
+
+                if matches!(recover, rfd::MessageDialogResult::Yes) {
+                    return Task::done(InstaxFramerMessage::ImagePicked(autosave));
+                }
+
+                discard_autosave();
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    /// The opened image with its framing, `None` without an image
+    pub(super) fn project(&self) -> Option<Project> {
+        let selected_file = self.selected_file.clone()?;
+
+        Some(Project {
+            version: PROJECT_VERSION,
+            film: self.film,
+            template: self.template.clone(),
+            // NOTE: not `crop()`, which is the selected slot's in collage mode
+            crop: self.crops.get(&selected_file).copied().unwrap_or_default(),
+            adjustments: self.adjustments(),
+            caption: self.caption().to_owned(),
+            caption_style: self.caption_style,
+            date_stamp: self.date_stamp,
+            collage: self.show_collage.then(|| self.collage.clone()),
+            embedded_slots: vec![],
+            source: ProjectSource::Path(selected_file),
+        })
+    }
+
+    /// Hash of the project as saved, to tell whether there is anything new to autosave
+    pub(super) fn project_hash(&self) -> Option<(u64, String)> {
+        let contents = self.project()?.to_ron().ok()?;
+
+        let mut hasher = DefaultHasher::new();
+        contents.hash(&mut hasher);
+
+        Some((hasher.finish(), contents))
+    }
+
+    /// Applies the project's framing to its image and opens it
+    pub(super) fn open_project(
+        &mut self,
+        file: PathBuf,
+        image: PathBuf,
+        project: Project,
+    ) -> Task<InstaxFramerMessage> {
+        let Project {
+            film,
+            template,
+            crop,
+            adjustments,
+            caption,
+            caption_style,
+            date_stamp,
+            collage,
+            ..
+        } = project;
+
+        self.film = film;
+        self.template = template;
+        self.caption_style = caption_style;
+        self.date_stamp = date_stamp;
+        self.crops.insert(image.clone(), crop);
+        self.adjustments.insert(image.clone(), adjustments);
+        if caption.is_empty() {
+            self.captions.remove(&image);
+        } else {
+            self.captions.insert(image.clone(), caption);
+        }
+
+        self.show_collage = collage.is_some();
+        if let Some(collage) = collage {
+            self.collage = collage;
+            self.collage_slot = 0;
+        }
+
+        // NOTE: saving again must not overwrite the autosave, it is a recovery slot only
+        if autosave_path().as_ref() != Some(&file) {
+            self.project_file = Some(file);
+        }
+
+        self.update(InstaxFramerMessage::ImagePicked(image))
+    }
+
+    /// Deletes the autosave, once the app closes cleanly
+    pub(crate) fn discard_autosave(&self) {
+        discard_autosave();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::instax_framer::collage::CollageSlot;
+
+    fn project(source: PathBuf) -> Project {
+        Project {
+            version: PROJECT_VERSION,
+            source: ProjectSource::Path(source),
+            film: InstaxFilm::default(),
+            template: None,
+            crop: Crop::default(),
+            adjustments: Adjustments::default(),
+            caption: "Summer".to_owned(),
+            caption_style: CaptionStyle::default(),
+            date_stamp: false,
+            collage: None,
+            embedded_slots: vec![],
+        }
+    }
+
+    #[test]
+    fn embedded_bytes_survive_base64() {
+        for length in 0..8 {
+            let bytes = (0..length)
+                .map(|byte: u8| byte.wrapping_mul(97))
+                .collect::<Vec<_>>();
+            let source = ProjectSource::Embedded {
+                file_name: "a.jpg".to_owned(),
+                bytes: bytes.clone(),
+            };
+
+            let contents = ron::to_string(&source).unwrap();
+
+            assert_eq!(ron::from_str::<ProjectSource>(&contents).unwrap(), source);
+        }
+    }
+
+    #[test]
+    fn embedded_bytes_are_written_as_base64() {
+        let source = ProjectSource::Embedded {
+            file_name: "a.jpg".to_owned(),
+            bytes: b"Man".to_vec(),
+        };
+
+        assert!(ron::to_string(&source).unwrap().contains("\"TWFu\""));
+    }
+
+    #[test]
+    fn invalid_base64_is_refused() {
+        assert!(
+            ron::from_str::<ProjectSource>(r#"Embedded(file_name: "a.jpg", bytes: "TW!u")"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn projects_of_newer_versions_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.omniframe");
+        let mut newer = project(dir.path().join("a.jpg"));
+        newer.version = PROJECT_VERSION + 1;
+        std::fs::write(&path, newer.to_ron().unwrap()).unwrap();
+
+        let error = Project::open(&path).unwrap_err();
+
+        assert!(error.contains("newer version"), "{error}");
+    }
+
+    #[test]
+    fn projects_reopen_as_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.omniframe");
+        let image = dir.path().join("a.jpg");
+        project(image.clone()).save(&path, false).unwrap();
+
+        assert_eq!(
+            Project::open(&path).unwrap(),
+            (image.clone(), project(image))
+        );
+    }
+
+    #[test]
+    fn collage_slots_are_embedded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.omniframe");
+        let image = dir.path().join("a.jpg");
+        let slot_image = dir.path().join("b.jpg");
+        std::fs::write(&image, "a").unwrap();
+        std::fs::write(&slot_image, "b").unwrap();
+        let mut collage_project = project(image);
+        collage_project.collage = Some(Collage {
+            slots: vec![
+                CollageSlot::default(),
+                CollageSlot {
+                    file: Some(slot_image),
+                    crop: Crop::default(),
+                },
+            ],
+            ..Collage::default()
+        });
+
+        collage_project.save(&path, true).unwrap();
+        let saved: Project = ron::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+        assert_eq!(
+            saved.source,
+            ProjectSource::Embedded {
+                file_name: "a.jpg".to_owned(),
+                bytes: b"a".to_vec(),
+            }
+        );
+        assert_eq!(
+            saved.embedded_slots,
+            [
+                None,
+                Some(EmbeddedImage {
+                    file_name: "b.jpg".to_owned(),
+                    bytes: b"b".to_vec(),
+                })
+            ]
+        );
+    }
+
+    #[test]
+    fn extracted_names_do_not_change_between_releases() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
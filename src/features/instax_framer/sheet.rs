@@ -0,0 +1,1005 @@
+use std::{
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use iced::{
+    Alignment, Element, Length, Task,
+    widget::{button, column, image as iced_image, pick_list, row, scrollable, slider, text},
+};
+use image::{
+    DynamicImage, ImageEncoder, ImageResult, Rgba, RgbaImage, codecs::jpeg::JpegEncoder, imageops,
+};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref};
+use rfd::{FileDialog, MessageDialog, MessageLevel};
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Framing, ImageMetadata, InstaxFilm, InstaxFramer, InstaxFramerMessage, LoadedImage, StampDate,
+    frame,
+};
+
+pub const MAX_SHEET_MARGIN: f32 = 30.0;
+pub const MAX_SHEET_GAP: f32 = 15.0;
+/// Resolution of the on-screen sheet preview
+pub const SHEET_PREVIEW_PIXELS_PER_MM: f32 = 3.0;
+/// Longest edge of the copies the sheet preview is composed from
+const THUMBNAIL_SIZE: u32 = 600;
+const PT_PER_MM: f32 = 72.0 / 25.4;
+/// Crop marks stop this far from the frame, so that none of them shows on a trimmed print
+const MARK_OFFSET: f32 = 1.0;
+const MARK_LENGTH: f32 = 5.0;
+const MARK_WIDTH: f32 = 0.2;
+const PAGE_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const MARK_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const MISSING_COLOR: Rgba<u8> = Rgba([224, 224, 224, 255]);
+
+/// Files of the sheet that could not be loaded, with why, left blank on the pages
+pub type SheetFailures = Vec<(PathBuf, String)>;
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PaperSize {
+    #[default]
+    A4,
+    Letter,
+}
+
+pub static PAPER_SIZES: &[PaperSize] = &[PaperSize::A4, PaperSize::Letter];
+
+impl std::fmt::Display for PaperSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaperSize::A4 => f.write_str("A4"),
+            PaperSize::Letter => f.write_str("US Letter"),
+        }
+    }
+}
+
+impl PaperSize {
+    /// Portrait width and height, in millimeters
+    pub fn size(self) -> (f32, f32) {
+        match self {
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SheetSettings {
+    pub paper: PaperSize,
+    /// Blank space kept along every page edge, in millimeters
+    pub margin: f32,
+    /// Space between neighbouring frames, in millimeters
+    pub gap: f32,
+}
+
+impl Default for SheetSettings {
+    fn default() -> Self {
+        Self {
+            paper: PaperSize::default(),
+            margin: 10.0,
+            gap: 4.0,
+        }
+    }
+}
+
+impl Hash for SheetSettings {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.paper.hash(state);
+        self.margin.to_bits().hash(state);
+        self.gap.to_bits().hash(state);
+    }
+}
+
+/// Where the frames go on a portrait page, in millimeters from its top-left corner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SheetLayout {
+    pub page_width: f32,
+    pub page_height: f32,
+    /// Size of a frame as placed on the page
+    pub frame_width: f32,
+    pub frame_height: f32,
+    /// Frames are turned a quarter clockwise when more of them fit that way
+    pub rotated: bool,
+    pub columns: usize,
+    pub rows: usize,
+    origin_x: f32,
+    origin_y: f32,
+    gap: f32,
+}
+
+impl SheetLayout {
+    pub fn new(settings: &SheetSettings, film: InstaxFilm) -> SheetLayout {
+        let (page_width, page_height) = settings.paper.size();
+        let (available_width, available_height) = (
+            page_width - settings.margin * 2.0,
+            page_height - settings.margin * 2.0,
+        );
+        let gap = settings.gap.max(0.0);
+        let fit = |available: f32, size: f32| ((available + gap) / (size + gap)).max(0.0) as usize;
+
+        let layout = film.layout();
+        let (columns, rows) = (
+            fit(available_width, layout.width),
+            fit(available_height, layout.height),
+        );
+        let (turned_columns, turned_rows) = (
+            fit(available_width, layout.height),
+            fit(available_height, layout.width),
+        );
+        let rotated = turned_columns * turned_rows > columns * rows;
+
+        let (frame_width, frame_height, columns, rows) = if rotated {
+            (layout.height, layout.width, turned_columns, turned_rows)
+        } else {
+            (layout.width, layout.height, columns, rows)
+        };
+        let span =
+            |count: usize, size: f32| count as f32 * size + count.saturating_sub(1) as f32 * gap;
+
+        SheetLayout {
+            page_width,
+            page_height,
+            frame_width,
+            frame_height,
+            rotated,
+            columns,
+            rows,
+            // NOTE: centered, so that the leftover space is split evenly between opposite margins
+            origin_x: (page_width - span(columns, frame_width)) / 2.0,
+            origin_y: (page_height - span(rows, frame_height)) / 2.0,
+            gap,
+        }
+    }
+
+    pub fn per_page(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    pub fn page_count(&self, frames: usize) -> usize {
+        match self.per_page() {
+            0 => 0,
+            per_page => frames.div_ceil(per_page),
+        }
+    }
+
+    /// Top-left corner of the frame at this position on its page
+    pub fn slot(&self, index: usize) -> (f32, f32) {
+        let (column, row) = (index % self.columns.max(1), index / self.columns.max(1));
+
+        (
+            self.origin_x + column as f32 * (self.frame_width + self.gap),
+            self.origin_y + row as f32 * (self.frame_height + self.gap),
+        )
+    }
+
+    /// Start and end points of the crop marks, continuing every frame edge into the margins
+    pub fn crop_marks(&self, frames: usize) -> Vec<[(f32, f32); 2]> {
+        let columns = frames.min(self.columns);
+        let rows = frames.div_ceil(self.columns.max(1)).min(self.rows);
+        if columns == 0 || rows == 0 {
+            return vec![];
+        }
+
+        let (left, top) = self.slot(0);
+        let (right, bottom) = (
+            left + columns as f32 * (self.frame_width + self.gap) - self.gap,
+            top + rows as f32 * (self.frame_height + self.gap) - self.gap,
+        );
+        let edges = |start: f32, count: usize, size: f32| {
+            (0..count).flat_map(move |index| {
+                let edge = start + index as f32 * (size + self.gap);
+
+                [edge, edge + size]
+            })
+        };
+        let length = |space: f32| MARK_LENGTH.min(space - MARK_OFFSET);
+
+        let mut marks = vec![];
+
+        for x in edges(left, columns, self.frame_width) {
+            let (above, below) = (length(top), length(self.page_height - bottom));
+
+            if above >= 1.0 {
+                marks.push([(x, top - MARK_OFFSET), (x, top - MARK_OFFSET - above)]);
+            }
+            if below >= 1.0 {
+                marks.push([(x, bottom + MARK_OFFSET), (x, bottom + MARK_OFFSET + below)]);
+            }
+        }
+        for y in edges(top, rows, self.frame_height) {
+            let (before, after) = (length(left), length(self.page_width - right));
+
+            if before >= 1.0 {
+                marks.push([(left - MARK_OFFSET, y), (left - MARK_OFFSET - before, y)]);
+            }
+            if after >= 1.0 {
+                marks.push([(right + MARK_OFFSET, y), (right + MARK_OFFSET + after, y)]);
+            }
+        }
+
+        marks
+    }
+}
+
+/// A small upright copy of a sheet entry, decoded once for the preview
+#[derive(Debug, Clone)]
+pub struct SheetThumbnail {
+    /// Why the image could not be loaded, it shows as an empty slot then
+    image: Result<Arc<DynamicImage>, String>,
+    taken_on: Option<StampDate>,
+}
+
+impl SheetThumbnail {
+    fn load(file: &Path, max_pixels: u64) -> SheetThumbnail {
+        match LoadedImage::decode(file, max_pixels) {
+            Ok((image, exif)) => {
+                let metadata = ImageMetadata::read(exif.as_deref(), image.width(), image.height());
+
+                SheetThumbnail {
+                    image: Ok(Arc::new(image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE))),
+                    taken_on: LoadedImage::taken_on(file, &metadata),
+                }
+            }
+            Err(e) => SheetThumbnail {
+                image: Err(format!("Failed to load: {e}")),
+                taken_on: None,
+            },
+        }
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.image.as_ref().err().map(String::as_str)
+    }
+}
+
+/// Everything a sheet needs, captured when it is rendered so that later edits do not leak into it
+#[derive(Debug, Clone)]
+pub struct SheetJob {
+    /// Every file with its own crop, adjustments and caption, the date stamp excepted
+    pub entries: Vec<(PathBuf, Framing)>,
+    pub date_stamp: bool,
+    pub layout: SheetLayout,
+    pub max_pixels: u64,
+}
+
+impl SheetJob {
+    /// Renders one page from thumbnails, returning the thumbnails it had to decode
+    pub fn preview(
+        &self,
+        page: usize,
+        mut thumbnails: BTreeMap<PathBuf, SheetThumbnail>,
+    ) -> (RgbaImage, Vec<(PathBuf, SheetThumbnail)>) {
+        let mut decoded = vec![];
+        let mut frames = vec![];
+
+        for (file, framing) in self.page_entries(page) {
+            let thumbnail = thumbnails.entry(file.clone()).or_insert_with(|| {
+                let thumbnail = SheetThumbnail::load(file, self.max_pixels);
+                decoded.push((file.clone(), thumbnail.clone()));
+
+                thumbnail
+            });
+
+            frames.push(thumbnail.image.as_ref().ok().map(|image| {
+                self.compose(
+                    image,
+                    framing,
+                    thumbnail.taken_on,
+                    SHEET_PREVIEW_PIXELS_PER_MM,
+                )
+            }));
+        }
+
+        (
+            self.render_page(&frames, SHEET_PREVIEW_PIXELS_PER_MM),
+            decoded,
+        )
+    }
+
+    /// Writes every page into a single PDF, frames embedded as JPEG at the given resolution.
+    /// Images that fail to load are left blank and returned
+    pub fn export_pdf(
+        &self,
+        path: &Path,
+        pixels_per_mm: f32,
+        quality: u8,
+    ) -> Result<SheetFailures, String> {
+        let mut failures = vec![];
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let mut next_id = Ref::new(3);
+        let mut page_ids = vec![];
+        let mut pdf = Pdf::new();
+
+        let (page_width, page_height) = (
+            self.layout.page_width * PT_PER_MM,
+            self.layout.page_height * PT_PER_MM,
+        );
+
+        for page in 0..self.page_count() {
+            let mut content = Content::new();
+            let mut images = vec![];
+            let mut frames_count = 0;
+
+            for (index, (file, framing)) in self.page_entries(page).enumerate() {
+                frames_count += 1;
+                let (x, y) = self.layout.slot(index);
+                // NOTE: PDF pages grow upwards from their bottom-left corner
+                let bottom = page_height - (y + self.layout.frame_height) * PT_PER_MM;
+
+                let frame = match self.frame_file(file, framing, pixels_per_mm) {
+                    Ok(frame) => frame,
+                    Err(e) => {
+                        let [red, green, blue, _] =
+                            MISSING_COLOR.0.map(|channel| f32::from(channel) / 255.0);
+                        content
+                            .save_state()
+                            .set_fill_rgb(red, green, blue)
+                            .rect(
+                                x * PT_PER_MM,
+                                bottom,
+                                self.layout.frame_width * PT_PER_MM,
+                                self.layout.frame_height * PT_PER_MM,
+                            )
+                            .fill_nonzero()
+                            .restore_state();
+                        failures.push((file.clone(), e));
+                        continue;
+                    }
+                };
+                let (width, height) = frame.dimensions();
+                let bytes = encode_jpeg(frame, quality)
+                    .map_err(|e| format!("Failed to encode {}: {e}", file.display()))?;
+
+                let image_id = next_id.bump();
+                let mut image = pdf.image_xobject(image_id, &bytes);
+                image.filter(Filter::DctDecode);
+                image
+                    .width(width as i32)
+                    .height(height as i32)
+                    .bits_per_component(8)
+                    .color_space()
+                    .device_rgb();
+                image.finish();
+                let name = format!("Im{index}");
+
+                content
+                    .save_state()
+                    .transform([
+                        self.layout.frame_width * PT_PER_MM,
+                        0.0,
+                        0.0,
+                        self.layout.frame_height * PT_PER_MM,
+                        x * PT_PER_MM,
+                        bottom,
+                    ])
+                    .x_object(Name(name.as_bytes()))
+                    .restore_state();
+                images.push((name, image_id));
+            }
+
+            content.set_line_width(MARK_WIDTH * PT_PER_MM);
+            for [(start_x, start_y), (end_x, end_y)] in self.layout.crop_marks(frames_count) {
+                content
+                    .move_to(start_x * PT_PER_MM, page_height - start_y * PT_PER_MM)
+                    .line_to(end_x * PT_PER_MM, page_height - end_y * PT_PER_MM)
+                    .stroke();
+            }
+
+            let content_id = next_id.bump();
+            pdf.stream(content_id, &content.finish());
+
+            let page_id = next_id.bump();
+            let mut pdf_page = pdf.page(page_id);
+            pdf_page
+                .parent(page_tree_id)
+                .media_box(Rect::new(0.0, 0.0, page_width, page_height))
+                .contents(content_id);
+            let mut resources = pdf_page.resources();
+            let mut x_objects = resources.x_objects();
+            for (name, image_id) in &images {
+                x_objects.pair(Name(name.as_bytes()), *image_id);
+            }
+            drop(x_objects);
+            drop(resources);
+            drop(pdf_page);
+
+            page_ids.push(page_id);
+        }
+
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .count(page_ids.len() as i32)
+            .kids(page_ids);
+
+        std::fs::write(path, pdf.finish())
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+
+        Ok(failures)
+    }
+
+    /// The `<name>-<page>.png` files the pages are written to, next to the path
+    pub fn png_pages(&self, path: &Path) -> Vec<PathBuf> {
+        let file_stem = path
+            .file_stem()
+            .map_or("sheet".into(), |file_stem| file_stem.to_string_lossy());
+
+        (1..=self.page_count())
+            .map(|page| path.with_file_name(format!("{file_stem}-{page}.png")))
+            .collect()
+    }
+
+    /// Writes every page as a PNG, replacing existing pages, returning the first page's path.
+    /// Images that fail to load are left blank and returned
+    pub fn export_png(
+        &self,
+        path: &Path,
+        pixels_per_mm: f32,
+    ) -> Result<(PathBuf, SheetFailures), String> {
+        let pages = self.png_pages(path);
+        let mut failures = vec![];
+
+        for (page, destination) in pages.iter().enumerate() {
+            let frames = self
+                .page_entries(page)
+                .map(
+                    |(file, framing)| match self.frame_file(file, framing, pixels_per_mm) {
+                        Ok(frame) => Some(frame),
+                        Err(e) => {
+                            failures.push((file.clone(), e));
+                            None
+                        }
+                    },
+                )
+                .collect::<Vec<_>>();
+
+            self.render_page(&frames, pixels_per_mm)
+                .save(destination)
+                .map_err(|e| format!("Failed to write {}: {e}", destination.display()))?;
+        }
+
+        let first_page = pages.into_iter().next().unwrap_or_else(|| path.to_owned());
+
+        Ok((first_page, failures))
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.layout.page_count(self.entries.len())
+    }
+
+    fn page_entries(&self, page: usize) -> impl Iterator<Item = &(PathBuf, Framing)> {
+        let per_page = self.layout.per_page();
+
+        self.entries.iter().skip(page * per_page).take(per_page)
+    }
+
+    fn frame_file(
+        &self,
+        file: &Path,
+        framing: &Framing,
+        pixels_per_mm: f32,
+    ) -> Result<RgbaImage, String> {
+        let (image, exif) = LoadedImage::decode(file, self.max_pixels)
+            .map_err(|e| format!("Failed to load: {e}"))?;
+        let metadata = ImageMetadata::read(exif.as_deref(), image.width(), image.height());
+
+        Ok(self.compose(
+            &image,
+            framing,
+            LoadedImage::taken_on(file, &metadata),
+            pixels_per_mm,
+        ))
+    }
+
+    /// Frames the image as it is placed on the page, turned when the layout is
+    fn compose(
+        &self,
+        image: &DynamicImage,
+        framing: &Framing,
+        taken_on: Option<StampDate>,
+        pixels_per_mm: f32,
+    ) -> RgbaImage {
+        let mut framing = framing.clone();
+        framing.inscription.date_stamp = taken_on.filter(|_| self.date_stamp);
+
+        let framed_image = frame::compose(image, &framing, pixels_per_mm);
+
+        if self.layout.rotated {
+            imageops::rotate90(&framed_image)
+        } else {
+            framed_image
+        }
+    }
+
+    /// Places the frames of a page and draws its crop marks, frames that failed to load are grey
+    fn render_page(&self, frames: &[Option<RgbaImage>], pixels_per_mm: f32) -> RgbaImage {
+        let layout = &self.layout;
+        let to_px = |millimeters: f32| (millimeters * pixels_per_mm).round() as i64;
+        let mut page = RgbaImage::from_pixel(
+            to_px(layout.page_width) as u32,
+            to_px(layout.page_height) as u32,
+            PAGE_COLOR,
+        );
+
+        for (index, frame) in frames.iter().enumerate() {
+            let (x, y) = layout.slot(index);
+
+            match frame {
+                Some(frame) => imageops::replace(&mut page, frame, to_px(x), to_px(y)),
+                None => imageops::replace(
+                    &mut page,
+                    &RgbaImage::from_pixel(
+                        to_px(layout.frame_width) as u32,
+                        to_px(layout.frame_height) as u32,
+                        MISSING_COLOR,
+                    ),
+                    to_px(x),
+                    to_px(y),
+                ),
+            }
+        }
+
+        let half_width = (MARK_WIDTH * pixels_per_mm / 2.0).max(0.5);
+        for [(start_x, start_y), (end_x, end_y)] in layout.crop_marks(frames.len()) {
+            let (left, right) = (start_x.min(end_x), start_x.max(end_x));
+            let (top, bottom) = (start_y.min(end_y), start_y.max(end_y));
+
+            let mark = RgbaImage::from_pixel(
+                ((right - left) * pixels_per_mm + half_width * 2.0).round() as u32,
+                ((bottom - top) * pixels_per_mm + half_width * 2.0).round() as u32,
+                MARK_COLOR,
+            );
+            imageops::replace(
+                &mut page,
+                &mark,
+                (left * pixels_per_mm - half_width).round() as i64,
+                (top * pixels_per_mm - half_width).round() as i64,
+            );
+        }
+
+        page
+    }
+}
+
+fn encode_jpeg(frame: RgbaImage, quality: u8) -> ImageResult<Vec<u8>> {
+    let frame = DynamicImage::ImageRgba8(frame).to_rgb8();
+    let mut bytes = Cursor::new(vec![]);
+
+    JpegEncoder::new_with_quality(&mut bytes, quality.clamp(1, 100)).write_image(
+        &frame,
+        frame.width(),
+        frame.height(),
+        image::ExtendedColorType::Rgb8,
+    )?;
+
+    Ok(bytes.into_inner())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetOutput {
+    Pdf,
+    PngPages,
+}
+
+impl std::fmt::Display for SheetOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SheetOutput::Pdf => f.write_str("PDF"),
+            SheetOutput::PngPages => f.write_str("PNG pages"),
+        }
+    }
+}
+
+impl SheetOutput {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SheetOutput::Pdf => "pdf",
+            SheetOutput::PngPages => "png",
+        }
+    }
+}
+
+impl InstaxFramer {
+    /// Handles the contact sheet messages
+    pub(super) fn update_sheet(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::SheetToggled => {
+                self.show_sheet = !self.show_sheet;
+
+                return self.render_sheet();
+            }
+            InstaxFramerMessage::SheetImageToggled => {
+                if let Some(selected_file) = self.selected_file.clone() {
+                    if let Some(index) = self
+                        .sheet_files
+                        .iter()
+                        .position(|file| file == &selected_file)
+                    {
+                        self.sheet_files.remove(index);
+                    } else {
+                        self.sheet_files.push(selected_file);
+                    }
+                }
+
+                return self.render_sheet();
+            }
+            InstaxFramerMessage::SheetCleared => {
+                self.sheet_files.clear();
+                self.sheet_thumbnails.clear();
+
+                return self.render_sheet();
+            }
+            InstaxFramerMessage::SheetPaperSelected(paper) => {
+                self.sheet_settings.paper = paper;
+
+                return self.render_sheet();
+            }
+            InstaxFramerMessage::SheetMarginChanged(margin) => {
+                self.sheet_settings.margin = margin;
+
+                return self.render_sheet();
+            }
+            InstaxFramerMessage::SheetGapChanged(gap) => {
+                self.sheet_settings.gap = gap;
+
+                return self.render_sheet();
+            }
+            // NOTE: only there to save the config once a slider is let go
+            InstaxFramerMessage::SheetSettingsReleased => {}
+            InstaxFramerMessage::SheetPageChanged(page) => {
+                self.sheet_page = page;
+
+                return self.render_sheet();
+            }
+            InstaxFramerMessage::SheetPreviewRendered(generation, sheet_preview, decoded) => {
+                self.sheet_thumbnails.extend(decoded);
+
+                if generation == self.sheet_generation {
+                    self.sheet_preview = Some(sheet_preview);
+                }
+            }
+            InstaxFramerMessage::SheetExportRequested(output) => {
+                let file = FileDialog::new()
+                    .add_filter(output.to_string(), &[output.extension()])
+                    .set_file_name(format!("instax_sheet.{}", output.extension()))
+                    .save_file();
+
+                if let Some(mut file) = file {
+                    if file.extension().is_none() {
+                        file.set_extension(output.extension());
+                    }
+
+                    let job = self.sheet_job();
+
+                    // NOTE: the save dialog only asked about the picked name, not about the pages
+                    let existing_pages = match output {
+                        SheetOutput::Pdf => 0,
+                        SheetOutput::PngPages => job
+                            .png_pages(&file)
+                            .iter()
+                            .filter(|page| page.exists())
+                            .count(),
+                    };
+                    if existing_pages > 0
+                        && !matches!(
+                            MessageDialog::new()
+                                .set_title("Replace existing pages?")
+                                .set_description(format!(
+                                    "{existing_pages} of the PNG pages already exist next to {}, \
+                                     replace them?",
+                                    file.to_string_lossy()
+                                ))
+                                .set_level(MessageLevel::Warning)
+                                .set_buttons(rfd::MessageButtons::YesNo)
+                                .show(),
+                            rfd::MessageDialogResult::Yes
+                        )
+                    {
+                        return Task::none();
+                    }
+
+                    self.sheet_exporting = true;
+                    self.last_exported_file = None;
+
+                    let pixels_per_mm = self.export_settings.dpi.pixels_per_mm();
+                    let quality = self.export_settings.jpeg_quality;
+
+                    return Task::future(async move {
+                        let export_result = tokio::task::spawn_blocking(move || match output {
+                            SheetOutput::Pdf => job
+                                .export_pdf(&file, pixels_per_mm, quality)
+                                .map(|failures| (file, failures)),
+                            SheetOutput::PngPages => job.export_png(&file, pixels_per_mm),
+                        })
+                        .await;
+
+                        match export_result {
+                            Ok(Ok((path, failures))) => InstaxFramerMessage::ExportFinished(
+                                path,
+                                failures
+                                    .into_iter()
+                                    .map(|(file, e)| {
+                                        format!(
+                                            "{} was left blank on the sheet: {e}",
+                                            file.to_string_lossy()
+                                        )
+                                    })
+                                    .collect(),
+                            ),
+                            Ok(Err(e)) => InstaxFramerMessage::ExportFailed(e),
+                            Err(e) => InstaxFramerMessage::ExportFailed(e.to_string()),
+                        }
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    pub(super) fn sheet_area(&self) -> Element<'_, InstaxFramerMessage> {
+        let layout = SheetLayout::new(&self.sheet_settings, self.film);
+        let page_count = layout.page_count(self.sheet_files.len()).max(1);
+        let on_sheet = self
+            .selected_file
+            .as_ref()
+            .is_some_and(|selected_file| self.sheet_files.contains(selected_file));
+        let can_export = !self.sheet_files.is_empty() && layout.per_page() > 0;
+
+        let page: Element<'_, InstaxFramerMessage> = match (&self.sheet_preview, layout.per_page())
+        {
+            (_, 0) => text("No frame fits on the page, reduce the margins").into(),
+            (None, _) => text("Laying out...").into(),
+            (Some(sheet_preview), _) => iced_image(sheet_preview.clone())
+                .content_fit(iced::ContentFit::Contain)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into(),
+        };
+
+        column![
+            page,
+            row![
+                button(text(if on_sheet {
+                    "Remove from sheet"
+                } else {
+                    "Add to sheet"
+                }))
+                .on_press(InstaxFramerMessage::SheetImageToggled),
+                text(format!(
+                    "{} on the sheet, {} per page",
+                    self.sheet_files.len(),
+                    layout.per_page()
+                )),
+                button(text("Clear"))
+                    .style(button::secondary)
+                    .on_press_maybe(
+                        (!self.sheet_files.is_empty()).then_some(InstaxFramerMessage::SheetCleared)
+                    ),
+                button(text("◀")).style(button::secondary).on_press_maybe(
+                    (self.sheet_page > 0)
+                        .then(|| InstaxFramerMessage::SheetPageChanged(self.sheet_page - 1))
+                ),
+                text(format!("Page {} / {page_count}", self.sheet_page + 1)),
+                button(text("▶")).style(button::secondary).on_press_maybe(
+                    (self.sheet_page + 1 < page_count)
+                        .then(|| InstaxFramerMessage::SheetPageChanged(self.sheet_page + 1))
+                ),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .wrap(),
+            row![
+                pick_list(
+                    PAPER_SIZES,
+                    Some(self.sheet_settings.paper),
+                    InstaxFramerMessage::SheetPaperSelected
+                ),
+                text(format!("Margin {:.0}mm", self.sheet_settings.margin)),
+                slider(
+                    0.0..=MAX_SHEET_MARGIN,
+                    self.sheet_settings.margin,
+                    InstaxFramerMessage::SheetMarginChanged
+                )
+                .step(1.0)
+                .on_release(InstaxFramerMessage::SheetSettingsReleased)
+                .width(80),
+                text(format!("Gap {:.0}mm", self.sheet_settings.gap)),
+                slider(
+                    0.0..=MAX_SHEET_GAP,
+                    self.sheet_settings.gap,
+                    InstaxFramerMessage::SheetGapChanged
+                )
+                .step(1.0)
+                .on_release(InstaxFramerMessage::SheetSettingsReleased)
+                .width(80),
+                button(text("Export PDF...")).on_press_maybe(
+                    (can_export && !self.sheet_exporting)
+                        .then_some(InstaxFramerMessage::SheetExportRequested(SheetOutput::Pdf))
+                ),
+                button(text("Export PNG pages...")).on_press_maybe(
+                    (can_export && !self.sheet_exporting).then_some(
+                        InstaxFramerMessage::SheetExportRequested(SheetOutput::PngPages)
+                    )
+                ),
+            ]
+            .push(self.sheet_exporting.then(|| text("Exporting sheet...")))
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .wrap(),
+        ]
+        .push(self.sheet_failures())
+        .spacing(8)
+        .align_x(Alignment::Center)
+        .into()
+    }
+
+    /// The sheet's images that could not be loaded, shown blank on the pages
+    pub(super) fn sheet_failures(&self) -> Option<Element<'_, InstaxFramerMessage>> {
+        let failures = self
+            .sheet_files
+            .iter()
+            .filter_map(|file| {
+                let error = self.sheet_thumbnails.get(file)?.error()?;
+
+                Some(
+                    text(format!(
+                        "{}: {error}",
+                        file.file_name()
+                            .map_or(file.to_string_lossy(), |file_name| file_name
+                                .to_string_lossy())
+                    ))
+                    .size(12)
+                    .into(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        (!failures.is_empty()).then(|| {
+            scrollable(column(failures).spacing(2))
+                .height(Length::Shrink)
+                .into()
+        })
+    }
+
+    /// Lays out the shown page of the print sheet, while the sheet is shown
+    pub(super) fn render_sheet(&mut self) -> Task<InstaxFramerMessage> {
+        if !self.show_sheet {
+            return Task::none();
+        }
+
+        let job = self.sheet_job();
+        self.sheet_page = self.sheet_page.min(job.page_count().saturating_sub(1));
+        let page = self.sheet_page;
+        let thumbnails = self.sheet_thumbnails.clone();
+
+        self.sheet_generation += 1;
+        let generation = self.sheet_generation;
+
+        Task::future(async move {
+            match tokio::task::spawn_blocking(move || job.preview(page, thumbnails)).await {
+                Ok((page, decoded)) => Some(InstaxFramerMessage::SheetPreviewRendered(
+                    generation,
+                    iced_image::Handle::from_rgba(page.width(), page.height(), page.into_raw()),
+                    decoded,
+                )),
+                Err(e) => {
+                    eprintln!("Blocking sheet rendering task panicked or was cancelled: {e:?}");
+                    None
+                }
+            }
+        })
+        .and_then(Task::done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::instax_framer::DEFAULT_MAX_PIXELS;
+
+    fn layout(film: InstaxFilm) -> SheetLayout {
+        SheetLayout::new(&SheetSettings::default(), film)
+    }
+
+    #[test]
+    fn mini_frames_fill_an_a4_page_upright() {
+        let layout = layout(InstaxFilm::Mini);
+
+        assert!(!layout.rotated);
+        assert_eq!((layout.columns, layout.rows), (3, 3));
+        assert_eq!(layout.page_count(9), 1);
+        assert_eq!(layout.page_count(10), 2);
+        // NOTE: 3 frames of 54mm and 2 gaps of 4mm, centered on the 210mm page
+        assert_eq!(layout.slot(0), (20.0, (297.0 - (3.0 * 86.0 + 8.0)) / 2.0));
+        assert_eq!(layout.slot(4).0, 20.0 + 54.0 + 4.0);
+    }
+
+    #[test]
+    fn wide_frames_are_turned_when_more_fit() {
+        let layout = layout(InstaxFilm::Wide);
+
+        assert!(layout.rotated);
+        assert_eq!((layout.frame_width, layout.frame_height), (86.0, 108.0));
+        assert_eq!(layout.per_page(), 4);
+        assert_eq!(layout.slot(3), (17.0 + 86.0 + 4.0, 38.5 + 108.0 + 4.0));
+    }
+
+    #[test]
+    fn no_frame_fits_within_huge_margins() {
+        let layout = SheetLayout::new(
+            &SheetSettings {
+                margin: 120.0,
+                ..SheetSettings::default()
+            },
+            InstaxFilm::Mini,
+        );
+
+        assert_eq!(layout.per_page(), 0);
+        assert_eq!(layout.page_count(3), 0);
+        assert!(layout.crop_marks(3).is_empty());
+    }
+
+    #[test]
+    fn crop_marks_continue_every_edge_into_the_margins() {
+        let layout = layout(InstaxFilm::Mini);
+
+        // NOTE: 2 vertical edges marked above and below, 2 horizontal edges on both sides
+        assert_eq!(layout.crop_marks(1).len(), 8);
+        // NOTE: a full row has 6 vertical edges, a single row 2 horizontal ones
+        assert_eq!(layout.crop_marks(3).len(), 6 * 2 + 2 * 2);
+
+        // NOTE: marks stay in the margins, clear of the frames
+        let (left, top) = layout.slot(0);
+        let (right, bottom) = (left + 3.0 * 54.0 + 2.0 * 4.0, top + 3.0 * 86.0 + 2.0 * 4.0);
+        for [start, end] in layout.crop_marks(9) {
+            for (x, y) in [start, end] {
+                assert!(x < left || x > right || y < top || y > bottom);
+                assert!((0.0..=layout.page_width).contains(&x));
+                assert!((0.0..=layout.page_height).contains(&y));
+            }
+        }
+    }
+
+    #[test]
+    fn png_export_leaves_images_that_fail_blank() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("a.png");
+        RgbaImage::from_pixel(4, 3, Rgba([200, 40, 40, 255]))
+            .save(&image)
+            .unwrap();
+        let missing = dir.path().join("missing.png");
+        let job = SheetJob {
+            entries: vec![
+                (image, Framing::default()),
+                (missing.clone(), Framing::default()),
+            ],
+            date_stamp: false,
+            layout: layout(InstaxFilm::Mini),
+            max_pixels: DEFAULT_MAX_PIXELS,
+        };
+
+        let (first_page, failures) = job.export_png(&dir.path().join("sheet.png"), 1.0).unwrap();
+
+        assert_eq!(first_page, dir.path().join("sheet-1.png"));
+        assert!(image::open(&first_page).is_ok());
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, missing);
+        assert_eq!(
+            job.png_pages(&dir.path().join("sheet.png")),
+            vec![first_page]
+        );
+    }
+}
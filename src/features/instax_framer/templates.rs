@@ -0,0 +1,434 @@
+#[cfg(feature = "config")]
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
+#[cfg(feature = "config")]
+use iced::Task;
+#[cfg(feature = "config")]
+use image::{Pixel, imageops};
+use image::{Rgba, RgbaImage};
+#[cfg(feature = "config")]
+use rfd::{MessageDialog, MessageLevel};
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+
+use crate::utils::HexColor;
+
+#[cfg(feature = "config")]
+use super::{InstaxFramer, InstaxFramerMessage};
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    Stripes,
+    Dots,
+    Checks,
+}
+
+/// How the border around the picture is painted, sizes are in millimeters on the print
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Border {
+    Solid(HexColor),
+    Gradient {
+        from: HexColor,
+        to: HexColor,
+        /// Clockwise degrees, 0 runs from top to bottom
+        #[cfg_attr(feature = "config", serde(default))]
+        angle: f32,
+    },
+    Pattern {
+        pattern: Pattern,
+        background: HexColor,
+        foreground: HexColor,
+        size: f32,
+    },
+    /// A PNG stretched over the whole print, the picture shows through its transparent window
+    #[cfg(feature = "config")]
+    Overlay {
+        /// Relative to the template file
+        image: PathBuf,
+        #[serde(skip)]
+        loaded: Option<Arc<RgbaImage>>,
+    },
+}
+
+/// A named border style, built in or loaded from the templates directory
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameTemplate {
+    pub name: String,
+    pub border: Border,
+}
+
+const CLASSIC_WHITE: HexColor = HexColor([250, 250, 247]);
+
+impl Default for FrameTemplate {
+    fn default() -> Self {
+        Self {
+            name: "Classic white".to_owned(),
+            border: Border::Solid(CLASSIC_WHITE),
+        }
+    }
+}
+
+pub static BUILT_IN_TEMPLATES: LazyLock<Vec<Arc<FrameTemplate>>> = LazyLock::new(|| {
+    [
+        FrameTemplate::default(),
+        FrameTemplate {
+            name: "Classic black".to_owned(),
+            border: Border::Solid(HexColor([24, 24, 26])),
+        },
+        FrameTemplate {
+            name: "Sky blue".to_owned(),
+            border: Border::Solid(HexColor([168, 208, 230])),
+        },
+        FrameTemplate {
+            name: "Sunset".to_owned(),
+            border: Border::Gradient {
+                from: HexColor([255, 196, 140]),
+                to: HexColor([232, 120, 160]),
+                angle: 30.0,
+            },
+        },
+        FrameTemplate {
+            name: "Candy stripes".to_owned(),
+            border: Border::Pattern {
+                pattern: Pattern::Stripes,
+                background: HexColor([255, 244, 246]),
+                foreground: HexColor([244, 170, 190]),
+                size: 3.0,
+            },
+        },
+        FrameTemplate {
+            name: "Polka dots".to_owned(),
+            border: Border::Pattern {
+                pattern: Pattern::Dots,
+                background: HexColor([252, 248, 232]),
+                foreground: HexColor([60, 60, 70]),
+                size: 4.0,
+            },
+        },
+        FrameTemplate {
+            name: "Gingham".to_owned(),
+            border: Border::Pattern {
+                pattern: Pattern::Checks,
+                background: HexColor([250, 250, 247]),
+                foreground: HexColor([214, 226, 240]),
+                size: 2.5,
+            },
+        },
+    ]
+    .into_iter()
+    .map(Arc::new)
+    .collect()
+});
+
+impl FrameTemplate {
+    /// Paints the empty print, before the picture is placed on it
+    pub fn background(&self, width: u32, height: u32, pixels_per_mm: f32) -> RgbaImage {
+        match &self.border {
+            Border::Solid(color) => RgbaImage::from_pixel(width, height, color.rgba()),
+            Border::Gradient { from, to, angle } => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let (half_width, half_height) = (width as f32 / 2.0, height as f32 / 2.0);
+                // NOTE: the projection of the corners, so that the gradient spans the whole print
+                let extent = half_width * sin.abs() + half_height * cos.abs();
+
+                RgbaImage::from_fn(width, height, |x, y| {
+                    let projection = (half_width - x as f32) * sin + (y as f32 - half_height) * cos;
+                    let t = (projection / extent / 2.0 + 0.5).clamp(0.0, 1.0);
+
+                    mix(from.rgba(), to.rgba(), t)
+                })
+            }
+            Border::Pattern {
+                pattern,
+                background,
+                foreground,
+                size,
+            } => {
+                let cell = (size * pixels_per_mm).max(2.0);
+
+                RgbaImage::from_fn(width, height, |x, y| {
+                    let (x, y) = (x as f32 / cell, y as f32 / cell);
+                    let is_foreground = match pattern {
+                        Pattern::Stripes => (x + y).rem_euclid(1.0) < 0.5,
+                        Pattern::Dots => {
+                            let (dx, dy) = (x.fract() - 0.5, y.fract() - 0.5);
+
+                            dx * dx + dy * dy < 0.25 * 0.25
+                        }
+                        Pattern::Checks => (x as u32 + y as u32).is_multiple_of(2),
+                    };
+
+                    if is_foreground {
+                        foreground.rgba()
+                    } else {
+                        background.rgba()
+                    }
+                })
+            }
+            #[cfg(feature = "config")]
+            Border::Overlay { .. } => RgbaImage::from_pixel(width, height, CLASSIC_WHITE.rgba()),
+        }
+    }
+
+    /// Draws the overlay of overlay templates on top of the placed picture
+    #[cfg_attr(not(feature = "config"), allow(unused_variables))]
+    pub fn overlay(&self, frame: &mut RgbaImage) {
+        #[cfg(feature = "config")]
+        if let Border::Overlay {
+            loaded: Some(overlay),
+            ..
+        } = &self.border
+        {
+            // NOTE: stretched to the film's size, so overlays should match its aspect ratio
+            let overlay = imageops::resize(
+                overlay.as_ref(),
+                frame.width(),
+                frame.height(),
+                imageops::FilterType::Triangle,
+            );
+
+            for (pixel, overlay_pixel) in frame.pixels_mut().zip(overlay.pixels()) {
+                pixel.blend(overlay_pixel);
+            }
+        }
+    }
+}
+
+fn mix(from: Rgba<u8>, to: Rgba<u8>, t: f32) -> Rgba<u8> {
+    Rgba(std::array::from_fn(|channel| {
+        (f32::from(from[channel]) * (1.0 - t) + f32::from(to[channel]) * t).round() as u8
+    }))
+}
+
+/// The `templates` directory next to the config file
+#[cfg(feature = "config")]
+pub fn templates_dir() -> Option<PathBuf> {
+    let config_file = confy::get_configuration_file_path(crate::constants::APP_NAME, None).ok()?;
+
+    Some(config_file.parent()?.join("templates"))
+}
+
+/// Reads every `.ron` template of the templates directory, with an error for every broken one.
+/// Without the directory there are no templates, reading never creates it
+#[cfg(feature = "config")]
+pub fn load_templates() -> (Vec<FrameTemplate>, Vec<String>) {
+    let Some(templates_dir) = templates_dir() else {
+        return (vec![], vec![]);
+    };
+
+    let entries = match std::fs::read_dir(&templates_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return (vec![], vec![]),
+        Err(e) => return (vec![], vec![format!("{}: {e}", templates_dir.display())]),
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut templates = vec![];
+    let mut errors = vec![];
+
+    for path in paths {
+        match load_template(&path) {
+            Ok(template) => templates.push(template),
+            Err(e) => errors.push(format!("{}: {e}", path.display())),
+        }
+    }
+
+    (templates, errors)
+}
+
+#[cfg(feature = "config")]
+fn load_template(path: &Path) -> Result<FrameTemplate, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut template: FrameTemplate = ron::from_str(&contents).map_err(|e| e.to_string())?;
+
+    if let Border::Overlay { image, loaded } = &mut template.border {
+        let image_path = path.parent().map_or(image.clone(), |dir| dir.join(&image));
+        let overlay = image::open(&image_path)
+            .map_err(|e| format!("Failed to load overlay {}: {e}", image_path.display()))?;
+
+        *loaded = Some(Arc::new(overlay.to_rgba8()));
+    }
+
+    Ok(template)
+}
+
+#[cfg(feature = "config")]
+impl InstaxFramer {
+    /// Handles the template loading messages
+    pub(super) fn update_templates(
+        &mut self,
+        message: InstaxFramerMessage,
+    ) -> Task<InstaxFramerMessage> {
+        match message {
+            InstaxFramerMessage::TemplatesReloadRequested => return load_templates_task(),
+            InstaxFramerMessage::TemplatesLoaded(templates, errors) => {
+                // NOTE: a template may not shadow a built-in or an earlier one
+                self.user_templates = vec![];
+                let mut errors = errors;
+
+                for template in templates {
+                    if self
+                        .templates()
+                        .any(|existing| existing.name == template.name)
+                    {
+                        errors.push(format!("Duplicate template name {:?}", template.name));
+                    } else {
+                        self.user_templates.push(template);
+                    }
+                }
+
+                if !errors.is_empty() {
+                    let _ = MessageDialog::new()
+                        .set_title("Some frame templates could not be loaded")
+                        .set_description(errors.join("\n"))
+                        .set_level(MessageLevel::Warning)
+                        .show();
+                }
+
+                return self.compose_preview();
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+}
+
+/// Loads the templates off the UI thread
+#[cfg(feature = "config")]
+pub(super) fn load_templates_task() -> Task<InstaxFramerMessage> {
+    Task::future(async {
+        match tokio::task::spawn_blocking(load_templates).await {
+            Ok((templates, errors)) => Some(InstaxFramerMessage::TemplatesLoaded(
+                templates.into_iter().map(Arc::new).collect(),
+                errors,
+            )),
+            Err(e) => {
+                eprintln!("Blocking template loading task panicked or was cancelled: {e:?}");
+                None
+            }
+        }
+    })
+    .and_then(Task::done)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: HexColor = HexColor([0, 0, 0]);
+    const WHITE: HexColor = HexColor([255, 255, 255]);
+
+    fn template(border: Border) -> FrameTemplate {
+        FrameTemplate {
+            name: "Test".to_owned(),
+            border,
+        }
+    }
+
+    #[test]
+    fn gradients_run_from_top_to_bottom_at_0_degrees() {
+        let background = template(Border::Gradient {
+            from: BLACK,
+            to: WHITE,
+            angle: 0.0,
+        })
+        .background(10, 100, 1.0);
+
+        assert!(background.get_pixel(5, 0)[0] < 5);
+        assert_eq!(background.get_pixel(5, 50)[0], 128);
+        assert!(background.get_pixel(5, 99)[0] > 250);
+        assert_eq!(background.get_pixel(0, 30), background.get_pixel(9, 30));
+    }
+
+    #[test]
+    fn patterns_paint_only_their_two_colors() {
+        for pattern in [Pattern::Stripes, Pattern::Dots, Pattern::Checks] {
+            let background = template(Border::Pattern {
+                pattern,
+                background: BLACK,
+                foreground: WHITE,
+                size: 4.0,
+            })
+            .background(16, 16, 1.0);
+
+            assert!(
+                background
+                    .pixels()
+                    .all(|pixel| *pixel == BLACK.rgba() || *pixel == WHITE.rgba())
+            );
+            assert!(background.pixels().any(|pixel| *pixel == WHITE.rgba()));
+        }
+    }
+
+    #[test]
+    fn checks_alternate_by_cell() {
+        let background = template(Border::Pattern {
+            pattern: Pattern::Checks,
+            background: BLACK,
+            foreground: WHITE,
+            size: 4.0,
+        })
+        .background(8, 8, 1.0);
+
+        assert_eq!(*background.get_pixel(0, 0), WHITE.rgba());
+        assert_eq!(*background.get_pixel(4, 0), BLACK.rgba());
+        assert_eq!(*background.get_pixel(4, 4), WHITE.rgba());
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn overlays_load_next_to_their_template_and_show_the_picture_through() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut overlay = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255]));
+        overlay.put_pixel(1, 1, Rgba([0, 0, 0, 0]));
+        overlay.save(dir.path().join("window.png")).unwrap();
+        let path = dir.path().join("window.ron");
+        std::fs::write(
+            &path,
+            r#"(name: "Window", border: Overlay(image: "window.png"))"#,
+        )
+        .unwrap();
+
+        let template = load_template(&path).unwrap();
+        let mut frame = RgbaImage::from_pixel(4, 4, Rgba([200, 0, 0, 255]));
+        template.overlay(&mut frame);
+
+        assert_eq!(*frame.get_pixel(1, 1), Rgba([200, 0, 0, 255]));
+        assert_eq!(*frame.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn missing_overlays_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("window.ron");
+        std::fs::write(
+            &path,
+            r#"(name: "Window", border: Overlay(image: "missing.png"))"#,
+        )
+        .unwrap();
+
+        let error = load_template(&path).unwrap_err();
+
+        assert!(error.starts_with("Failed to load overlay"), "{error}");
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn reads_colors_as_hex() {
+        let template: FrameTemplate =
+            ron::from_str(r##"(name: "Mint", border: Solid("#c8f0dc"))"##).unwrap();
+
+        assert_eq!(template.border, Border::Solid(HexColor([0xc8, 0xf0, 0xdc])));
+    }
+}
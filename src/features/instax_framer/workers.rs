@@ -0,0 +1,37 @@
+use std::{num::NonZero, sync::Arc};
+
+use iced::futures::{Stream, StreamExt, stream};
+
+/// Every worker may hold a full resolution image, so keep memory in check
+const MAX_WORKERS: usize = 4;
+
+/// Runs the work for every item on a bounded pool of blocking threads, yielding each item with
+/// its result as they finish
+pub fn run_blocking<T, R>(
+    items: Vec<T>,
+    work: impl Fn(T) -> R + Send + Sync + 'static,
+) -> impl Stream<Item = (T, Result<R, String>)>
+where
+    T: Clone + Send + 'static,
+    R: Send + 'static,
+{
+    let workers = std::thread::available_parallelism()
+        .map_or(2, NonZero::get)
+        .min(MAX_WORKERS);
+    let work = Arc::new(work);
+
+    stream::iter(items)
+        .map(move |item| {
+            let work = work.clone();
+
+            async move {
+                let input = item.clone();
+                let result = tokio::task::spawn_blocking(move || work(input))
+                    .await
+                    .map_err(|e| format!("Worker panicked or was cancelled: {e}"));
+
+                (item, result)
+            }
+        })
+        .buffer_unordered(workers)
+}
@@ -6,5 +6,9 @@ pub mod counter;
 pub mod instax_framer;
 #[cfg(feature = "omni_themes")]
 pub mod omni_themes;
+#[cfg(feature = "single_instance")]
+pub mod single_instance;
 #[cfg(feature = "system_info")]
 pub mod system_info;
+#[cfg(feature = "web_server")]
+pub mod web_server;
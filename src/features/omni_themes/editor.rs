@@ -0,0 +1,265 @@
+use super::{SerializableTheme, ThemePalette};
+use crate::utils::HexColor;
+
+/// Edits kept for undo, the oldest are dropped beyond it
+const MAX_UNDO: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteSlot {
+    Background,
+    Text,
+    Primary,
+    Success,
+    Danger,
+    Warning,
+}
+
+pub static PALETTE_SLOTS: &[PaletteSlot] = &[
+    PaletteSlot::Background,
+    PaletteSlot::Text,
+    PaletteSlot::Primary,
+    PaletteSlot::Success,
+    PaletteSlot::Danger,
+    PaletteSlot::Warning,
+];
+
+impl std::fmt::Display for PaletteSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaletteSlot::Background => f.write_str("Background"),
+            PaletteSlot::Text => f.write_str("Text"),
+            PaletteSlot::Primary => f.write_str("Primary"),
+            PaletteSlot::Success => f.write_str("Success"),
+            PaletteSlot::Danger => f.write_str("Danger"),
+            PaletteSlot::Warning => f.write_str("Warning"),
+        }
+    }
+}
+
+impl ThemePalette {
+    pub fn get(&self, slot: PaletteSlot) -> HexColor {
+        match slot {
+            PaletteSlot::Background => self.background,
+            PaletteSlot::Text => self.text,
+            PaletteSlot::Primary => self.primary,
+            PaletteSlot::Success => self.success,
+            PaletteSlot::Danger => self.danger,
+            PaletteSlot::Warning => self.warning,
+        }
+    }
+
+    pub fn set(&mut self, slot: PaletteSlot, color: HexColor) {
+        match slot {
+            PaletteSlot::Background => self.background = color,
+            PaletteSlot::Text => self.text = color,
+            PaletteSlot::Primary => self.primary = color,
+            PaletteSlot::Success => self.success = color,
+            PaletteSlot::Danger => self.danger = color,
+            PaletteSlot::Warning => self.warning = color,
+        }
+    }
+}
+
+/// A palette being edited, previewed on the whole app until the editor is closed
+#[derive(Debug, Clone)]
+pub struct ThemeEditor {
+    pub name: String,
+    pub palette: ThemePalette,
+    /// The palette the editor started from, restored by revert
+    pub original: ThemePalette,
+    /// Palettes before each edit, the latest last
+    history: Vec<ThemePalette>,
+    pub slot: PaletteSlot,
+    /// The hex field of every slot as typed, possibly not a color yet
+    pub hex_inputs: Vec<String>,
+    /// A slider is held, its moves are one edit until released
+    dragging: bool,
+    /// Why the theme was not saved, shown until the next save
+    pub error: Option<String>,
+}
+
+impl ThemeEditor {
+    /// Starts from a copy of the theme, keeping the name of a user theme to save over it
+    pub fn new(theme: &SerializableTheme) -> ThemeEditor {
+        let name = match theme {
+            SerializableTheme::Custom(custom) => custom.name.clone(),
+            theme => format!("{theme} copy"),
+        };
+        let palette = ThemePalette::from(iced::Theme::from(theme.clone()).palette());
+
+        Self {
+            name,
+            palette,
+            original: palette,
+            history: vec![],
+            slot: PaletteSlot::Background,
+            hex_inputs: hex_inputs(&palette),
+            dragging: false,
+            error: None,
+        }
+    }
+
+    /// Sets the slot's color, a held slider only recording the first move for undo
+    pub fn edit(&mut self, slot: PaletteSlot, color: HexColor) {
+        if self.palette.get(slot) == color {
+            return;
+        }
+
+        if !self.dragging {
+            self.remember();
+        }
+        self.palette.set(slot, color);
+        self.hex_inputs[slot as usize] = color.to_string();
+    }
+
+    pub fn drag(&mut self, slot: PaletteSlot, color: HexColor) {
+        self.edit(slot, color);
+        self.dragging = true;
+    }
+
+    pub fn release(&mut self) {
+        self.dragging = false;
+    }
+
+    /// Keeps the text as typed, applying it once it is a whole color
+    pub fn type_hex(&mut self, slot: PaletteSlot, hex: String) {
+        if let Ok(color) = hex.parse() {
+            self.edit(slot, color);
+        }
+
+        self.hex_inputs[slot as usize] = hex;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(palette) = self.history.pop() {
+            self.set_palette(palette);
+        }
+    }
+
+    /// Back to the starting palette, itself undoable
+    pub fn revert(&mut self) {
+        if self.palette != self.original {
+            self.remember();
+            self.set_palette(self.original);
+        }
+    }
+
+    fn remember(&mut self) {
+        if self.history.len() == MAX_UNDO {
+            self.history.remove(0);
+        }
+
+        self.history.push(self.palette);
+    }
+
+    fn set_palette(&mut self, palette: ThemePalette) {
+        self.palette = palette;
+        self.hex_inputs = hex_inputs(&palette);
+        self.dragging = false;
+    }
+}
+
+fn hex_inputs(palette: &ThemePalette) -> Vec<String> {
+    PALETTE_SLOTS
+        .iter()
+        .map(|&slot| palette.get(slot).to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::omni_themes::CustomTheme;
+
+    fn gray(value: u8) -> HexColor {
+        HexColor([value; 3])
+    }
+
+    #[test]
+    fn copies_built_in_themes_and_keeps_user_theme_names() {
+        let copy = ThemeEditor::new(&SerializableTheme::Nord);
+        assert_eq!(copy.name, "Nord copy");
+        assert_eq!(copy.palette, copy.original);
+
+        let custom = CustomTheme {
+            name: "Mine".to_owned(),
+            palette: copy.palette,
+        };
+        assert_eq!(
+            ThemeEditor::new(&SerializableTheme::Custom(custom)).name,
+            "Mine"
+        );
+    }
+
+    #[test]
+    fn a_held_slider_is_one_edit() {
+        let mut editor = ThemeEditor::new(&SerializableTheme::Light);
+
+        for value in [10, 20, 30] {
+            editor.drag(PaletteSlot::Primary, gray(value));
+        }
+        editor.release();
+        editor.drag(PaletteSlot::Primary, gray(40));
+        editor.release();
+
+        assert_eq!(editor.palette.primary, gray(40));
+        editor.undo();
+        assert_eq!(editor.palette.primary, gray(30));
+        editor.undo();
+        assert_eq!(editor.palette, editor.original);
+        assert!(!editor.can_undo());
+    }
+
+    #[test]
+    fn typed_hex_applies_once_it_is_a_color() {
+        let mut editor = ThemeEditor::new(&SerializableTheme::Light);
+
+        editor.type_hex(PaletteSlot::Text, "#12".to_owned());
+        assert_eq!(editor.palette, editor.original);
+        assert_eq!(editor.hex_inputs[PaletteSlot::Text as usize], "#12");
+
+        editor.type_hex(PaletteSlot::Text, "#121212".to_owned());
+        assert_eq!(editor.palette.text, gray(0x12));
+
+        editor.undo();
+        assert_eq!(editor.palette, editor.original);
+        assert_eq!(
+            editor.hex_inputs[PaletteSlot::Text as usize],
+            editor.original.text.to_string()
+        );
+    }
+
+    #[test]
+    fn reverting_can_be_undone() {
+        let mut editor = ThemeEditor::new(&SerializableTheme::Light);
+
+        editor.revert();
+        assert!(!editor.can_undo());
+
+        editor.edit(PaletteSlot::Danger, gray(1));
+        editor.revert();
+        assert_eq!(editor.palette, editor.original);
+
+        editor.undo();
+        assert_eq!(editor.palette.danger, gray(1));
+    }
+
+    #[test]
+    fn only_the_latest_edits_are_undone() {
+        let mut editor = ThemeEditor::new(&SerializableTheme::Light);
+
+        for value in 0..=MAX_UNDO as u8 {
+            editor.edit(PaletteSlot::Warning, gray(value));
+        }
+        for _ in 0..MAX_UNDO {
+            editor.undo();
+        }
+
+        assert_eq!(editor.palette.warning, gray(0));
+        assert!(!editor.can_undo());
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use iced::{
+    futures::{SinkExt, Stream, channel::mpsc},
+    stream,
+};
+
+use super::CustomTheme;
+
+pub const THEME_EXTENSION: &str = "ron";
+/// How often the themes directory is checked for changed files
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Themes read from the directory, with the file of each, and an error for every broken file
+pub type LoadedThemes = (Vec<(PathBuf, CustomTheme)>, Vec<String>);
+
+/// The `themes` directory next to the config file
+pub fn themes_dir() -> Option<PathBuf> {
+    let config_file = confy::get_configuration_file_path(crate::constants::APP_NAME, None).ok()?;
+
+    Some(config_file.parent()?.join("themes"))
+}
+
+/// Reads every `.ron` theme of the themes directory, sorted by file name
+pub fn load_themes() -> LoadedThemes {
+    let Some(themes_dir) = themes_dir() else {
+        return (vec![], vec![]);
+    };
+
+    // NOTE: created up front, so that users find where to put their themes
+    let entries =
+        match std::fs::create_dir_all(&themes_dir).and_then(|()| std::fs::read_dir(&themes_dir)) {
+            Ok(entries) => entries,
+            Err(e) => return (vec![], vec![format!("{}: {e}", themes_dir.display())]),
+        };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_theme_file(path))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut themes = vec![];
+    let mut errors = vec![];
+
+    for path in paths {
+        match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| ron::from_str(&contents).map_err(|e| e.to_string()))
+        {
+            Ok(theme) => themes.push((path, theme)),
+            Err(e) => errors.push(format!("{}: {e}", path.display())),
+        }
+    }
+
+    (themes, errors)
+}
+
+/// The file a theme is written to when it has none yet
+pub fn default_path(name: &str) -> Option<PathBuf> {
+    Some(themes_dir()?.join(format!("{}.{THEME_EXTENSION}", file_stem(name))))
+}
+
+/// Writes the theme to its file, or to a new one named after it
+pub fn write(theme: &CustomTheme, path: Option<&Path>) -> Result<PathBuf, String> {
+    let path = match path {
+        Some(path) => path.to_owned(),
+        None => unused_path(
+            &themes_dir().ok_or("No config directory to save the theme to")?,
+            &theme.name,
+        ),
+    };
+    let contents = ron::ser::to_string_pretty(theme, ron::ser::PrettyConfig::default())
+        .map_err(|e| e.to_string())?;
+
+    std::fs::create_dir_all(path.parent().unwrap_or(&path))
+        .and_then(|()| std::fs::write(&path, contents))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Reloads the themes whenever a file of the directory is added, changed or removed, starting
+/// with a first load
+pub fn watch() -> impl Stream<Item = LoadedThemes> {
+    stream::channel(1, async |mut output: mpsc::Sender<LoadedThemes>| {
+        let mut last_snapshot = None;
+
+        loop {
+            let previous_snapshot = last_snapshot.take();
+            let is_first = previous_snapshot.is_none();
+
+            let Ok((snapshot, loaded)) = tokio::task::spawn_blocking(move || {
+                if !is_first {
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+
+                let snapshot = snapshot();
+                let loaded = (previous_snapshot.as_ref() != Some(&snapshot)).then(load_themes);
+
+                (snapshot, loaded)
+            })
+            .await
+            else {
+                return;
+            };
+
+            last_snapshot = Some(snapshot);
+
+            if let Some(loaded) = loaded
+                && output.send(loaded).await.is_err()
+            {
+                return;
+            }
+        }
+    })
+}
+
+/// Every theme file with its modification time and size, which change on every save
+fn snapshot() -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+    let Some(entries) = themes_dir().and_then(|themes_dir| std::fs::read_dir(themes_dir).ok())
+    else {
+        return vec![];
+    };
+
+    let mut snapshot = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_theme_file(path))
+        .map(|path| {
+            let metadata = std::fs::metadata(&path).ok();
+
+            (
+                path,
+                metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.modified().ok()),
+                metadata.map_or(0, |metadata| metadata.len()),
+            )
+        })
+        .collect::<Vec<_>>();
+    snapshot.sort();
+
+    snapshot
+}
+
+/// The file named after the theme in `dir`, numbered when another theme's name maps to the same
+/// file
+fn unused_path(dir: &Path, name: &str) -> PathBuf {
+    let stem = file_stem(name);
+    let mut path = dir.join(format!("{stem}.{THEME_EXTENSION}"));
+    let mut number = 1;
+
+    while path.exists() {
+        number += 1;
+        path = dir.join(format!("{stem} {number}.{THEME_EXTENSION}"));
+    }
+
+    path
+}
+
+fn is_theme_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == THEME_EXTENSION)
+}
+
+/// The theme's name without the characters file systems refuse
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|character| {
+            if character.is_alphanumeric() || matches!(character, ' ' | '-' | '_') {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if stem.is_empty() {
+        "theme".to_owned()
+    } else {
+        stem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::theme::Palette;
+
+    use super::*;
+    use crate::features::omni_themes::ThemePalette;
+
+    #[test]
+    fn file_stems_keep_only_safe_characters() {
+        assert_eq!(file_stem("  Tokyo Night-2_b "), "Tokyo Night-2_b");
+        assert_eq!(file_stem("a/b\\c:d*e?.ron"), "a_b_c_d_e__ron");
+        assert_eq!(file_stem("Café"), "Café");
+        assert_eq!(file_stem("   "), "theme");
+    }
+
+    #[test]
+    fn unused_paths_are_numbered() {
+        let dir = tempfile::tempdir().unwrap();
+
+        assert_eq!(unused_path(dir.path(), "Mine"), dir.path().join("Mine.ron"));
+
+        std::fs::write(dir.path().join("Mine.ron"), "").unwrap();
+        std::fs::write(dir.path().join("Mine 2.ron"), "").unwrap();
+        assert_eq!(
+            unused_path(dir.path(), "Mine"),
+            dir.path().join("Mine 3.ron")
+        );
+        assert_eq!(
+            unused_path(dir.path(), "Mine?"),
+            dir.path().join("Mine_.ron")
+        );
+    }
+
+    #[test]
+    fn written_themes_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("Mine.ron");
+        let theme = CustomTheme {
+            name: "Mine".to_owned(),
+            palette: ThemePalette::from(Palette::DARK),
+        };
+
+        assert_eq!(write(&theme, Some(&path)), Ok(path.clone()));
+        assert!(is_theme_file(&path));
+        assert_eq!(
+            ron::from_str::<CustomTheme>(&std::fs::read_to_string(&path).unwrap()),
+            Ok(theme)
+        );
+    }
+}
@@ -0,0 +1,477 @@
+use std::{collections::BTreeMap, path::Path};
+
+use iced::theme::Palette;
+
+use super::{CustomTheme, PaletteSlot, ThemePalette};
+use crate::utils::HexColor;
+
+pub static IMPORT_EXTENSIONS: &[&str] = &["yaml", "yml", "toml", "conf", "json"];
+
+/// Reads a color scheme, picking its format by the file extension
+pub fn import(path: &Path) -> Result<CustomTheme, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let fallback_name = path
+        .file_stem()
+        .map_or("Imported".into(), |file_stem| file_stem.to_string_lossy());
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "yaml" | "yml" => base16(&contents, &fallback_name),
+        "toml" => alacritty(&contents, &fallback_name),
+        "conf" => kitty(&contents, &fallback_name),
+        "json" => vscode(&contents, &fallback_name),
+        _ => Err(format!(
+            "Unknown color scheme format, expected one of {}",
+            IMPORT_EXTENSIONS.join("/")
+        )),
+    }
+}
+
+/// base16 schemes: `base00` is the background, `base05` the text, `base08` to `base0D` the
+/// accents from red to blue
+pub fn base16(contents: &str, fallback_name: &str) -> Result<CustomTheme, String> {
+    let values: BTreeMap<String, String> = contents
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+
+            Some((key.trim().to_owned(), unquote(strip_comment(value, '#'))))
+        })
+        .collect();
+
+    let name = values
+        .get("scheme")
+        .or_else(|| values.get("name"))
+        .filter(|name| !name.is_empty())
+        .map_or(fallback_name, String::as_str);
+
+    theme(name, |slot| {
+        let key = match slot {
+            PaletteSlot::Background => "base00",
+            PaletteSlot::Text => "base05",
+            PaletteSlot::Primary => "base0D",
+            PaletteSlot::Success => "base0B",
+            PaletteSlot::Danger => "base08",
+            PaletteSlot::Warning => "base0A",
+        };
+
+        values.get(key).map(String::as_str)
+    })
+}
+
+/// Alacritty's `[colors.primary]` and `[colors.normal]` tables
+pub fn alacritty(contents: &str, fallback_name: &str) -> Result<CustomTheme, String> {
+    let mut values = BTreeMap::new();
+    let mut table = String::new();
+
+    for line in contents.lines() {
+        let line = strip_comment(line, '#');
+
+        if let Some(header) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            table = header.trim().to_owned();
+        } else if let Some((key, value)) = line.split_once('=') {
+            values.insert(format!("{table}.{}", key.trim()), unquote(value));
+        }
+    }
+
+    theme(fallback_name, |slot| {
+        let key = match slot {
+            PaletteSlot::Background => "colors.primary.background",
+            PaletteSlot::Text => "colors.primary.foreground",
+            PaletteSlot::Primary => "colors.normal.blue",
+            PaletteSlot::Success => "colors.normal.green",
+            PaletteSlot::Danger => "colors.normal.red",
+            PaletteSlot::Warning => "colors.normal.yellow",
+        };
+
+        values.get(key).map(String::as_str)
+    })
+}
+
+/// Kitty's `key value` lines, named by a `## name:` comment when there is one
+pub fn kitty(contents: &str, fallback_name: &str) -> Result<CustomTheme, String> {
+    let name = contents
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("## name:"))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(fallback_name);
+
+    let values: BTreeMap<&str, &str> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once(char::is_whitespace)?;
+
+            Some((key, value.trim()))
+        })
+        .collect();
+
+    theme(name, |slot| {
+        let key = match slot {
+            PaletteSlot::Background => "background",
+            PaletteSlot::Text => "foreground",
+            PaletteSlot::Primary => "color4",
+            PaletteSlot::Success => "color2",
+            PaletteSlot::Danger => "color1",
+            PaletteSlot::Warning => "color3",
+        };
+
+        values.get(key).copied()
+    })
+}
+
+/// VS Code's workbench `colors`, falling back to the terminal colors for the accents
+pub fn vscode(contents: &str, fallback_name: &str) -> Result<CustomTheme, String> {
+    let values = string_pairs(contents);
+
+    let name = values
+        .get("name")
+        .filter(|name| !name.is_empty())
+        .map_or(fallback_name, String::as_str);
+
+    theme(name, |slot| {
+        let keys: &[&str] = match slot {
+            PaletteSlot::Background => &["editor.background", "background"],
+            PaletteSlot::Text => &["editor.foreground", "foreground"],
+            PaletteSlot::Primary => &["button.background", "focusBorder", "terminal.ansiBlue"],
+            PaletteSlot::Success => &[
+                "terminal.ansiGreen",
+                "gitDecoration.addedResourceForeground",
+            ],
+            PaletteSlot::Danger => &["errorForeground", "terminal.ansiRed"],
+            PaletteSlot::Warning => &["editorWarning.foreground", "terminal.ansiYellow"],
+        };
+
+        keys.iter()
+            .find_map(|key| values.get(*key).map(String::as_str))
+    })
+}
+
+/// Builds the theme from the scheme's colors, the background and text are required while
+/// missing accents are taken from iced's light or dark palette
+fn theme<'a>(
+    name: &str,
+    lookup: impl Fn(PaletteSlot) -> Option<&'a str>,
+) -> Result<CustomTheme, String> {
+    let color = |slot| {
+        lookup(slot)
+            .map(|value| {
+                parse_color(value).ok_or_else(|| format!("{slot} is not a color: {value}"))
+            })
+            .transpose()
+    };
+
+    let background = color(PaletteSlot::Background)?.ok_or("No background color found")?;
+    let text = color(PaletteSlot::Text)?.ok_or("No text color found")?;
+
+    let mut custom = CustomTheme {
+        name: name.trim().to_owned(),
+        palette: ThemePalette::from(Palette::LIGHT),
+    };
+    custom.palette.background = background;
+    if custom.is_dark() {
+        custom.palette = ThemePalette::from(Palette::DARK);
+        custom.palette.background = background;
+    }
+
+    custom.palette.text = text;
+    for slot in [
+        PaletteSlot::Primary,
+        PaletteSlot::Success,
+        PaletteSlot::Danger,
+        PaletteSlot::Warning,
+    ] {
+        if let Some(accent) = color(slot)? {
+            custom.palette.set(slot, accent);
+        }
+    }
+
+    Ok(custom)
+}
+
+/// Hex colors as the schemes write them, `#rgb` and an alpha channel included
+fn parse_color(value: &str) -> Option<HexColor> {
+    let digits = value.trim();
+    let digits = digits
+        .strip_prefix('#')
+        .or_else(|| digits.strip_prefix("0x"))
+        .unwrap_or(digits);
+
+    match digits.len() {
+        3 if digits.is_ascii() => {
+            let expanded: String = digits.chars().flat_map(|digit| [digit, digit]).collect();
+
+            expanded.parse().ok()
+        }
+        // NOTE: the alpha channel is dropped, palettes are opaque
+        6 | 8 if digits.is_ascii() => digits[..6].parse().ok(),
+        _ => None,
+    }
+}
+
+fn strip_comment(line: &str, marker: char) -> &str {
+    // NOTE: a marker inside quotes, like a quoted `#rrggbb`, does not start a comment
+    let mut quote = None;
+
+    for (index, character) in line.char_indices() {
+        match (quote, character) {
+            (None, '"' | '\'') => quote = Some(character),
+            (Some(open), _) if character == open => quote = None,
+            (None, _) if character == marker => return line[..index].trim(),
+            _ => {}
+        }
+    }
+
+    line.trim()
+}
+
+fn unquote(value: &str) -> String {
+    value
+        .trim()
+        .trim_end_matches(',')
+        .trim_matches(|character| character == '"' || character == '\'')
+        .to_owned()
+}
+
+/// Every `"key": "value"` pair, wherever it is nested. VS Code themes are JSON with comments
+/// and trailing commas, which strict parsers refuse
+fn string_pairs(contents: &str) -> BTreeMap<String, String> {
+    let mut strings = vec![];
+    let mut characters = contents.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '"' => {
+                let mut string = String::new();
+
+                while let Some(character) = characters.next() {
+                    match character {
+                        '\\' => string.extend(characters.next()),
+                        '"' => break,
+                        _ => string.push(character),
+                    }
+                }
+
+                // NOTE: a key is a string followed by a colon
+                while characters
+                    .next_if(|character| character.is_whitespace())
+                    .is_some()
+                {}
+                strings.push((string, characters.next_if_eq(&':').is_some()));
+            }
+            '/' if characters.next_if_eq(&'/').is_some() => {
+                while characters.next_if(|&character| character != '\n').is_some() {}
+            }
+            '/' if characters.next_if_eq(&'*').is_some() => {
+                while let Some(character) = characters.next() {
+                    if character == '*' && characters.next_if_eq(&'/').is_some() {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    strings
+        .windows(2)
+        .filter_map(|pair| match pair {
+            [(key, true), (value, false)] => Some((key.clone(), value.clone())),
+            _ => None,
+        })
+        .rev()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE16: &str = r##"
+scheme: "Ocean" # base16
+author: "Chris Kempson"
+base00: "#2b303b" # background
+base05: "c0c5ce"
+base08: "bf616a"
+base0A: "ebcb8b"
+base0B: "a3be8c"
+base0D: "8fa1b3"
+"##;
+
+    const ALACRITTY: &str = r##"
+# Tomorrow Night
+[colors.primary]
+background = "#1d1f21" # the window
+foreground = '0xc5c8c6'
+
+[colors.normal]
+red = "0xcc6666"
+green = "#b5bd68"
+"##;
+
+    const KITTY: &str = "
+## name: Paper
+# a light theme
+background #f2eede
+foreground   #000000
+color1 #cc3e28
+color4 #1e6fcc
+";
+
+    const VSCODE: &str = r##"
+// Exported from the marketplace
+{
+  "name": "Night Owl", /* "name": "Not this one" */
+  "type": "dark",
+  "colors": {
+    "editor.background": "#011627",
+    "editor.foreground": "#d6deeb", // the text
+    "terminal.ansiGreen": "#22da6e",
+  },
+  "tokenColors": [
+    {
+      "scope": "comment",
+      "settings": { "foreground": "#637777", },
+    },
+  ],
+}
+"##;
+
+    fn color(hex: &str) -> HexColor {
+        hex.parse().unwrap()
+    }
+
+    #[test]
+    fn imports_base16() {
+        let custom = base16(BASE16, "ocean").unwrap();
+
+        assert_eq!(custom.name, "Ocean");
+        assert!(custom.is_dark());
+        assert_eq!(custom.palette.background, color("#2b303b"));
+        assert_eq!(custom.palette.text, color("#c0c5ce"));
+        assert_eq!(custom.palette.primary, color("#8fa1b3"));
+        assert_eq!(custom.palette.success, color("#a3be8c"));
+        assert_eq!(custom.palette.danger, color("#bf616a"));
+        assert_eq!(custom.palette.warning, color("#ebcb8b"));
+    }
+
+    #[test]
+    fn imports_alacritty() {
+        let custom = alacritty(ALACRITTY, "tomorrow").unwrap();
+        let dark = ThemePalette::from(Palette::DARK);
+
+        assert_eq!(custom.name, "tomorrow");
+        assert!(custom.is_dark());
+        assert_eq!(custom.palette.background, color("#1d1f21"));
+        assert_eq!(custom.palette.text, color("#c5c8c6"));
+        assert_eq!(custom.palette.danger, color("#cc6666"));
+        assert_eq!(custom.palette.success, color("#b5bd68"));
+        assert_eq!(custom.palette.primary, dark.primary);
+        assert_eq!(custom.palette.warning, dark.warning);
+    }
+
+    #[test]
+    fn imports_kitty() {
+        let custom = kitty(KITTY, "paper").unwrap();
+        let light = ThemePalette::from(Palette::LIGHT);
+
+        assert_eq!(custom.name, "Paper");
+        assert!(!custom.is_dark());
+        assert_eq!(custom.palette.background, color("#f2eede"));
+        assert_eq!(custom.palette.text, color("#000000"));
+        assert_eq!(custom.palette.danger, color("#cc3e28"));
+        assert_eq!(custom.palette.primary, color("#1e6fcc"));
+        assert_eq!(custom.palette.success, light.success);
+    }
+
+    #[test]
+    fn imports_vscode_json_with_comments() {
+        let custom = vscode(VSCODE, "night-owl").unwrap();
+
+        assert_eq!(custom.name, "Night Owl");
+        assert!(custom.is_dark());
+        assert_eq!(custom.palette.background, color("#011627"));
+        assert_eq!(custom.palette.text, color("#d6deeb"));
+        assert_eq!(custom.palette.success, color("#22da6e"));
+    }
+
+    #[test]
+    fn refuses_schemes_without_a_background_or_text() {
+        assert_eq!(
+            kitty("foreground #000000", "a").unwrap_err(),
+            "No background color found"
+        );
+        assert_eq!(
+            kitty("background #ffffff", "a").unwrap_err(),
+            "No text color found"
+        );
+        assert_eq!(
+            kitty("background #ffffff\nforeground #000000\ncolor4 blue", "a").unwrap_err(),
+            "Primary is not a color: blue"
+        );
+    }
+
+    #[test]
+    fn picks_the_format_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paper.conf");
+        std::fs::write(&path, KITTY).unwrap();
+        let unknown = dir.path().join("paper.txt");
+        std::fs::write(&unknown, KITTY).unwrap();
+
+        assert_eq!(import(&path).unwrap(), kitty(KITTY, "paper").unwrap());
+        assert!(import(&unknown).is_err());
+    }
+
+    #[test]
+    fn string_pairs_skips_comments_and_unescapes() {
+        let pairs = string_pairs(
+            r#"{
+              "a": "1", // "b": "2"
+              "c" : "3", /* "d": "4" */
+              "e\"f": "5",
+              "g": ["h", "i"],
+              "j": { "k": "6" },
+            }"#,
+        );
+
+        assert_eq!(pairs.get("a").map(String::as_str), Some("1"));
+        assert_eq!(pairs.get("c").map(String::as_str), Some("3"));
+        assert_eq!(pairs.get("e\"f").map(String::as_str), Some("5"));
+        assert_eq!(pairs.get("k").map(String::as_str), Some("6"));
+        assert!(!pairs.contains_key("b"));
+        assert!(!pairs.contains_key("d"));
+        assert!(!pairs.contains_key("j"));
+    }
+
+    #[test]
+    fn strip_comment_keeps_quoted_markers() {
+        assert_eq!(
+            strip_comment(r##" "#1d1f21" # dark"##, '#'),
+            r##""#1d1f21""##
+        );
+        assert_eq!(strip_comment("'a#b' c # d", '#'), "'a#b' c");
+        assert_eq!(strip_comment("# only a comment", '#'), "");
+        assert_eq!(strip_comment(" no comment ", '#'), "no comment");
+    }
+
+    #[test]
+    fn parses_scheme_colors() {
+        assert_eq!(parse_color("#1d1f21"), Some(color("#1d1f21")));
+        assert_eq!(parse_color("0x1D1F21"), Some(color("#1d1f21")));
+        assert_eq!(parse_color(" 1d1f21 "), Some(color("#1d1f21")));
+        assert_eq!(parse_color("#abc"), Some(color("#aabbcc")));
+        assert_eq!(parse_color("#1d1f21ff"), Some(color("#1d1f21")));
+        assert_eq!(parse_color("#1d1f2"), None);
+        assert_eq!(parse_color("blue"), None);
+        assert_eq!(parse_color("#ééé"), None);
+    }
+}
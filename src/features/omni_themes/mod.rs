@@ -0,0 +1,1006 @@
+mod editor;
+#[cfg(feature = "config")]
+mod files;
+mod import;
+
+#[cfg(feature = "config")]
+use std::{collections::BTreeMap, path::PathBuf};
+
+use iced::{
+    Alignment, Color, Element, Length, Subscription, Task, Theme, border, theme, theme::Palette,
+    widget,
+};
+use rfd::FileDialog;
+#[cfg(feature = "config")]
+use serde::{Deserialize, Serialize};
+
+use crate::utils::HexColor;
+
+pub use editor::{PALETTE_SLOTS, PaletteSlot, ThemeEditor};
+pub use import::IMPORT_EXTENSIONS;
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Clone, Hash)]
+pub enum OmniThemeMode {
+    SystemDefault,
+    Dark,
+    Light,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum OmniThemesMessage {
+    ChangeThemeMode(OmniThemeMode),
+    ChangeSystemThemeMode(theme::Mode),
+    SwitchLightTheme(SerializableTheme),
+    SwitchDarkTheme(SerializableTheme),
+    /// Selects a built-in or user theme by name, along with its mode, once the theme files are
+    /// read
+    ThemeRequested(String),
+    EditorToggled,
+    /// Starts over from a copy of the theme
+    EditorStartedFrom(SerializableTheme),
+    EditorSlotSelected(PaletteSlot),
+    EditorColorDragged(PaletteSlot, HexColor),
+    EditorColorReleased,
+    EditorHexEdited(PaletteSlot, String),
+    EditorNameEdited(String),
+    EditorUndone,
+    EditorReverted,
+    EditorSaved,
+    /// The preview's widgets do nothing
+    PreviewTouched,
+    ImportRequested,
+    /// The themes directory was read again, with an error for every broken file
+    #[cfg(feature = "config")]
+    ThemeFilesLoaded(Vec<(PathBuf, CustomTheme)>, Vec<String>),
+    BannerDismissed,
+    CriticalStateChanged,
+}
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Debug, Clone, Hash)]
+pub enum SerializableTheme {
+    Light,
+    Dark,
+    Dracula,
+    Nord,
+    SolarizedLight,
+    SolarizedDark,
+    GruvboxLight,
+    GruvboxDark,
+    CatppuccinLatte,
+    CatppuccinFrappe,
+    CatppuccinMacchiato,
+    CatppuccinMocha,
+    TokyoNight,
+    TokyoNightStorm,
+    TokyoNightLight,
+    KanagawaWave,
+    KanagawaDragon,
+    KanagawaLotus,
+    Moonfly,
+    Nightfly,
+    Oxocarbon,
+    Ferra,
+    /// A user theme, carrying its whole palette so that it applies even once deleted
+    Custom(CustomTheme),
+}
+
+/// A named palette, listed with the light or dark themes by its background
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Debug, Clone, Hash)]
+pub struct CustomTheme {
+    pub name: String,
+    pub palette: ThemePalette,
+}
+
+impl CustomTheme {
+    pub fn is_dark(&self) -> bool {
+        // NOTE: below this luminance, white text contrasts more than black text
+        Color::from(self.palette.background).relative_luminance() < 0.179
+    }
+}
+
+/// The colors iced derives every widget style from
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub struct ThemePalette {
+    pub background: HexColor,
+    pub text: HexColor,
+    pub primary: HexColor,
+    pub success: HexColor,
+    pub danger: HexColor,
+    pub warning: HexColor,
+}
+
+impl From<Palette> for ThemePalette {
+    fn from(palette: Palette) -> Self {
+        Self {
+            background: palette.background.into(),
+            text: palette.text.into(),
+            primary: palette.primary.into(),
+            success: palette.success.into(),
+            danger: palette.danger.into(),
+            warning: palette.warning.into(),
+        }
+    }
+}
+
+impl From<ThemePalette> for Palette {
+    fn from(palette: ThemePalette) -> Self {
+        Self {
+            background: palette.background.into(),
+            text: palette.text.into(),
+            primary: palette.primary.into(),
+            success: palette.success.into(),
+            danger: palette.danger.into(),
+            warning: palette.warning.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for SerializableTheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SerializableTheme::Dark => f.write_str("Dark"),
+            SerializableTheme::Light => f.write_str("Light"),
+            SerializableTheme::Dracula => f.write_str("Dracula"),
+            SerializableTheme::Nord => f.write_str("Nord"),
+            SerializableTheme::SolarizedLight => f.write_str("Solarized Light"),
+            SerializableTheme::SolarizedDark => f.write_str("Solarized Dark"),
+            SerializableTheme::GruvboxLight => f.write_str("Gruvbox Light"),
+            SerializableTheme::GruvboxDark => f.write_str("Gruvbox Dark"),
+            SerializableTheme::CatppuccinLatte => f.write_str("Catppuccin Latte"),
+            SerializableTheme::CatppuccinFrappe => f.write_str("Catppuccin Frappe"),
+            SerializableTheme::CatppuccinMacchiato => f.write_str("Catppuccin Macchiato"),
+            SerializableTheme::CatppuccinMocha => f.write_str("Catppuccin Mocha"),
+            SerializableTheme::TokyoNight => f.write_str("Tokyo Night"),
+            SerializableTheme::TokyoNightStorm => f.write_str("Tokyo Night Storm"),
+            SerializableTheme::TokyoNightLight => f.write_str("Tokyo Night Light"),
+            SerializableTheme::KanagawaWave => f.write_str("Kanagawa Wave"),
+            SerializableTheme::KanagawaDragon => f.write_str("Kanagawa Dragon"),
+            SerializableTheme::KanagawaLotus => f.write_str("Kanagawa Lotus"),
+            SerializableTheme::Moonfly => f.write_str("Moonfly"),
+            SerializableTheme::Nightfly => f.write_str("Nightfly"),
+            SerializableTheme::Oxocarbon => f.write_str("Oxocarbon"),
+            SerializableTheme::Ferra => f.write_str("Ferra"),
+            SerializableTheme::Custom(custom) => f.write_str(&custom.name),
+        }
+    }
+}
+
+impl std::str::FromStr for SerializableTheme {
+    type Err = String;
+
+    /// Accepts both the display name and the variant name, e.g. `"Tokyo Night"` or `tokyonight`
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let normalize = |name: &str| name.replace(' ', "").to_lowercase();
+        let normalized_name = normalize(name);
+
+        LIGHT_THEMES
+            .iter()
+            .chain(DARK_THEMES)
+            .find(|theme| normalize(&theme.to_string()) == normalized_name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown theme: {name}"))
+    }
+}
+
+impl SerializableTheme {
+    /// Whether the theme belongs with the dark themes
+    pub fn is_dark(&self) -> bool {
+        match self {
+            SerializableTheme::Custom(custom) => custom.is_dark(),
+            theme => DARK_THEMES.contains(theme),
+        }
+    }
+}
+
+/// Whether the name is taken by a built-in theme. `--theme` looks those up by name, so a user
+/// theme must not shadow one
+fn is_builtin_theme_name(name: &str) -> bool {
+    name.parse::<SerializableTheme>().is_ok()
+}
+
+impl From<Theme> for SerializableTheme {
+    fn from(theme: Theme) -> Self {
+        match theme {
+            Theme::Light => Self::Light,
+            Theme::Dark => Self::Dark,
+            Theme::Dracula => Self::Dracula,
+            Theme::Ferra => Self::Ferra,
+            Theme::Nord => Self::Nord,
+            Theme::SolarizedLight => Self::SolarizedLight,
+            Theme::SolarizedDark => Self::SolarizedDark,
+            Theme::GruvboxLight => Self::GruvboxLight,
+            Theme::GruvboxDark => Self::GruvboxDark,
+            Theme::CatppuccinLatte => Self::CatppuccinLatte,
+            Theme::CatppuccinFrappe => Self::CatppuccinFrappe,
+            Theme::CatppuccinMacchiato => Self::CatppuccinMacchiato,
+            Theme::CatppuccinMocha => Self::CatppuccinMocha,
+            Theme::TokyoNight => Self::TokyoNight,
+            Theme::TokyoNightStorm => Self::TokyoNightStorm,
+            Theme::TokyoNightLight => Self::TokyoNightLight,
+            Theme::KanagawaWave => Self::KanagawaWave,
+            Theme::KanagawaDragon => Self::KanagawaDragon,
+            Theme::KanagawaLotus => Self::KanagawaLotus,
+            Theme::Moonfly => Self::Moonfly,
+            Theme::Nightfly => Self::Nightfly,
+            Theme::Oxocarbon => Self::Oxocarbon,
+            theme @ Theme::Custom(_) => Self::Custom(CustomTheme {
+                name: theme.to_string(),
+                palette: theme.palette().into(),
+            }),
+        }
+    }
+}
+
+impl From<SerializableTheme> for Theme {
+    fn from(theme: SerializableTheme) -> Self {
+        match theme {
+            SerializableTheme::Light => Theme::Light,
+            SerializableTheme::Dark => Theme::Dark,
+            SerializableTheme::Dracula => Theme::Dracula,
+            SerializableTheme::Nord => Theme::Nord,
+            SerializableTheme::SolarizedLight => Theme::SolarizedLight,
+            SerializableTheme::SolarizedDark => Theme::SolarizedDark,
+            SerializableTheme::GruvboxLight => Theme::GruvboxLight,
+            SerializableTheme::GruvboxDark => Theme::GruvboxDark,
+            SerializableTheme::CatppuccinLatte => Theme::CatppuccinLatte,
+            SerializableTheme::CatppuccinFrappe => Theme::CatppuccinFrappe,
+            SerializableTheme::CatppuccinMacchiato => Theme::CatppuccinMacchiato,
+            SerializableTheme::CatppuccinMocha => Theme::CatppuccinMocha,
+            SerializableTheme::TokyoNight => Theme::TokyoNight,
+            SerializableTheme::TokyoNightStorm => Theme::TokyoNightStorm,
+            SerializableTheme::TokyoNightLight => Theme::TokyoNightLight,
+            SerializableTheme::KanagawaWave => Theme::KanagawaWave,
+            SerializableTheme::KanagawaDragon => Theme::KanagawaDragon,
+            SerializableTheme::KanagawaLotus => Theme::KanagawaLotus,
+            SerializableTheme::Moonfly => Theme::Moonfly,
+            SerializableTheme::Nightfly => Theme::Nightfly,
+            SerializableTheme::Oxocarbon => Theme::Oxocarbon,
+            SerializableTheme::Ferra => Theme::Ferra,
+            SerializableTheme::Custom(custom) => Theme::custom(custom.name, custom.palette.into()),
+        }
+    }
+}
+
+#[cfg_attr(feature = "config", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub(crate) struct OmniThemes {
+    pub application_theme_mode: OmniThemeMode,
+    #[cfg_attr(feature = "config", serde(skip))]
+    system_theme_mode: theme::Mode,
+    pub light_theme: SerializableTheme,
+    pub dark_theme: SerializableTheme,
+    /// Listed after the built-in themes. Read from the themes directory, older configs held
+    /// them inline and have them moved to files
+    #[cfg_attr(feature = "config", serde(default, skip_serializing))]
+    pub custom_themes: Vec<CustomTheme>,
+    /// The file of every user theme by name, saving a theme writes back to it
+    #[cfg(feature = "config")]
+    #[serde(skip)]
+    theme_files: BTreeMap<String, PathBuf>,
+    /// Theme files that failed to load as of the last reload
+    #[cfg_attr(feature = "config", serde(skip))]
+    file_errors: Vec<String>,
+    /// Why themes failed to import or save, shown until dismissed
+    #[cfg_attr(feature = "config", serde(skip))]
+    notices: Vec<String>,
+    /// Open while set, its palette then themes the whole app
+    #[cfg_attr(feature = "config", serde(skip))]
+    editor: Option<ThemeEditor>,
+    /// Name of a theme to select, kept until the user themes it may be one of are read
+    #[cfg_attr(feature = "config", serde(skip))]
+    requested_theme: Option<String>,
+    #[cfg(feature = "config")]
+    #[serde(skip)]
+    theme_files_loaded: bool,
+}
+
+pub static LIGHT_THEMES: &[SerializableTheme] = &[
+    SerializableTheme::Light,
+    SerializableTheme::SolarizedLight,
+    SerializableTheme::GruvboxLight,
+    SerializableTheme::CatppuccinLatte,
+    SerializableTheme::TokyoNightLight,
+    SerializableTheme::KanagawaLotus,
+];
+
+pub static DARK_THEMES: &[SerializableTheme] = &[
+    SerializableTheme::Dark,
+    SerializableTheme::Dracula,
+    SerializableTheme::Nord,
+    SerializableTheme::SolarizedDark,
+    SerializableTheme::GruvboxDark,
+    SerializableTheme::CatppuccinFrappe,
+    SerializableTheme::CatppuccinMacchiato,
+    SerializableTheme::CatppuccinMocha,
+    SerializableTheme::TokyoNight,
+    SerializableTheme::TokyoNightStorm,
+    SerializableTheme::KanagawaWave,
+    SerializableTheme::KanagawaDragon,
+    SerializableTheme::Moonfly,
+    SerializableTheme::Nightfly,
+    SerializableTheme::Oxocarbon,
+    SerializableTheme::Ferra,
+];
+
+impl OmniThemes {
+    pub(crate) fn init() -> OmniThemes {
+        Self {
+            application_theme_mode: OmniThemeMode::SystemDefault,
+            system_theme_mode: theme::Mode::None,
+            light_theme: SerializableTheme::GruvboxLight,
+            dark_theme: SerializableTheme::GruvboxDark,
+            custom_themes: vec![],
+            #[cfg(feature = "config")]
+            theme_files: BTreeMap::new(),
+            file_errors: vec![],
+            notices: vec![],
+            editor: None,
+            requested_theme: None,
+            #[cfg(feature = "config")]
+            theme_files_loaded: false,
+        }
+    }
+
+    /// The built-in light themes, then the user's
+    fn light_themes(&self) -> Vec<SerializableTheme> {
+        LIGHT_THEMES
+            .iter()
+            .cloned()
+            .chain(
+                self.custom_themes
+                    .iter()
+                    .filter(|custom| !custom.is_dark())
+                    .cloned()
+                    .map(SerializableTheme::Custom),
+            )
+            .collect()
+    }
+
+    /// The built-in dark themes, then the user's
+    fn dark_themes(&self) -> Vec<SerializableTheme> {
+        DARK_THEMES
+            .iter()
+            .cloned()
+            .chain(
+                self.custom_themes
+                    .iter()
+                    .filter(|custom| custom.is_dark())
+                    .cloned()
+                    .map(SerializableTheme::Custom),
+            )
+            .collect()
+    }
+
+    pub(crate) fn update(&mut self, message: OmniThemesMessage) -> Task<OmniThemesMessage> {
+        let is_critical_state_changed = matches!(
+            message,
+            OmniThemesMessage::ChangeSystemThemeMode(_)
+                | OmniThemesMessage::SwitchLightTheme(_)
+                | OmniThemesMessage::SwitchDarkTheme(_)
+        );
+
+        match message {
+            OmniThemesMessage::ChangeThemeMode(mode) => {
+                self.application_theme_mode = mode;
+            }
+            OmniThemesMessage::ChangeSystemThemeMode(mode) => {
+                self.system_theme_mode = mode;
+            }
+            OmniThemesMessage::SwitchLightTheme(theme) => self.light_theme = theme,
+            OmniThemesMessage::SwitchDarkTheme(theme) => self.dark_theme = theme,
+            OmniThemesMessage::ThemeRequested(name) => {
+                self.requested_theme = Some(name);
+
+                return self.select_requested_theme();
+            }
+            OmniThemesMessage::EditorToggled => {
+                self.editor = match self.editor {
+                    Some(_) => None,
+                    None => Some(ThemeEditor::new(self.active_theme())),
+                };
+            }
+            OmniThemesMessage::EditorStartedFrom(theme) => {
+                self.editor = Some(ThemeEditor::new(&theme));
+            }
+            OmniThemesMessage::EditorSlotSelected(slot) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.slot = slot;
+                }
+            }
+            OmniThemesMessage::EditorColorDragged(slot, color) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.drag(slot, color);
+                }
+            }
+            OmniThemesMessage::EditorColorReleased => {
+                if let Some(editor) = &mut self.editor {
+                    editor.release();
+                }
+            }
+            OmniThemesMessage::EditorHexEdited(slot, hex) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.slot = slot;
+                    editor.type_hex(slot, hex);
+                }
+            }
+            OmniThemesMessage::EditorNameEdited(name) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.name = name;
+                }
+            }
+            OmniThemesMessage::EditorUndone => {
+                if let Some(editor) = &mut self.editor {
+                    editor.undo();
+                }
+            }
+            OmniThemesMessage::EditorReverted => {
+                if let Some(editor) = &mut self.editor {
+                    editor.revert();
+                }
+            }
+            OmniThemesMessage::EditorSaved => return self.save_edited_theme(),
+            OmniThemesMessage::ImportRequested => return self.import_themes(),
+            #[cfg(feature = "config")]
+            OmniThemesMessage::ThemeFilesLoaded(themes, errors) => {
+                return self.reload_theme_files(themes, errors);
+            }
+            OmniThemesMessage::BannerDismissed => {
+                self.file_errors.clear();
+                self.notices.clear();
+            }
+            OmniThemesMessage::PreviewTouched | OmniThemesMessage::CriticalStateChanged => {}
+        };
+
+        if is_critical_state_changed {
+            Task::done(OmniThemesMessage::CriticalStateChanged)
+        } else {
+            Task::none()
+        }
+    }
+
+    pub(crate) fn view(&self) -> Element<'_, OmniThemesMessage> {
+        const R: f32 = 6.0;
+
+        let left_radius = border::Radius {
+            top_left: R,
+            top_right: 0.0,
+            bottom_right: 0.0,
+            bottom_left: R,
+        };
+        let mid_radius = border::Radius::default();
+        let right_radius = border::Radius {
+            top_left: 0.0,
+            top_right: R,
+            bottom_right: R,
+            bottom_left: 0.0,
+        };
+
+        let current_mode = &self.application_theme_mode;
+        let toggle_button = |label: &'static str, mode: OmniThemeMode, radius: border::Radius| {
+            let is_active = &mode == current_mode;
+            widget::button(label)
+                .on_press(OmniThemesMessage::ChangeThemeMode(mode))
+                .style(move |theme, status| {
+                    let mut style = if is_active {
+                        widget::button::primary(theme, status)
+                    } else {
+                        widget::button::secondary(theme, status)
+                    };
+                    style.border.radius = radius;
+                    style
+                })
+        };
+
+        widget::column![
+            widget::container(
+                widget::row![
+                    toggle_button("☽ Dark", OmniThemeMode::Dark, left_radius),
+                    toggle_button("⚙ System", OmniThemeMode::SystemDefault, mid_radius),
+                    toggle_button("☀ Light", OmniThemeMode::Light, right_radius),
+                ]
+                .spacing(0),
+            )
+            .center_x(Length::Fill),
+            widget::container(
+                widget::row![
+                    widget::text("Light:"),
+                    widget::PickList::new(
+                        self.light_themes(),
+                        Some(&self.light_theme),
+                        OmniThemesMessage::SwitchLightTheme
+                    )
+                    .width(Length::Shrink),
+                    widget::text("Dark:"),
+                    widget::PickList::new(
+                        self.dark_themes(),
+                        Some(&self.dark_theme),
+                        OmniThemesMessage::SwitchDarkTheme
+                    )
+                    .width(Length::Shrink),
+                    widget::button("🎨 Edit")
+                        .style(if self.editor.is_some() {
+                            widget::button::primary
+                        } else {
+                            widget::button::secondary
+                        })
+                        .on_press(OmniThemesMessage::EditorToggled),
+                    widget::button("Import...")
+                        .style(widget::button::secondary)
+                        .on_press(OmniThemesMessage::ImportRequested),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+            )
+            .center_x(Length::Fill),
+        ]
+        .push(self.banner())
+        .push(self.editor.as_ref().map(|editor| self.editor_view(editor)))
+        .spacing(8)
+        .into()
+    }
+
+    /// Broken theme files and failed imports, above the editor so that it stays usable
+    fn banner(&self) -> Option<Element<'_, OmniThemesMessage>> {
+        if self.file_errors.is_empty() && self.notices.is_empty() {
+            return None;
+        }
+
+        let lines = self
+            .file_errors
+            .iter()
+            .chain(&self.notices)
+            .map(|line| widget::text(line).style(widget::text::warning).into());
+
+        Some(
+            widget::container(
+                widget::row![
+                    widget::column(lines).spacing(2).width(Length::Fill),
+                    widget::button("Dismiss")
+                        .style(widget::button::text)
+                        .on_press(OmniThemesMessage::BannerDismissed),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+            )
+            .padding(8)
+            .style(widget::container::bordered_box)
+            .into(),
+        )
+    }
+
+    /// Saves the edited palette as a user theme and selects it, replacing one of the same name
+    fn save_edited_theme(&mut self) -> Task<OmniThemesMessage> {
+        let Some(editor) = &mut self.editor else {
+            return Task::none();
+        };
+
+        let name = editor.name.trim();
+        if name.is_empty() {
+            editor.error = Some("Name the theme to save it".to_owned());
+            return Task::none();
+        }
+        if is_builtin_theme_name(name) {
+            editor.error = Some(format!("{name} is a built-in theme, pick another name"));
+            return Task::none();
+        }
+
+        let custom = CustomTheme {
+            name: name.to_owned(),
+            palette: editor.palette,
+        };
+        editor.original = editor.palette;
+        editor.error = None;
+
+        self.add_custom_theme(custom);
+
+        Task::done(OmniThemesMessage::CriticalStateChanged)
+    }
+
+    /// Keeps the theme, replacing one of the same name, and selects it as the light or dark theme
+    fn add_custom_theme(&mut self, custom: CustomTheme) {
+        match self
+            .custom_themes
+            .iter_mut()
+            .find(|existing| existing.name == custom.name)
+        {
+            Some(existing) => *existing = custom.clone(),
+            None => self.custom_themes.push(custom.clone()),
+        }
+
+        #[cfg(feature = "config")]
+        self.write_theme_file(&custom);
+
+        if custom.is_dark() {
+            self.dark_theme = SerializableTheme::Custom(custom);
+        } else {
+            self.light_theme = SerializableTheme::Custom(custom);
+        }
+    }
+
+    /// Adds the picked color schemes as user themes, the last one selected
+    fn import_themes(&mut self) -> Task<OmniThemesMessage> {
+        let Some(files) = FileDialog::new()
+            .add_filter("color scheme", IMPORT_EXTENSIONS)
+            .pick_files()
+        else {
+            return Task::none();
+        };
+
+        let mut imported = false;
+        let mut errors = vec![];
+
+        for file in files {
+            match import::import(&file) {
+                Ok(mut custom) => {
+                    if is_builtin_theme_name(&custom.name) {
+                        custom.name = format!("{} (imported)", custom.name);
+                    }
+
+                    self.add_custom_theme(custom);
+                    imported = true;
+                }
+                Err(e) => errors.push(format!("{}: {e}", file.to_string_lossy())),
+            }
+        }
+
+        self.notices.extend(errors);
+
+        if imported {
+            Task::done(OmniThemesMessage::CriticalStateChanged)
+        } else {
+            Task::none()
+        }
+    }
+
+    /// Writes the theme back to the file it was read from, or to a new one
+    #[cfg(feature = "config")]
+    fn write_theme_file(&mut self, custom: &CustomTheme) {
+        match files::write(
+            custom,
+            self.theme_files.get(&custom.name).map(PathBuf::as_path),
+        ) {
+            Ok(path) => {
+                self.theme_files.insert(custom.name.clone(), path);
+            }
+            Err(e) => self.notices.push(e),
+        }
+    }
+
+    /// Replaces the user themes with those read from their files. A selected theme follows its
+    /// file, so that edits made in a text editor show right away
+    #[cfg(feature = "config")]
+    fn reload_theme_files(
+        &mut self,
+        themes: Vec<(PathBuf, CustomTheme)>,
+        mut errors: Vec<String>,
+    ) -> Task<OmniThemesMessage> {
+        self.custom_themes.clear();
+        self.theme_files.clear();
+
+        for (path, custom) in themes {
+            if is_builtin_theme_name(&custom.name) {
+                errors.push(format!(
+                    "{}: {} is a built-in theme, rename it",
+                    path.display(),
+                    custom.name
+                ));
+            } else if let Some(first) = self.theme_files.get(&custom.name) {
+                errors.push(format!(
+                    "{}: {} is already the name of {}",
+                    path.display(),
+                    custom.name,
+                    first.display()
+                ));
+            } else {
+                self.theme_files.insert(custom.name.clone(), path);
+                self.custom_themes.push(custom);
+            }
+        }
+
+        self.file_errors = errors;
+        self.theme_files_loaded = true;
+
+        let mut is_selected_theme_changed = false;
+        for selected in [&mut self.light_theme, &mut self.dark_theme] {
+            if let SerializableTheme::Custom(selected) = selected
+                && let Some(custom) = self
+                    .custom_themes
+                    .iter()
+                    .find(|custom| custom.name == selected.name)
+                && custom != selected
+            {
+                *selected = custom.clone();
+                is_selected_theme_changed = true;
+            }
+        }
+
+        let select_requested_theme = self.select_requested_theme();
+
+        if is_selected_theme_changed {
+            Task::batch([
+                Task::done(OmniThemesMessage::CriticalStateChanged),
+                select_requested_theme,
+            ])
+        } else {
+            select_requested_theme
+        }
+    }
+
+    /// Selects the requested theme, or reports it unknown once the user themes are read too
+    fn select_requested_theme(&mut self) -> Task<OmniThemesMessage> {
+        let Some(name) = self.requested_theme.take() else {
+            return Task::none();
+        };
+
+        let normalize = |name: &str| name.replace(' ', "").to_lowercase();
+        let theme = name.parse::<SerializableTheme>().ok().or_else(|| {
+            self.custom_themes
+                .iter()
+                .find(|custom| normalize(&custom.name) == normalize(&name))
+                .cloned()
+                .map(SerializableTheme::Custom)
+        });
+
+        let Some(theme) = theme else {
+            #[cfg(feature = "config")]
+            if !self.theme_files_loaded {
+                self.requested_theme = Some(name);
+
+                return Task::none();
+            }
+
+            self.notices.push(format!("Unknown theme: {name}"));
+
+            return Task::none();
+        };
+
+        if theme.is_dark() {
+            self.dark_theme = theme;
+            self.application_theme_mode = OmniThemeMode::Dark;
+        } else {
+            self.light_theme = theme;
+            self.application_theme_mode = OmniThemeMode::Light;
+        }
+
+        Task::done(OmniThemesMessage::CriticalStateChanged)
+    }
+
+    /// Keeps what was read from the theme files before the config loaded, and moves the themes
+    /// older configs held inline to files. Returns whether the config held any
+    #[cfg(feature = "config")]
+    pub(crate) fn keep_theme_files(&mut self, previous: OmniThemes) -> bool {
+        let inline_themes = std::mem::replace(&mut self.custom_themes, previous.custom_themes);
+        self.theme_files = previous.theme_files;
+        self.file_errors = previous.file_errors;
+        self.notices = previous.notices;
+        self.requested_theme = previous.requested_theme;
+        self.theme_files_loaded = previous.theme_files_loaded;
+
+        let had_inline_themes = !inline_themes.is_empty();
+
+        for custom in inline_themes {
+            // NOTE: a file of the same name may have been edited since, it wins
+            if self.theme_files.contains_key(&custom.name)
+                || files::default_path(&custom.name).is_some_and(|path| path.exists())
+            {
+                continue;
+            }
+
+            self.write_theme_file(&custom);
+            self.custom_themes.push(custom);
+        }
+
+        had_inline_themes
+    }
+
+    fn editor_view<'a>(&'a self, editor: &'a ThemeEditor) -> Element<'a, OmniThemesMessage> {
+        let header = widget::row![
+            widget::PickList::new(
+                self.light_themes()
+                    .into_iter()
+                    .chain(self.dark_themes())
+                    .collect::<Vec<_>>(),
+                None::<SerializableTheme>,
+                OmniThemesMessage::EditorStartedFrom
+            )
+            .placeholder("Duplicate a theme..."),
+            widget::text_input("Theme name", &editor.name)
+                .on_input(OmniThemesMessage::EditorNameEdited)
+                .on_submit(OmniThemesMessage::EditorSaved)
+                .width(200),
+            widget::button("↶ Undo")
+                .style(widget::button::secondary)
+                .on_press_maybe(editor.can_undo().then_some(OmniThemesMessage::EditorUndone)),
+            widget::button("Revert")
+                .style(widget::button::secondary)
+                .on_press_maybe(
+                    (editor.palette != editor.original)
+                        .then_some(OmniThemesMessage::EditorReverted)
+                ),
+            widget::button("Save").on_press(OmniThemesMessage::EditorSaved),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .wrap();
+
+        let slots = widget::column(PALETTE_SLOTS.iter().map(|&slot| {
+            let color = Color::from(editor.palette.get(slot));
+            let is_selected = slot == editor.slot;
+
+            widget::row![
+                widget::text(slot.to_string()).width(90),
+                widget::button(widget::Space::new().width(32).height(20))
+                    .padding(0)
+                    .on_press(OmniThemesMessage::EditorSlotSelected(slot))
+                    .style(move |theme: &Theme, _status| {
+                        let palette = theme.extended_palette();
+
+                        widget::button::Style {
+                            background: Some(color.into()),
+                            border: iced::Border {
+                                color: if is_selected {
+                                    palette.primary.strong.color
+                                } else {
+                                    palette.background.strong.color
+                                },
+                                width: if is_selected { 3.0 } else { 1.0 },
+                                radius: 4.0.into(),
+                            },
+                            ..widget::button::Style::default()
+                        }
+                    }),
+                widget::text_input("#rrggbb", &editor.hex_inputs[slot as usize])
+                    .on_input(move |hex| OmniThemesMessage::EditorHexEdited(slot, hex))
+                    .width(90),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into()
+        }))
+        .spacing(4);
+
+        let slot = editor.slot;
+        let HexColor(rgb) = editor.palette.get(slot);
+        let channels = widget::column(["R", "G", "B"].into_iter().enumerate().map(
+            |(channel, label)| {
+                widget::row![
+                    widget::text(label).width(16),
+                    widget::slider(0..=255, rgb[channel], move |value| {
+                        let mut rgb = rgb;
+                        rgb[channel] = value;
+
+                        OmniThemesMessage::EditorColorDragged(slot, HexColor(rgb))
+                    })
+                    .on_release(OmniThemesMessage::EditorColorReleased)
+                    .width(200),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .into()
+            },
+        ))
+        .spacing(4);
+
+        widget::container(
+            widget::column![
+                header,
+                widget::row![
+                    widget::column![slots, widget::text(slot.to_string()).size(12), channels]
+                        .spacing(8),
+                    preview_pane(slot),
+                ]
+                .spacing(16),
+            ]
+            .push(
+                editor
+                    .error
+                    .as_ref()
+                    .map(|error| widget::text(error).style(widget::text::danger)),
+            )
+            .spacing(8),
+        )
+        .padding(8)
+        .center_x(Length::Fill)
+        .into()
+    }
+
+    pub(crate) fn start_up_tasks(&self) -> Task<OmniThemesMessage> {
+        iced::system::theme().map(OmniThemesMessage::ChangeSystemThemeMode)
+    }
+
+    pub(crate) fn subscription(&self) -> Subscription<OmniThemesMessage> {
+        Subscription::batch([
+            iced::system::theme_changes().map(OmniThemesMessage::ChangeSystemThemeMode),
+            #[cfg(feature = "config")]
+            Subscription::run(files::watch)
+                .map(|(themes, errors)| OmniThemesMessage::ThemeFilesLoaded(themes, errors)),
+        ])
+    }
+
+    /// The light or dark theme, whichever the mode calls for
+    fn active_theme(&self) -> &SerializableTheme {
+        match &self.application_theme_mode {
+            OmniThemeMode::Light => &self.light_theme,
+            OmniThemeMode::Dark => &self.dark_theme,
+            OmniThemeMode::SystemDefault => match self.system_theme_mode {
+                theme::Mode::Light => &self.light_theme,
+                theme::Mode::Dark | theme::Mode::None => &self.dark_theme,
+            },
+        }
+    }
+
+    pub(crate) fn theme(&self) -> Theme {
+        // NOTE: the palette being edited previews on the whole app
+        if let Some(editor) = &self.editor {
+            return Theme::custom(editor.name.clone(), editor.palette.into());
+        }
+
+        self.active_theme().clone().into()
+    }
+}
+
+/// A sample of every widget and style the app uses, to judge a palette at a glance
+fn preview_pane<'a>(slot: PaletteSlot) -> Element<'a, OmniThemesMessage> {
+    widget::container(
+        widget::column![
+            widget::text("Preview").size(20),
+            widget::text("Body text as most labels show it"),
+            widget::text("Smaller notes and file paths").size(12),
+            widget::row![
+                widget::text("Primary").style(widget::text::primary),
+                widget::text("Success").style(widget::text::success),
+                widget::text("Warning").style(widget::text::warning),
+                widget::text("Danger").style(widget::text::danger),
+            ]
+            .spacing(8),
+            widget::row![
+                widget::button("Primary").on_press(OmniThemesMessage::PreviewTouched),
+                widget::button("Secondary")
+                    .style(widget::button::secondary)
+                    .on_press(OmniThemesMessage::PreviewTouched),
+                widget::button("Success")
+                    .style(widget::button::success)
+                    .on_press(OmniThemesMessage::PreviewTouched),
+                widget::button("Danger")
+                    .style(widget::button::danger)
+                    .on_press(OmniThemesMessage::PreviewTouched),
+                widget::button("Text")
+                    .style(widget::button::text)
+                    .on_press(OmniThemesMessage::PreviewTouched),
+                widget::button("Disabled"),
+            ]
+            .spacing(8)
+            .wrap(),
+            widget::row![
+                widget::checkbox(true)
+                    .label("Checked")
+                    .on_toggle(|_| OmniThemesMessage::PreviewTouched),
+                widget::checkbox(false)
+                    .label("Unchecked")
+                    .on_toggle(|_| OmniThemesMessage::PreviewTouched),
+                widget::PickList::new(
+                    PALETTE_SLOTS,
+                    Some(slot),
+                    OmniThemesMessage::EditorSlotSelected
+                ),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            widget::slider(0.0..=1.0, 0.4, |_| OmniThemesMessage::PreviewTouched).step(0.01),
+            widget::progress_bar(0.0..=1.0, 0.6).girth(8),
+            widget::text_input("Placeholder", "Typed text")
+                .on_input(|_| OmniThemesMessage::PreviewTouched),
+            widget::text_input("Empty field shows its placeholder", "")
+                .on_input(|_| OmniThemesMessage::PreviewTouched),
+        ]
+        .spacing(8)
+        .width(320),
+    )
+    .padding(8)
+    .style(widget::container::bordered_box)
+    .into()
+}
@@ -0,0 +1,282 @@
+use std::{
+    fs::{File, TryLockError},
+    io::{self, Read, Write},
+    net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use iced::{
+    Subscription,
+    futures::{SinkExt, Stream, channel::mpsc},
+    stream,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::{cli::CliArgs, constants::APP_NAME, utils};
+
+const ACKNOWLEDGEMENT: &[u8] = b"ok";
+/// Bytes of OS randomness in the key other instances must send
+const KEY_BYTES: usize = 16;
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(2);
+/// How often a launch checks for the address of an instance that is still starting
+const ADDRESS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Forwarded arguments are a few paths and names, longer messages are refused unread
+const MAX_MESSAGE_BYTES: u64 = 64 * 1024;
+
+/// The listener of the running instance, with the key it expects and the file it keeps locked
+struct InstanceLock {
+    listener: TcpListener,
+    key: String,
+    _lock: File,
+}
+
+// NOTE: bound in main() before the iced runtime exists, picked up later by the subscription
+static INSTANCE_LOCK: OnceLock<InstanceLock> = OnceLock::new();
+
+pub(crate) enum InstanceRole {
+    Primary,
+    Forwarded,
+}
+
+/// The config directory, which belongs to the user, so other users' instances never meet
+fn config_dir() -> Option<PathBuf> {
+    let config_file = confy::get_configuration_file_path(APP_NAME, None).ok()?;
+
+    Some(config_file.parent()?.to_owned())
+}
+
+/// Claims `instance.lock` with an OS file lock, released by the system whenever the instance
+/// exits or crashes, and publishes the port and key in `instance.address`. A launch finding the
+/// lock taken hands its arguments over to that address instead
+pub(crate) fn acquire(cli_args: &CliArgs) -> io::Result<InstanceRole> {
+    let config_dir = config_dir()
+        .ok_or_else(|| io::Error::other("No config directory to keep the instance lock in"))?;
+    std::fs::create_dir_all(&config_dir)?;
+
+    let lock = File::options()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(config_dir.join("instance.lock"))?;
+    let address_file = config_dir.join("instance.address");
+
+    match lock.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            return forward_to_running(&address_file, cli_args).map(|()| InstanceRole::Forwarded);
+        }
+        Err(TryLockError::Error(e)) => return Err(e),
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let port = listener.local_addr()?.port();
+    let key = utils::random_hex(KEY_BYTES).map_err(io::Error::other)?;
+
+    utils::write_private_file(&address_file, format!("{port}\n{key}\n").as_bytes())?;
+    let _ = INSTANCE_LOCK.set(InstanceLock {
+        listener,
+        key,
+        _lock: lock,
+    });
+
+    Ok(InstanceRole::Primary)
+}
+
+/// The instance holding the lock may still be starting, or not have replaced the address left
+/// by a crashed one yet, so its address is retried for a while
+fn forward_to_running(address_file: &Path, cli_args: &CliArgs) -> io::Result<()> {
+    let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+
+    loop {
+        let error = match read_address(address_file) {
+            Some((port, key)) => match forward(port, &key, cli_args) {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            },
+            None => io::Error::other("the running instance has not published its address"),
+        };
+
+        if Instant::now() >= deadline {
+            return Err(error);
+        }
+        std::thread::sleep(ADDRESS_POLL_INTERVAL);
+    }
+}
+
+fn read_address(address_file: &Path) -> Option<(u16, String)> {
+    let contents = std::fs::read_to_string(address_file).ok()?;
+    let mut lines = contents.lines();
+
+    Some((lines.next()?.parse().ok()?, lines.next()?.to_owned()))
+}
+
+fn forward(port: u16, key: &str, cli_args: &CliArgs) -> io::Result<()> {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect_timeout(&address, HANDSHAKE_TIMEOUT)?;
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+
+    stream.write_all(&encode(key, cli_args))?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut acknowledgement = vec![];
+    stream.read_to_end(&mut acknowledgement)?;
+
+    if acknowledgement == ACKNOWLEDGEMENT {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "port {port} is held by another program"
+        )))
+    }
+}
+
+/// `APP_NAME`, the key, then every argument, each terminated by a NUL byte since paths may
+/// contain newlines
+fn encode(key: &str, cli_args: &CliArgs) -> Vec<u8> {
+    let mut message = vec![];
+
+    for part in [APP_NAME.to_owned(), key.to_owned()]
+        .into_iter()
+        .chain(cli_args.to_args())
+    {
+        message.extend_from_slice(part.as_bytes());
+        message.push(0);
+    }
+
+    message
+}
+
+fn decode(message: &[u8], key: &str) -> Result<CliArgs, String> {
+    let message = std::str::from_utf8(message).map_err(|e| e.to_string())?;
+    let mut parts = message.split_terminator('\0');
+
+    if parts.next() != Some(APP_NAME)
+        || !parts
+            .next()
+            .is_some_and(|given| utils::secrets_match(given.as_bytes(), key.as_bytes()))
+    {
+        return Err("Unexpected single-instance handshake".to_owned());
+    }
+
+    CliArgs::parse(parts.map(str::to_owned))
+}
+
+pub(crate) fn subscription() -> Subscription<CliArgs> {
+    Subscription::run(forwarded_args)
+}
+
+fn forwarded_args() -> impl Stream<Item = CliArgs> {
+    stream::channel(4, async |mut output: mpsc::Sender<CliArgs>| {
+        let Some((listener, key)) = INSTANCE_LOCK
+            .get()
+            .and_then(|lock| Some((lock.listener.try_clone().ok()?, lock.key.clone())))
+        else {
+            return;
+        };
+
+        let listener = match listener
+            .set_nonblocking(true)
+            .and_then(|()| tokio::net::TcpListener::from_std(listener))
+        {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Failed to listen for other instances: {e}");
+                return;
+            }
+        };
+
+        loop {
+            let mut stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    eprintln!("Failed to accept another instance: {e}");
+                    continue;
+                }
+            };
+
+            // NOTE: read with a deadline, a client sending nothing must not hold up later ones
+            let mut message = vec![];
+            match tokio::time::timeout(
+                HANDSHAKE_TIMEOUT,
+                (&mut stream)
+                    .take(MAX_MESSAGE_BYTES + 1)
+                    .read_to_end(&mut message),
+            )
+            .await
+            {
+                Ok(Ok(_)) if message.len() as u64 <= MAX_MESSAGE_BYTES => {}
+                Ok(Ok(_)) => {
+                    eprintln!("Ignoring forwarded arguments longer than {MAX_MESSAGE_BYTES} bytes");
+                    continue;
+                }
+                Ok(Err(e)) => {
+                    eprintln!("Failed to read forwarded arguments: {e}");
+                    continue;
+                }
+                Err(_) => {
+                    eprintln!("Another instance sent no arguments in time");
+                    continue;
+                }
+            }
+
+            match decode(&message, &key) {
+                Ok(cli_args) => {
+                    let _ = stream.write_all(ACKNOWLEDGEMENT).await;
+                    let _ = output.send(cli_args).await;
+                }
+                Err(e) => eprintln!("Ignoring forwarded arguments: {e}"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "0123456789abcdef";
+
+    fn cli_args() -> CliArgs {
+        CliArgs {
+            #[cfg(feature = "instax_framer")]
+            open: Some(PathBuf::from("/photos/line\nbreak.jpg")),
+            new_instance: false,
+            ..CliArgs::default()
+        }
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        assert_eq!(decode(&encode(KEY, &cli_args()), KEY), Ok(cli_args()));
+        assert_eq!(
+            decode(&encode(KEY, &CliArgs::default()), KEY),
+            Ok(CliArgs::default())
+        );
+    }
+
+    #[test]
+    fn rejects_a_wrong_key() {
+        let message = encode("fedcba9876543210", &cli_args());
+
+        assert!(decode(&message, KEY).is_err());
+        assert!(decode(&encode("", &cli_args()), KEY).is_err());
+    }
+
+    #[test]
+    fn rejects_another_app() {
+        let mut message = b"other_app\0".to_vec();
+        message.extend_from_slice(&encode(KEY, &cli_args())[APP_NAME.len() + 1..]);
+
+        assert!(decode(&message, KEY).is_err());
+        assert!(decode(b"", KEY).is_err());
+    }
+
+    #[test]
+    fn rejects_arguments_the_instance_does_not_know() {
+        let message = format!("{APP_NAME}\0{KEY}\0--bogus\0");
+
+        assert!(decode(message.as_bytes(), KEY).is_err());
+    }
+}
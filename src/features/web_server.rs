@@ -0,0 +1,752 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+};
+
+#[cfg(any(feature = "counter", feature = "system_info", feature = "omni_themes"))]
+use axum::Json;
+#[cfg(feature = "omni_themes")]
+use axum::routing::put;
+use axum::{
+    Router,
+    extract::{
+        Request, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use iced::{
+    Element, Subscription, Task,
+    futures::{SinkExt, Stream, channel::mpsc},
+    stream,
+    widget::text,
+};
+use rfd::{MessageDialog, MessageLevel};
+#[cfg(any(feature = "counter", feature = "omni_themes"))]
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::watch;
+
+#[cfg(feature = "counter")]
+use crate::features::counter::{Counter, CounterMessage};
+#[cfg(feature = "omni_themes")]
+use crate::features::omni_themes::{
+    OmniThemeMode, OmniThemes, OmniThemesMessage, SerializableTheme,
+};
+#[cfg(feature = "system_info")]
+use crate::features::system_info::SystemInfomation;
+use crate::utils;
+
+const DEFAULT_PORT: u16 = 7878;
+const PORT_ENV: &str = "OMNI_APP_SERVER_PORT";
+const TOKEN_ENV: &str = "OMNI_APP_SERVER_TOKEN";
+/// Bytes of OS randomness in a generated token, hex encoded
+const TOKEN_BYTES: usize = 32;
+
+#[derive(Debug, Clone, Hash)]
+pub(crate) struct ServerSettings {
+    pub port: u16,
+    pub token: String,
+    /// Not set through the environment, so clients read it from the token file
+    pub is_token_generated: bool,
+}
+
+impl ServerSettings {
+    fn from_env() -> Result<ServerSettings, String> {
+        let port = std::env::var(PORT_ENV)
+            .ok()
+            .and_then(|port| port.parse().ok())
+            .unwrap_or(DEFAULT_PORT);
+
+        match std::env::var(TOKEN_ENV)
+            .ok()
+            .filter(|token| !token.is_empty())
+        {
+            Some(token) => Ok(ServerSettings {
+                port,
+                token,
+                is_token_generated: false,
+            }),
+            None => Ok(ServerSettings {
+                port,
+                token: generate_token()?,
+                is_token_generated: true,
+            }),
+        }
+    }
+}
+
+/// Fails without OS randomness, a guessable token would be worse than no server
+fn generate_token() -> Result<String, String> {
+    utils::random_hex(TOKEN_BYTES).map_err(|e| format!("No server token generated: {e}"))
+}
+
+/// The `server-token` file next to the config file, holding a generated token
+fn token_file() -> Option<PathBuf> {
+    let config_file = confy::get_configuration_file_path(crate::constants::APP_NAME, None).ok()?;
+
+    Some(config_file.parent()?.join("server-token"))
+}
+
+/// Writes the token readable by the current user only
+fn write_token_file(token: &str) -> Result<PathBuf, String> {
+    let path = token_file().ok_or("No config directory to write the token to")?;
+
+    utils::write_private_file(&path, token.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+
+    Ok(path)
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct WebServerHandle {
+    pub address: SocketAddr,
+    snapshot: watch::Sender<AppSnapshot>,
+}
+
+#[derive(Debug)]
+pub(crate) struct WebServer {
+    /// The server stays off when they could not be set up
+    settings: Result<ServerSettings, String>,
+    handle: Option<WebServerHandle>,
+    /// Where the server listens and its token is found, or why it stopped
+    status: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum WebServerMessage {
+    Started(WebServerHandle),
+    Failed(String),
+    #[cfg(feature = "counter")]
+    CounterRequested(CounterAction),
+    #[cfg(feature = "omni_themes")]
+    ThemeRequested(ThemeUpdate),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct AppSnapshot {
+    #[cfg(feature = "counter")]
+    pub counter: Option<Counter>,
+    #[cfg(feature = "system_info")]
+    pub sysinfo: Option<SysInfoSnapshot>,
+    #[cfg(feature = "omni_themes")]
+    pub theme: Option<ThemeSnapshot>,
+}
+
+#[cfg(feature = "system_info")]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct SysInfoSnapshot {
+    system_name: Option<String>,
+    system_kernel: Option<String>,
+    system_version: Option<String>,
+    cpu_brand: String,
+    cpu_cores: Option<usize>,
+    memory_total: u64,
+}
+
+#[cfg(feature = "system_info")]
+impl From<&SystemInfomation> for SysInfoSnapshot {
+    fn from(information: &SystemInfomation) -> Self {
+        Self {
+            system_name: information.system_name.clone(),
+            system_kernel: information.system_kernel.clone(),
+            system_version: information.system_version.clone(),
+            cpu_brand: information.cpu_brand.clone(),
+            cpu_cores: information.cpu_cores,
+            memory_total: information.memory_total,
+        }
+    }
+}
+
+#[cfg(feature = "omni_themes")]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct ThemeSnapshot {
+    mode: OmniThemeMode,
+    light_theme: SerializableTheme,
+    dark_theme: SerializableTheme,
+}
+
+#[cfg(feature = "omni_themes")]
+impl From<&OmniThemes> for ThemeSnapshot {
+    fn from(omni_themes: &OmniThemes) -> Self {
+        Self {
+            mode: omni_themes.application_theme_mode.clone(),
+            light_theme: omni_themes.light_theme.clone(),
+            dark_theme: omni_themes.dark_theme.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "counter")]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum CounterAction {
+    Increment,
+    Decrement,
+    Reset,
+}
+
+#[cfg(feature = "counter")]
+impl From<CounterAction> for CounterMessage {
+    fn from(action: CounterAction) -> Self {
+        match action {
+            CounterAction::Increment => CounterMessage::Increment,
+            CounterAction::Decrement => CounterMessage::Decrement,
+            CounterAction::Reset => CounterMessage::Reset,
+        }
+    }
+}
+
+#[cfg(feature = "omni_themes")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThemeUpdate {
+    mode: Option<OmniThemeMode>,
+    light_theme: Option<SerializableTheme>,
+    dark_theme: Option<SerializableTheme>,
+}
+
+#[cfg(feature = "omni_themes")]
+impl ThemeUpdate {
+    fn is_valid(&self) -> bool {
+        let light_theme_valid = self
+            .light_theme
+            .as_ref()
+            .is_none_or(|theme| !theme.is_dark());
+        let dark_theme_valid = self
+            .dark_theme
+            .as_ref()
+            .is_none_or(SerializableTheme::is_dark);
+
+        light_theme_valid && dark_theme_valid
+    }
+
+    pub(crate) fn into_messages(self) -> Vec<OmniThemesMessage> {
+        let ThemeUpdate {
+            mode,
+            light_theme,
+            dark_theme,
+        } = self;
+
+        [
+            mode.map(OmniThemesMessage::ChangeThemeMode),
+            light_theme.map(OmniThemesMessage::SwitchLightTheme),
+            dark_theme.map(OmniThemesMessage::SwitchDarkTheme),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+impl WebServer {
+    pub(crate) fn init() -> WebServer {
+        Self {
+            settings: ServerSettings::from_env(),
+            handle: None,
+            status: "Web server starting...".to_owned(),
+        }
+    }
+
+    pub(crate) fn update(&mut self, message: WebServerMessage) -> Task<WebServerMessage> {
+        match message {
+            WebServerMessage::Started(handle) => {
+                // NOTE: the token itself is never shown, only where to find it
+                let token_source = match &self.settings {
+                    Ok(settings) if settings.is_token_generated => {
+                        match write_token_file(&settings.token) {
+                            Ok(path) => format!("token in {}", path.display()),
+                            Err(e) => e,
+                        }
+                    }
+                    _ => format!("token from {TOKEN_ENV}"),
+                };
+                self.status = format!("Web server on http://{} ({token_source})", handle.address);
+
+                self.handle = Some(handle);
+            }
+            WebServerMessage::Failed(message) => {
+                self.handle = None;
+                self.status = format!("Web server stopped: {message}");
+
+                let _ = MessageDialog::new()
+                    .set_title("Web server stopped")
+                    .set_description(message)
+                    .set_level(MessageLevel::Warning)
+                    .show();
+            }
+            #[cfg(feature = "counter")]
+            WebServerMessage::CounterRequested(_) => {}
+            #[cfg(feature = "omni_themes")]
+            WebServerMessage::ThemeRequested(_) => {}
+        }
+
+        Task::none()
+    }
+
+    /// Pushes the latest app state to HTTP and WebSocket clients, skipping unchanged snapshots
+    pub(crate) fn publish(&self, snapshot: AppSnapshot) {
+        if let Some(handle) = &self.handle {
+            handle.snapshot.send_if_modified(|current| {
+                if *current == snapshot {
+                    false
+                } else {
+                    *current = snapshot;
+                    true
+                }
+            });
+        }
+    }
+
+    pub(crate) fn view(&self) -> Element<'_, WebServerMessage> {
+        text(&self.status).size(12).into()
+    }
+
+    pub(crate) fn subscription(&self) -> Subscription<WebServerMessage> {
+        match &self.settings {
+            Ok(settings) => Subscription::run_with(settings.clone(), serve),
+            Err(e) => Subscription::run_with(e.clone(), |e| {
+                iced::futures::stream::iter([WebServerMessage::Failed(e.clone())])
+            }),
+        }
+    }
+}
+
+fn serve(settings: &ServerSettings) -> impl Stream<Item = WebServerMessage> + use<> {
+    let settings = settings.clone();
+
+    stream::channel(
+        16,
+        async move |mut output: mpsc::Sender<WebServerMessage>| {
+            let address = SocketAddr::from((Ipv4Addr::LOCALHOST, settings.port));
+
+            let listener = match tokio::net::TcpListener::bind(address).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    let _ = output
+                        .send(WebServerMessage::Failed(format!(
+                            "Failed to bind {address}: {e}"
+                        )))
+                        .await;
+                    return;
+                }
+            };
+
+            let (snapshot_sender, snapshot_receiver) = watch::channel(AppSnapshot::default());
+
+            let _ = output
+                .send(WebServerMessage::Started(WebServerHandle {
+                    address,
+                    snapshot: snapshot_sender,
+                }))
+                .await;
+
+            let state = ServerState {
+                token: settings.token.into(),
+                snapshot: snapshot_receiver,
+                #[cfg(any(feature = "counter", feature = "omni_themes"))]
+                commands: output.clone(),
+            };
+
+            if let Err(e) = axum::serve(listener, router(state)).await {
+                let _ = output
+                    .send(WebServerMessage::Failed(format!("Web server error: {e}")))
+                    .await;
+            }
+        },
+    )
+}
+
+#[derive(Clone)]
+pub(crate) struct ServerState {
+    token: Arc<str>,
+    snapshot: watch::Receiver<AppSnapshot>,
+    #[cfg(any(feature = "counter", feature = "omni_themes"))]
+    commands: mpsc::Sender<WebServerMessage>,
+}
+
+impl ServerState {
+    #[cfg(any(feature = "counter", feature = "omni_themes"))]
+    async fn dispatch(&self, message: WebServerMessage) -> StatusCode {
+        match self.commands.clone().send(message).await {
+            Ok(()) => StatusCode::ACCEPTED,
+            Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+fn router(state: ServerState) -> Router {
+    let router = Router::new().route("/ws", get(stream_state));
+
+    #[cfg(feature = "counter")]
+    let router = router.route("/counter", get(get_counter).post(post_counter));
+
+    #[cfg(feature = "system_info")]
+    let router = router.route("/sysinfo", get(get_sysinfo));
+
+    #[cfg(feature = "omni_themes")]
+    let router = router.route("/theme", put(put_theme));
+
+    router
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state)
+}
+
+/// Accepts the token as a bearer header, or as a `token` query parameter for browser WebSockets
+async fn require_token(State(state): State<ServerState>, request: Request, next: Next) -> Response {
+    let bearer_token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let query_token = request.uri().query().and_then(|query| {
+        form_urlencoded::parse(query.as_bytes())
+            .find_map(|(key, value)| (key == "token").then_some(value))
+    });
+
+    let is_authorized = match (bearer_token, query_token) {
+        (Some(token), _) => utils::secrets_match(token.as_bytes(), state.token.as_bytes()),
+        (None, Some(token)) => utils::secrets_match(token.as_bytes(), state.token.as_bytes()),
+        (None, None) => false,
+    };
+
+    if is_authorized {
+        next.run(request).await
+    } else {
+        StatusCode::UNAUTHORIZED.into_response()
+    }
+}
+
+#[cfg(feature = "counter")]
+async fn get_counter(State(state): State<ServerState>) -> Response {
+    match &state.snapshot.borrow().counter {
+        Some(counter) => Json(counter).into_response(),
+        None => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
+
+#[cfg(feature = "counter")]
+async fn post_counter(
+    State(state): State<ServerState>,
+    Json(action): Json<CounterAction>,
+) -> StatusCode {
+    state
+        .dispatch(WebServerMessage::CounterRequested(action))
+        .await
+}
+
+#[cfg(feature = "system_info")]
+async fn get_sysinfo(State(state): State<ServerState>) -> Response {
+    match &state.snapshot.borrow().sysinfo {
+        Some(sysinfo) => Json(sysinfo).into_response(),
+        None => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
+
+#[cfg(feature = "omni_themes")]
+async fn put_theme(
+    State(state): State<ServerState>,
+    Json(theme_update): Json<ThemeUpdate>,
+) -> StatusCode {
+    if !theme_update.is_valid() {
+        return StatusCode::UNPROCESSABLE_ENTITY;
+    }
+
+    state
+        .dispatch(WebServerMessage::ThemeRequested(theme_update))
+        .await
+}
+
+async fn stream_state(State(state): State<ServerState>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| forward_snapshots(socket, state.snapshot))
+}
+
+async fn forward_snapshots(mut socket: WebSocket, mut snapshot: watch::Receiver<AppSnapshot>) {
+    if send_snapshot(&mut socket, &mut snapshot).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            changed = snapshot.changed() => {
+                if changed.is_err() || send_snapshot(&mut socket, &mut snapshot).await.is_err() {
+                    return;
+                }
+            }
+            incoming = socket.recv() => {
+                if !matches!(incoming, Some(Ok(_))) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn send_snapshot(
+    socket: &mut WebSocket,
+    snapshot: &mut watch::Receiver<AppSnapshot>,
+) -> Result<(), axum::Error> {
+    let payload =
+        serde_json::to_string(&*snapshot.borrow_and_update()).map_err(axum::Error::new)?;
+
+    socket.send(Message::Text(payload.into())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use iced::futures::StreamExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_tungstenite::tungstenite;
+
+    use super::*;
+
+    // NOTE: characters that must be percent-encoded in a query
+    const TOKEN: &str = "s3cret+/=";
+    const ENCODED_TOKEN: &str = "s3cret%2B%2F%3D";
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    struct TestServer {
+        address: SocketAddr,
+        snapshot: watch::Sender<AppSnapshot>,
+        #[cfg_attr(
+            not(any(feature = "counter", feature = "omni_themes")),
+            allow(dead_code)
+        )]
+        commands: mpsc::Receiver<WebServerMessage>,
+    }
+
+    /// Serves the router on an ephemeral port until the test ends
+    async fn serve_router() -> TestServer {
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap();
+        let address = listener.local_addr().unwrap();
+        let (snapshot, snapshot_receiver) = watch::channel(AppSnapshot::default());
+        #[cfg_attr(
+            not(any(feature = "counter", feature = "omni_themes")),
+            allow(unused_variables)
+        )]
+        let (command_sender, commands) = mpsc::channel(16);
+
+        let state = ServerState {
+            token: TOKEN.into(),
+            snapshot: snapshot_receiver,
+            #[cfg(any(feature = "counter", feature = "omni_themes"))]
+            commands: command_sender,
+        };
+        tokio::spawn(async move { axum::serve(listener, router(state)).await });
+
+        TestServer {
+            address,
+            snapshot,
+            commands,
+        }
+    }
+
+    /// A bare HTTP/1.1 request, returning the status code and body
+    async fn request(
+        address: SocketAddr,
+        method: &str,
+        path: &str,
+        token: Option<&str>,
+        body: Option<&str>,
+    ) -> (u16, String) {
+        let mut request =
+            format!("{method} {path} HTTP/1.1\r\nHost: {address}\r\nConnection: close\r\n");
+        if let Some(token) = token {
+            request.push_str(&format!("Authorization: Bearer {token}\r\n"));
+        }
+        if let Some(body) = body {
+            request.push_str(&format!(
+                "Content-Type: application/json\r\nContent-Length: {}\r\n",
+                body.len()
+            ));
+        }
+        request.push_str("\r\n");
+        request.push_str(body.unwrap_or_default());
+
+        let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        tokio::time::timeout(TIMEOUT, stream.read_to_string(&mut response))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+
+        (status, body.to_owned())
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_or_wrong_token() {
+        let server = serve_router().await;
+
+        assert_eq!(
+            request(server.address, "GET", "/ws", None, None).await.0,
+            401
+        );
+        assert_eq!(
+            request(server.address, "GET", "/ws", Some("s3cret"), None)
+                .await
+                .0,
+            401
+        );
+        assert_eq!(
+            request(server.address, "GET", "/ws?token=s3cret+/=", None, None)
+                .await
+                .0,
+            401
+        );
+    }
+
+    #[test]
+    fn secrets_match_only_whole_tokens() {
+        let matches = |given: &str| utils::secrets_match(given.as_bytes(), TOKEN.as_bytes());
+
+        assert!(matches(TOKEN));
+        assert!(!matches("s3cret"));
+        assert!(!matches("s3cret+/x"));
+        assert!(!matches(""));
+    }
+
+    #[test]
+    fn generated_tokens_differ() {
+        let token = generate_token().unwrap();
+
+        assert_eq!(token.len(), TOKEN_BYTES * 2);
+        assert_ne!(token, generate_token().unwrap());
+    }
+
+    #[cfg(feature = "counter")]
+    #[tokio::test]
+    async fn get_counter_returns_the_snapshot() {
+        let server = serve_router().await;
+
+        let (status, _) = request(server.address, "GET", "/counter", Some(TOKEN), None).await;
+        assert_eq!(status, 503);
+
+        server
+            .snapshot
+            .send_modify(|snapshot| snapshot.counter = Some(Counter::default()));
+        let (status, body) = request(server.address, "GET", "/counter", Some(TOKEN), None).await;
+        assert_eq!(status, 200);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+            serde_json::to_value(Counter::default()).unwrap()
+        );
+    }
+
+    #[cfg(feature = "counter")]
+    #[tokio::test]
+    async fn post_counter_dispatches_the_counter_message() {
+        let mut server = serve_router().await;
+
+        let (status, _) = request(
+            server.address,
+            "POST",
+            "/counter",
+            Some(TOKEN),
+            Some(r#"{"action": "increment"}"#),
+        )
+        .await;
+        assert_eq!(status, 202);
+
+        let command = tokio::time::timeout(TIMEOUT, server.commands.next())
+            .await
+            .unwrap();
+        assert!(matches!(
+            command,
+            Some(WebServerMessage::CounterRequested(CounterAction::Increment))
+        ));
+    }
+
+    #[cfg(feature = "omni_themes")]
+    #[tokio::test]
+    async fn put_theme_rejects_an_invalid_pairing() {
+        let mut server = serve_router().await;
+
+        let (status, _) = request(
+            server.address,
+            "PUT",
+            "/theme",
+            Some(TOKEN),
+            Some(r#"{"light_theme": "Nord", "dark_theme": "SolarizedLight"}"#),
+        )
+        .await;
+        assert_eq!(status, 422);
+
+        let (status, _) = request(
+            server.address,
+            "PUT",
+            "/theme",
+            Some(TOKEN),
+            Some(r#"{"mode": "Dark", "light_theme": "SolarizedLight", "dark_theme": "Nord"}"#),
+        )
+        .await;
+        assert_eq!(status, 202);
+
+        // NOTE: only the valid update reached the app
+        let command = tokio::time::timeout(TIMEOUT, server.commands.next())
+            .await
+            .unwrap();
+        let Some(WebServerMessage::ThemeRequested(theme_update)) = command else {
+            panic!("Expected a theme update, got {command:?}");
+        };
+        assert_eq!(theme_update.into_messages().len(), 3);
+        assert!(server.commands.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn websocket_pushes_a_snapshot_after_a_state_change() {
+        let server = serve_router().await;
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!(
+            "ws://{}/ws?token={ENCODED_TOKEN}",
+            server.address
+        ))
+        .await
+        .unwrap();
+
+        let mut next_snapshot = async || {
+            let message = tokio::time::timeout(TIMEOUT, socket.next())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            let tungstenite::Message::Text(text) = message else {
+                panic!("Expected a text message, got {message:?}");
+            };
+
+            serde_json::from_str::<serde_json::Value>(&text).unwrap()
+        };
+
+        let initial = next_snapshot().await;
+        assert_eq!(
+            initial,
+            serde_json::to_value(AppSnapshot::default()).unwrap()
+        );
+
+        let mut changed = AppSnapshot::default();
+        #[cfg(feature = "counter")]
+        {
+            changed.counter = Some(Counter::default());
+        }
+        #[cfg(feature = "omni_themes")]
+        {
+            changed.theme = Some(ThemeSnapshot::from(&OmniThemes::init()));
+        }
+        server.snapshot.send_replace(changed.clone());
+
+        assert_eq!(
+            next_snapshot().await,
+            serde_json::to_value(changed).unwrap()
+        );
+    }
+}
@@ -1,16 +1,38 @@
 use iced::Size;
 
+mod cli;
 mod constants;
 mod features;
 mod omni_app;
+mod utils;
 
+use cli::{CliArgs, USAGE};
 use constants::APP_NAME_WITH_AUTHOR;
+#[cfg(feature = "single_instance")]
+use features::single_instance::{self, InstanceRole};
 use omni_app::OmniApp;
 
 fn main() -> iced::Result {
+    let cli_args = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(cli_args) => cli_args,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    #[cfg(feature = "single_instance")]
+    if !cli_args.new_instance {
+        match single_instance::acquire(&cli_args) {
+            Ok(InstanceRole::Primary) => {}
+            Ok(InstanceRole::Forwarded) => return Ok(()),
+            Err(e) => eprintln!("Single-instance lock unavailable, starting anyway: {e}"),
+        }
+    }
+
     let app = iced::application(
         move || {
-            let mut omni_app = OmniApp::init();
+            let mut omni_app = OmniApp::init(cli_args.clone());
             let start_up_tasks = omni_app.start_up_tasks();
 
             (omni_app, start_up_tasks)
@@ -22,7 +44,7 @@ fn main() -> iced::Result {
     .theme(OmniApp::theme)
     .window_size(Size {
         width: 544.0,
-        height: 288.0,
+        height: 720.0,
     })
     .exit_on_close_request(false)
     .subscription(OmniApp::subscription);
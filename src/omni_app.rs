@@ -1,3 +1,4 @@
+use crate::cli::CliArgs;
 #[cfg(feature = "config")]
 use crate::constants::APP_NAME;
 #[cfg(feature = "instax_framer")]
@@ -12,6 +13,8 @@ use rfd::{MessageDialog, MessageLevel};
 
 #[cfg(feature = "omni_themes")]
 use crate::features::omni_themes::{OmniThemes, OmniThemesMessage};
+#[cfg(feature = "single_instance")]
+use crate::features::single_instance;
 #[cfg(feature = "system_info")]
 use crate::features::system_info::{SystemInfo, SystemInfoMessage};
 
@@ -24,10 +27,15 @@ use crate::features::instax_framer::{InstaxFramer, InstaxFramerMessage};
 #[cfg(feature = "config")]
 use crate::features::config::OmniAppConfig;
 
+#[cfg(feature = "web_server")]
+use crate::features::web_server::{AppSnapshot, WebServer, WebServerMessage};
+
 #[derive(Debug)]
 pub(super) struct OmniApp {
     #[cfg(feature = "config")]
     last_saved_config_hash: Option<u64>,
+    /// Applied once the config is loaded, so the saved state doesn't override them
+    pending_cli_args: Option<CliArgs>,
     #[cfg(feature = "omni_themes")]
     pub omni_themes: OmniThemes,
     #[cfg(feature = "counter")]
@@ -36,13 +44,15 @@ pub(super) struct OmniApp {
     pub system_info: SystemInfo,
     #[cfg(feature = "instax_framer")]
     pub instax_framer: InstaxFramer,
+    #[cfg(feature = "web_server")]
+    web_server: WebServer,
 }
 
 #[allow(unused)]
 #[derive(Clone, Debug)]
 pub enum OmniAppMessage {
     #[cfg(feature = "config")]
-    ConfigLoaded(OmniAppConfig),
+    ConfigLoaded(Box<OmniAppConfig>),
     #[cfg(feature = "config")]
     SavingConfigRequested,
     #[cfg(feature = "config")]
@@ -60,14 +70,19 @@ pub enum OmniAppMessage {
     InstaxFramer(InstaxFramerMessage),
     #[cfg(feature = "omni_themes")]
     OmniThemes(OmniThemesMessage),
+    #[cfg(feature = "web_server")]
+    WebServer(WebServerMessage),
+    #[cfg(feature = "single_instance")]
+    SecondInstanceLaunched(CliArgs),
     TerminateImmediately,
 }
 
 impl OmniApp {
-    pub fn init() -> Self {
+    pub fn init(cli_args: CliArgs) -> Self {
         Self {
             #[cfg(feature = "config")]
             last_saved_config_hash: None,
+            pending_cli_args: Some(cli_args),
             #[cfg(feature = "omni_themes")]
             omni_themes: OmniThemes::init(),
             #[cfg(feature = "counter")]
@@ -76,6 +91,8 @@ impl OmniApp {
             system_info: SystemInfo::init(),
             #[cfg(feature = "instax_framer")]
             instax_framer: InstaxFramer::init(),
+            #[cfg(feature = "web_server")]
+            web_server: WebServer::init(),
         }
     }
 
@@ -106,6 +123,12 @@ impl OmniApp {
                     #[cfg(not(feature = "instax_framer"))]
                     None::<Element<'_, OmniAppMessage>>,
                 )
+                .push(
+                    #[cfg(feature = "web_server")]
+                    Some(self.web_server.view().map(OmniAppMessage::WebServer)),
+                    #[cfg(not(feature = "web_server"))]
+                    None::<Element<'_, OmniAppMessage>>,
+                )
                 .align_x(Alignment::Center)
                 .spacing(12)
                 .height(Length::Fill),
@@ -121,7 +144,7 @@ impl OmniApp {
         Task::perform(
             async move { confy::load(APP_NAME, None) },
             |result| match result {
-                Ok(config) => OmniAppMessage::ConfigLoaded(config),
+                Ok(config) => OmniAppMessage::ConfigLoaded(Box::new(config)),
                 Err(e) => OmniAppMessage::LoadingConfigFailed(e.to_string()),
             },
         )
@@ -162,7 +185,49 @@ impl OmniApp {
         )
     }
 
+    fn apply_cli_args(&mut self, cli_args: CliArgs) -> Task<OmniAppMessage> {
+        #[allow(unused_mut)]
+        let mut tasks: Vec<Task<OmniAppMessage>> = vec![];
+
+        #[cfg(feature = "instax_framer")]
+        if let Some(path) = cli_args.open {
+            tasks.push(Task::done(OmniAppMessage::InstaxFramer(
+                InstaxFramerMessage::ImagePicked(path),
+            )));
+        }
+
+        #[cfg(feature = "omni_themes")]
+        if let Some(name) = cli_args.theme {
+            tasks.push(Task::done(OmniAppMessage::OmniThemes(
+                OmniThemesMessage::ThemeRequested(name),
+            )));
+        }
+
+        Task::batch(tasks)
+    }
+
+    #[cfg(feature = "web_server")]
+    fn snapshot(&self) -> AppSnapshot {
+        AppSnapshot {
+            #[cfg(feature = "counter")]
+            counter: Some(self.counter.clone()),
+            #[cfg(feature = "system_info")]
+            sysinfo: self.system_info.system_info.as_ref().map(Into::into),
+            #[cfg(feature = "omni_themes")]
+            theme: Some((&self.omni_themes).into()),
+        }
+    }
+
     pub fn update(&mut self, message: OmniAppMessage) -> Task<OmniAppMessage> {
+        let task = self.handle_message(message);
+
+        #[cfg(feature = "web_server")]
+        self.web_server.publish(self.snapshot());
+
+        task
+    }
+
+    fn handle_message(&mut self, message: OmniAppMessage) -> Task<OmniAppMessage> {
         match message {
             OmniAppMessage::TerminateImmediately => {
                 window::latest().and_then(window::close::<OmniAppMessage>)
@@ -171,6 +236,9 @@ impl OmniApp {
             OmniAppMessage::CloseRequested => {
                 #[cfg(feature = "config")]
                 {
+                    #[cfg(feature = "instax_framer")]
+                    self.instax_framer.discard_autosave();
+
                     self.save_config()
                         .chain(window::latest().and_then(window::close::<OmniAppMessage>))
                 }
@@ -189,14 +257,33 @@ impl OmniApp {
                     self.counter = app_config.counter;
                 }
 
+                // NOTE: user themes are read from their files, keep them if they loaded before the
+                // config
                 #[cfg(feature = "omni_themes")]
-                {
-                    self.omni_themes = app_config.omni_themes;
-                }
+                let had_inline_themes = {
+                    let previous = std::mem::replace(&mut self.omni_themes, app_config.omni_themes);
+
+                    self.omni_themes.keep_theme_files(previous)
+                };
 
                 #[cfg(feature = "instax_framer")]
                 {
+                    // NOTE: templates are not saved, keep them if they loaded before the config
+                    let user_templates = std::mem::take(&mut self.instax_framer.user_templates);
+
                     self.instax_framer = app_config.instax_framer;
+                    self.instax_framer.user_templates = user_templates;
+                    self.instax_framer.prune_missing_files();
+                }
+
+                #[allow(unused_mut)]
+                let mut cli_args = self.pending_cli_args.take().unwrap_or_default();
+
+                // NOTE: an image passed on the command line replaces the saved one instead of
+                // racing it, see the reload below
+                #[cfg(feature = "instax_framer")]
+                if let Some(path) = cli_args.open.take() {
+                    self.instax_framer.selected_file = Some(path);
                 }
 
                 #[allow(unused_mut)]
@@ -211,6 +298,21 @@ impl OmniApp {
                     );
                 }
 
+                // NOTE: after the saved image, so that a recovered one replaces it
+                #[cfg(feature = "instax_framer")]
+                tasks.push(Task::done(OmniAppMessage::InstaxFramer(
+                    InstaxFramerMessage::AutosaveRecoveryChecked,
+                )));
+
+                // NOTE: rewrites the config without the themes now kept in files
+                #[cfg(feature = "omni_themes")]
+                if had_inline_themes {
+                    self.last_saved_config_hash = None;
+                    tasks.push(Task::done(OmniAppMessage::SavingConfigRequested));
+                }
+
+                tasks.push(self.apply_cli_args(cli_args));
+
                 Task::batch(tasks)
             }
             #[cfg(feature = "config")]
@@ -242,7 +344,11 @@ impl OmniApp {
                     .set_buttons(rfd::MessageButtons::YesNo);
 
                 match dialog.show() {
-                    rfd::MessageDialogResult::Yes => Task::none(),
+                    rfd::MessageDialogResult::Yes => {
+                        let cli_args = self.pending_cli_args.take().unwrap_or_default();
+
+                        self.apply_cli_args(cli_args)
+                    }
                     rfd::MessageDialogResult::No => {
                         Task::done(OmniAppMessage::TerminateImmediately)
                     }
@@ -305,7 +411,58 @@ impl OmniApp {
                 .map(OmniAppMessage::SystemInfo),
             #[cfg(feature = "instax_framer")]
             OmniAppMessage::InstaxFramer(message) => {
-                let should_save_config = matches!(message, InstaxFramerMessage::ImagePicked(_));
+                let should_save_config = matches!(
+                    message,
+                    InstaxFramerMessage::ImagePicked(_)
+                        | InstaxFramerMessage::FilmSelected(_)
+                        | InstaxFramerMessage::TemplateSelected(_)
+                        | InstaxFramerMessage::CropDragEnded
+                        | InstaxFramerMessage::CropTurned { .. }
+                        | InstaxFramerMessage::CropReset
+                        | InstaxFramerMessage::FilmPresetSelected(_)
+                        | InstaxFramerMessage::AdjustmentReleased
+                        | InstaxFramerMessage::AdjustmentsReset
+                        | InstaxFramerMessage::ExportFormatSelected(_)
+                        | InstaxFramerMessage::ExportDpiSelected(_)
+                        | InstaxFramerMessage::ExportMetadataSelected(_)
+                        | InstaxFramerMessage::CaptionSubmitted
+                        | InstaxFramerMessage::CaptionFontSelected(_)
+                        | InstaxFramerMessage::CaptionColorSelected(_)
+                        | InstaxFramerMessage::CaptionAlignmentSelected(_)
+                        | InstaxFramerMessage::CaptionSizeReleased
+                        | InstaxFramerMessage::DateStampToggled(_)
+                        | InstaxFramerMessage::BatchNamePatternEdited(_)
+                        | InstaxFramerMessage::SheetImageToggled
+                        | InstaxFramerMessage::SheetCleared
+                        | InstaxFramerMessage::SheetPaperSelected(_)
+                        | InstaxFramerMessage::SheetSettingsReleased
+                        | InstaxFramerMessage::ImageLoadingFailed(..)
+                        | InstaxFramerMessage::RecentFilePinToggled(_)
+                        | InstaxFramerMessage::RecentFilesCleared
+                        | InstaxFramerMessage::CollageToggled
+                        | InstaxFramerMessage::CollageLayoutSelected(_)
+                        | InstaxFramerMessage::CollageSlotCountSelected(_)
+                        | InstaxFramerMessage::CollageSlotReleased(_)
+                        | InstaxFramerMessage::CollageSlotFilled
+                        | InstaxFramerMessage::CollageSlotPicked(_)
+                        | InstaxFramerMessage::CollageSlotCleared
+                        | InstaxFramerMessage::CollageImageLoaded(..)
+                );
+                #[cfg(feature = "config")]
+                let should_save_config = should_save_config
+                    || matches!(
+                        message,
+                        InstaxFramerMessage::ProjectOpened(..)
+                            | InstaxFramerMessage::EmbedInProjectToggled(_)
+                    );
+                #[cfg(feature = "camera")]
+                let should_save_config = should_save_config
+                    || matches!(
+                        message,
+                        InstaxFramerMessage::PhotoboothCountdownSelected(_)
+                            | InstaxFramerMessage::PhotoboothShotsSelected(_)
+                            | InstaxFramerMessage::PhotoboothIntervalSelected(_)
+                    );
 
                 let task = self
                     .instax_framer
@@ -327,6 +484,30 @@ impl OmniApp {
 
                 task.chain(save_task)
             }
+            #[cfg(feature = "single_instance")]
+            OmniAppMessage::SecondInstanceLaunched(cli_args) => self
+                .apply_cli_args(cli_args)
+                .chain(window::latest().and_then(|id| {
+                    window::minimize::<OmniAppMessage>(id, false).chain(window::gain_focus(id))
+                })),
+            #[cfg(feature = "web_server")]
+            OmniAppMessage::WebServer(message) => match message {
+                #[cfg(feature = "counter")]
+                WebServerMessage::CounterRequested(action) => {
+                    Task::done(OmniAppMessage::CounterEvent(action.into()))
+                }
+                #[cfg(feature = "omni_themes")]
+                WebServerMessage::ThemeRequested(theme_update) => Task::batch(
+                    theme_update
+                        .into_messages()
+                        .into_iter()
+                        .map(|message| Task::done(OmniAppMessage::OmniThemes(message))),
+                ),
+                message => self
+                    .web_server
+                    .update(message)
+                    .map(OmniAppMessage::WebServer),
+            },
         }
     }
 
@@ -342,10 +523,20 @@ impl OmniApp {
             self.counter
                 .subscription()
                 .map(OmniAppMessage::CounterEvent),
+            #[cfg(feature = "instax_framer")]
+            self.instax_framer
+                .subscription()
+                .map(OmniAppMessage::InstaxFramer),
             #[cfg(feature = "omni_themes")]
             self.omni_themes
                 .subscription()
                 .map(OmniAppMessage::OmniThemes),
+            #[cfg(feature = "web_server")]
+            self.web_server
+                .subscription()
+                .map(OmniAppMessage::WebServer),
+            #[cfg(feature = "single_instance")]
+            single_instance::subscription().map(OmniAppMessage::SecondInstanceLaunched),
         ])
     }
 
@@ -370,6 +561,11 @@ impl OmniApp {
             self.load_config(),
         ];
 
+        #[cfg(not(feature = "config"))]
+        if let Some(cli_args) = self.pending_cli_args.take() {
+            start_up_tasks.push(self.apply_cli_args(cli_args));
+        }
+
         Task::batch(start_up_tasks)
     }
 
@@ -0,0 +1,144 @@
+use iced::Color;
+#[cfg(feature = "config")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// An opaque sRGB color, written as `#rrggbb` in the config, theme and template files
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub struct HexColor(pub [u8; 3]);
+
+impl HexColor {
+    #[cfg(feature = "instax_framer")]
+    pub fn rgba(self) -> image::Rgba<u8> {
+        let [red, green, blue] = self.0;
+
+        image::Rgba([red, green, blue, 255])
+    }
+}
+
+impl From<Color> for HexColor {
+    fn from(color: Color) -> Self {
+        let [r, g, b, _] = color.into_rgba8();
+
+        Self([r, g, b])
+    }
+}
+
+impl From<HexColor> for Color {
+    fn from(HexColor([r, g, b]): HexColor) -> Self {
+        Color::from_rgb8(r, g, b)
+    }
+}
+
+impl std::fmt::Display for HexColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let HexColor([r, g, b]) = self;
+
+        write!(f, "#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+impl std::str::FromStr for HexColor {
+    type Err = String;
+
+    /// Accepts `#rrggbb`, `0xrrggbb` and bare `rrggbb`
+    fn from_str(hex: &str) -> Result<Self, Self::Err> {
+        let digits = hex.trim();
+        let digits = digits
+            .strip_prefix('#')
+            .or_else(|| digits.strip_prefix("0x"))
+            .unwrap_or(digits);
+
+        if digits.len() != 6 || !digits.is_ascii() {
+            return Err(format!("Not a #rrggbb color: {hex}"));
+        }
+
+        let channel = |index: usize| {
+            u8::from_str_radix(&digits[index * 2..index * 2 + 2], 16)
+                .map_err(|_| format!("Not a #rrggbb color: {hex}"))
+        };
+
+        Ok(Self([channel(0)?, channel(1)?, channel(2)?]))
+    }
+}
+
+#[cfg(feature = "config")]
+impl Serialize for HexColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "config")]
+impl<'de> Deserialize<'de> for HexColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_written_form() {
+        let color = HexColor([0x1d, 0x1f, 0xa1]);
+
+        assert_eq!("#1d1fa1".parse(), Ok(color));
+        assert_eq!("#1D1FA1".parse(), Ok(color));
+        assert_eq!("0x1d1fa1".parse(), Ok(color));
+        assert_eq!(" 1d1fa1 ".parse(), Ok(color));
+    }
+
+    #[test]
+    fn refuses_anything_else() {
+        for hex in [
+            "",
+            "#",
+            "#1d1f",
+            "#1d1fa1ff",
+            "#abc",
+            "#1d1fzz",
+            "#éééé",
+            "red",
+        ] {
+            assert_eq!(
+                hex.parse::<HexColor>(),
+                Err(format!("Not a #rrggbb color: {hex}"))
+            );
+        }
+    }
+
+    #[test]
+    fn displays_what_it_parses() {
+        let color = HexColor([0, 0x80, 0xff]);
+
+        assert_eq!(color.to_string(), "#0080ff");
+        assert_eq!(color.to_string().parse(), Ok(color));
+    }
+
+    #[test]
+    fn converts_with_iced_colors() {
+        let color = HexColor([12, 34, 56]);
+
+        assert_eq!(HexColor::from(Color::from(color)), color);
+    }
+
+    #[cfg(feature = "instax_framer")]
+    #[test]
+    fn draws_opaque() {
+        assert_eq!(HexColor([1, 2, 3]).rgba(), image::Rgba([1, 2, 3, 255]));
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn serializes_as_a_hex_string() {
+        let color = HexColor([0x1d, 0x1f, 0xa1]);
+
+        assert_eq!(ron::to_string(&color).unwrap(), "\"#1d1fa1\"");
+        assert_eq!(ron::from_str::<HexColor>("\"#1d1fa1\""), Ok(color));
+        assert!(ron::from_str::<HexColor>("\"#1d1f\"").is_err());
+        assert!(ron::from_str::<HexColor>("(29, 31, 161)").is_err());
+    }
+}
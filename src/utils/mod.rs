@@ -0,0 +1,42 @@
+#[cfg(any(feature = "omni_themes", feature = "instax_framer"))]
+mod hex_color;
+
+#[cfg(any(feature = "omni_themes", feature = "instax_framer"))]
+pub(crate) use hex_color::HexColor;
+
+/// A random hex string from OS randomness, `bytes` long before encoding
+#[cfg(any(feature = "web_server", feature = "single_instance"))]
+pub(crate) fn random_hex(bytes: usize) -> Result<String, String> {
+    let mut random = vec![0; bytes];
+    getrandom::fill(&mut random).map_err(|e| format!("Failed to get random bytes: {e}"))?;
+
+    Ok(random.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Compares every byte whatever the first difference, so timing tells nothing about the secret
+#[cfg(any(feature = "web_server", feature = "single_instance"))]
+pub(crate) fn secrets_match(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0, |difference, (given, expected)| {
+                difference | (given ^ expected)
+            })
+            == 0
+}
+
+/// Creates or replaces the file, readable by the current user only on Unix. Other systems keep
+/// the config directory private already
+#[cfg(any(feature = "web_server", feature = "single_instance"))]
+pub(crate) fn write_private_file(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    std::fs::create_dir_all(path.parent().unwrap_or(path))?;
+    options.open(path)?.write_all(contents)
+}